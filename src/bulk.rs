@@ -0,0 +1,205 @@
+//! Operations applied to many items at once.
+//! 
+//! # Spreadsheet Metadata Editing
+//! [`MetadataCsv`] applies metadata changes to many items based on a CSV file, such as one exported
+//! from a spreadsheet. The first row must be a header, containing an `identifier` column and one
+//! column per metadata field to modify. Each following row lists an item's identifier, and the values
+//! of each field for that item:
+//! 
+//! ```text
+//! identifier,title,subject[0],subject[1],notes
+//! item_one,First Item,maps,history,REMOVE_TAG
+//! item_two,Second Item,photos,,
+//! ```
+//! 
+//! * Empty cells are ignored, leaving the field unchanged.
+//! * A cell containing `REMOVE_TAG` removes the field from the item.
+//! * Columns named `field[N]` are combined into a list of values for `field`, ordered by `N`.
+
+use std::collections::BTreeMap;
+use std::io::Read;
+use crate::{Credentials, default_user_agent};
+use crate::client::Client;
+use crate::item::{Item, ItemError};
+use crate::item::metadata::{self, Change};
+
+/// Cell value which causes a field to be removed.
+pub const REMOVE_TAG: &str = "REMOVE_TAG";
+
+/// Request builder for applying metadata changes from a CSV file.
+/// 
+/// See the [module docs][`crate::bulk`] for the expected format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetadataCsv {
+    credentials: Option<Credentials>,
+    useragent: String,
+    client: Client,
+    dry_run: bool,
+}
+impl Default for MetadataCsv {
+    fn default() -> Self {
+        Self {
+            credentials: None,
+            useragent: default_user_agent(),
+            client: Client::shared(),
+            dry_run: false,
+        }
+    }
+}
+impl MetadataCsv {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Provide authentication credentials to be used when modifying each item.
+    pub fn with_credentials(mut self, credentials: Option<Credentials>) -> Self {
+        self.credentials = credentials;
+        
+        self
+    }
+    
+    /// Configures the User-Agent string provided in each request.
+    /// 
+    /// If `None` or if the string is empty, a [default][`default_user_agent`] will be used.
+    pub fn with_useragent(mut self, useragent: Option<String>) -> Self {
+        self.useragent = match useragent {
+            Some(useragent) if !useragent.is_empty() => useragent,
+            _ => default_user_agent(),
+        };
+        
+        self
+    }
+    
+    /// Configures the [`Client`] used to perform each request.
+    /// 
+    /// By default, the [shared client][`Client::shared`] is used.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        
+        self
+    }
+    
+    /// Configures whether or not changes should only be parsed and reported, without submitting them.
+    /// 
+    /// This is false (disabled) by default.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        
+        self
+    }
+    
+    /// Reads the CSV data and applies the changes of each row to its item.
+    /// 
+    /// Rows are processed in order, and a failure to modify one item does not stop the remaining rows
+    /// from being processed. The outcome of each row is returned in the same order.
+    /// 
+    /// # Errors
+    /// If the CSV data cannot be read or parsed, or the header has no `identifier` column, an
+    /// [`std::io::Error`] is returned before any changes are submitted.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use std::fs::File;
+    /// use iars::Credentials;
+    /// use iars::bulk::MetadataCsv;
+    /// 
+    /// let results = MetadataCsv::new()
+    ///     .with_credentials(Some(Credentials::new("abcdefghijklmnop", "1234567890123456")))
+    ///     .apply(File::open("metadata.csv")?)?;
+    /// 
+    /// for row in results {
+    ///     if let Err(err) = row.result {
+    ///         eprintln!("row {} ({}): {err}", row.row, row.identifier);
+    ///     }
+    /// }
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn apply(&self, reader: impl Read) -> Result<Vec<RowResult>, std::io::Error> {
+        let mut csv = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+        let headers = csv.headers()?.clone();
+        let id_col = headers.iter().position(|header| header.trim() == "identifier")
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "CSV header has no identifier column"))?;
+        
+        let mut rows = vec![];
+        for record in csv.records() {
+            let record = record?;
+            
+            // field name -> (list index, value)
+            let mut fields: BTreeMap<&str, Vec<(Option<usize>, &str)>> = BTreeMap::new();
+            for (col, value) in record.iter().enumerate() {
+                let Some(header) = headers.get(col).map(|header| header.trim()) else { continue };
+                if col == id_col || header.is_empty() || value.is_empty() {
+                    continue;
+                }
+                
+                let (key, index) = parse_column(header);
+                fields.entry(key).or_default().push((index, value));
+            }
+            
+            let mut changes = vec![];
+            for (key, mut values) in fields {
+                values.sort_by_key(|(index, _)| *index);
+                if values.iter().any(|(_, value)| *value == REMOVE_TAG) {
+                    changes.push(Change::Remove(key.to_string()));
+                } else if values.iter().any(|(index, _)| index.is_some()) {
+                    changes.push(Change::Set(key.to_string(), values.iter().map(|(_, value)| *value).collect::<Vec<_>>().into()));
+                } else if let Some((_, value)) = values.last() {
+                    changes.push(Change::Set(key.to_string(), (*value).into()));
+                }
+            }
+            
+            rows.push((record.get(id_col).unwrap_or_default().trim().to_string(), changes));
+        }
+        
+        Ok(rows.into_iter().enumerate().map(|(i, (identifier, changes))| {
+            let result = self.apply_row(&identifier, &changes);
+            
+            RowResult {
+                row: i + 1,
+                identifier,
+                changes,
+                result,
+            }
+        }).collect())
+    }
+    
+    fn apply_row(&self, identifier: &str, changes: &[Change]) -> Result<Option<metadata::Response>, ItemError> {
+        let item = Item::new(identifier)?
+            .with_credentials(self.credentials.clone())
+            .with_useragent(Some(self.useragent.clone()))
+            .with_client(self.client.clone());
+        
+        if self.dry_run || changes.is_empty() {
+            return Ok(None);
+        }
+        
+        let req = changes.iter().cloned().fold(item.modify_metadata(), |req, change| req.with_change(change));
+        
+        req.call().map(Some)
+    }
+}
+
+/// Outcome of applying a single CSV row.
+#[derive(Debug)]
+pub struct RowResult {
+    /// Number of the row, starting from 1 for the first row after the header.
+    pub row: usize,
+    
+    /// Identifier of the item the row applies to.
+    pub identifier: String,
+    
+    /// Changes parsed from the row.
+    pub changes: Vec<Change>,
+    
+    /// Result of submitting the changes. `Ok(None)` if nothing was submitted, either because this was
+    /// a [dry run][`MetadataCsv::with_dry_run`] or the row had no changes.
+    pub result: Result<Option<metadata::Response>, ItemError>,
+}
+
+/// Splits a column name of the form `field[N]` into the field name and list index.
+fn parse_column(header: &str) -> (&str, Option<usize>) {
+    header.strip_suffix(']')
+        .and_then(|rest| rest.split_once('['))
+        .and_then(|(key, index)| Some((key, Some(index.parse().ok()?))))
+        .unwrap_or((header, None))
+}
\ No newline at end of file
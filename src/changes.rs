@@ -0,0 +1,551 @@
+//! Tracking of changes to items.
+//! 
+//! The [Changes API](https://archive.org/developers/changes.html) lists the identifiers of items which
+//! have changed (e.g. were created, or had their files or metadata modified), in the order the changes
+//! happened. Each response includes a token, which is used to request the changes that follow. Tokens
+//! remain valid, so they can be saved and used later to resume where a previous run left off.
+//! 
+//! A [`Request`] starts either [from a specific day][`Request::from_date`], [from the beginning][`Request::from_beginning`]
+//! of the Internet Archive's history, or [from a saved token][`Request::from_token`]. Changes can be
+//! retrieved a batch at a time using [`Request::call`], or using the [`Changes`] iterator, which
+//! follows the tokens automatically.
+//! 
+//! # Example
+//! ```rust,no_run
+//! use iars::changes::Request;
+//! 
+//! let mut changes = Request::from_date(2024, 1, 31)?.changes();
+//! for change in changes.by_ref().take(1000) {
+//!     println!("{}", change?.identifier);
+//! }
+//! 
+//! // save the token somewhere, to resume from it later
+//! println!("resume from: {:?}", changes.token());
+//! # Ok::<(), iars::changes::ChangesError>(())
+//! ```
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::fmt::Formatter;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use serde::Deserialize;
+use crate::{Credentials, default_user_agent, glob};
+use crate::client::{self, Client, RawResponse, RequestContext};
+use crate::headers::RequestHeaderExt;
+
+/// Errors which may occur while retrieving changes.
+/// 
+/// Errors produced by a request include the [`RequestContext`] of that request, identifying the
+/// operation and URL involved.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ChangesError {
+    /// An error while performing [`std::io`] operations.
+    /// 
+    /// `context` is `None` if the error did not occur during a request.
+    Io {
+        context: Option<RequestContext>,
+        source: std::io::Error,
+    },
+    
+    /// An error while processing a [`ureq`] request.
+    Ureq {
+        context: RequestContext,
+        source: ureq::Error,
+    },
+    
+    /// A [`ureq`] request was successful, but returned a 403 Forbidden error code.
+    /// 
+    /// This is usually caused by not having valid [authentication][`crate::Credentials`].
+    Forbidden {
+        context: RequestContext,
+        response: ureq::Response,
+    },
+    
+    /// A [`ureq`] request was successful, but returned a 429 Too Many Requests error code.
+    /// 
+    /// This is only returned after any [automatic retries][`crate::client`] have been exhausted. Use
+    /// [`ResponseError::retry_after`][`crate::client::ResponseError::retry_after`] to find out how long the server asked the client to wait.
+    TooManyRequests {
+        context: RequestContext,
+        response: ureq::Response,
+    },
+    
+    /// The request was received, but refused by the Internet Archive with an error message (e.g. the
+    /// token is invalid).
+    Rejected {
+        context: RequestContext,
+        message: String,
+    },
+    
+    /// Start date passed to [`Request::from_date`] is not a valid date.
+    InvalidDate(String),
+}
+impl ChangesError {
+    /// Returns the context of the request this error occurred during, if any.
+    pub fn context(&self) -> Option<&RequestContext> {
+        match self {
+            Self::Io { context, .. } => context.as_ref(),
+            Self::Ureq { context, .. } | Self::Forbidden { context, .. } | Self::TooManyRequests { context, .. } | Self::Rejected { context, .. } => Some(context),
+            Self::InvalidDate(_) => None,
+        }
+    }
+    
+    pub(crate) fn io(context: &RequestContext, source: std::io::Error) -> Self {
+        Self::Io { context: Some(context.clone()), source }
+    }
+    
+    pub(crate) fn ureq(context: &RequestContext, source: ureq::Error) -> Self {
+        let context = context.with_error(&source);
+        match source {
+            ureq::Error::Status(403, response) => Self::Forbidden { context, response },
+            ureq::Error::Status(429, response) => Self::TooManyRequests { context, response },
+            _ => Self::Ureq { context, source },
+        }
+    }
+}
+impl fmt::Display for ChangesError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { context: Some(context), source } => write!(f, "{context}: {source}"),
+            Self::Io { context: None, source } => write!(f, "{source}"),
+            Self::Ureq { context, source } => write!(f, "{context}: {source}"),
+            Self::Forbidden { context, .. } => write!(f, "{context}: 403 Forbidden"),
+            Self::TooManyRequests { context, .. } => write!(f, "{context}: 429 Too Many Requests"),
+            Self::Rejected { context, message } => write!(f, "{context}: rejected: {message}"),
+            Self::InvalidDate(date) => write!(f, "invalid start date: '{date}'"),
+        }
+    }
+}
+impl std::error::Error for ChangesError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Ureq { source, .. } => Some(source),
+            Self::Forbidden { .. } | Self::TooManyRequests { .. } | Self::Rejected { .. } | Self::InvalidDate(_) => None,
+        }
+    }
+}
+impl client::sealed::FailedResponse for ChangesError {
+    fn failed_response(&self) -> Option<&ureq::Response> {
+        match self {
+            Self::Ureq { source: ureq::Error::Status(_, resp), .. } | Self::Forbidden { response: resp, .. } | Self::TooManyRequests { response: resp, .. } => Some(resp),
+            _ => None,
+        }
+    }
+    
+    fn failed_context(&self) -> Option<&RequestContext> {
+        self.context()
+    }
+    
+    fn failed_transport(&self) -> Option<&ureq::Transport> {
+        match self {
+            Self::Ureq { source: ureq::Error::Transport(transport), .. } => Some(transport),
+            _ => None,
+        }
+    }
+}
+impl From<std::io::Error> for ChangesError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io { context: None, source: value }
+    }
+}
+
+/// Position the changes are retrieved from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Start {
+    Date(String),
+    Beginning,
+    Token(String),
+}
+
+/// Request builder for retrieving changed items.
+/// 
+/// Requests are created using one of [`Request::from_date`], [`Request::from_beginning`], or [`Request::from_token`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    credentials: Option<Credentials>,
+    useragent: String,
+    client: Client,
+    start: Start,
+}
+impl Request {
+    fn new(start: Start) -> Self {
+        Self {
+            credentials: None,
+            useragent: default_user_agent(),
+            client: Client::shared(),
+            start,
+        }
+    }
+    
+    /// Creates a request for changes starting from the beginning of a day (UTC).
+    /// 
+    /// # Errors
+    /// If the date does not exist (e.g. February 30th), a [`ChangesError::InvalidDate`] is returned.
+    pub fn from_date(year: u16, month: u8, day: u8) -> Result<Self, ChangesError> {
+        let leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+        let days = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if leap => 29,
+            2 => 28,
+            _ => 0,
+        };
+        
+        let date = format!("{year:04}{month:02}{day:02}");
+        if year > 9999 || day == 0 || day > days {
+            return Err(ChangesError::InvalidDate(date));
+        }
+        
+        Ok(Self::new(Start::Date(date)))
+    }
+    
+    /// Creates a request for every change, starting from the beginning of the Internet Archive's history.
+    /// 
+    /// This is only useful for building a complete index of every item, which will take a very long time.
+    pub fn from_beginning() -> Self {
+        Self::new(Start::Beginning)
+    }
+    
+    /// Creates a request for changes following a token, as provided by a previous [`Response`] or
+    /// [`Changes::token`].
+    pub fn from_token(token: &str) -> Self {
+        Self::new(Start::Token(token.to_string()))
+    }
+    
+    /// Provide authentication credentials to be used with this request.
+    pub fn with_credentials(mut self, credentials: Option<Credentials>) -> Self {
+        self.credentials = credentials;
+        
+        self
+    }
+    
+    /// Configures the User-Agent string provided in this request.
+    /// 
+    /// If `None` or if the string is empty, a [default][`default_user_agent`] will be used.
+    pub fn with_useragent(mut self, useragent: Option<String>) -> Self {
+        self.useragent = match useragent {
+            Some(useragent) if !useragent.is_empty() => useragent,
+            _ => default_user_agent(),
+        };
+        
+        self
+    }
+    
+    /// Configures the [`Client`] used to perform this request.
+    /// 
+    /// By default, the [shared client][`Client::shared`] is used.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        
+        self
+    }
+    
+    /// Performs the request query to the Internet Archive, returning a single batch of changes.
+    /// 
+    /// # Errors
+    /// This may return [`ChangesError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    /// 
+    /// If any [I/O errors][`std::io::Error`] occur or the response fails to be deserialized, a [`ChangesError::Io`]
+    /// is returned. If the Changes API refuses the request, a [`ChangesError::Rejected`] is returned.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use iars::changes::Request;
+    /// 
+    /// let resp = Request::from_date(2024, 1, 31)?.call()?;
+    /// for change in resp.changes {
+    ///     println!("{}", change.identifier);
+    /// }
+    /// 
+    /// let next = Request::from_token(&resp.next_token.unwrap()).call()?;
+    /// # Ok::<(), iars::changes::ChangesError>(())
+    /// ```
+    pub fn call(&self) -> Result<Response, ChangesError> {
+        let (req, context) = self.request();
+        
+        let resp = match self.client.call(req) {
+            Ok(resp) | Err(ureq::Error::Status(400, resp)) => resp,
+            Err(err) => return Err(ChangesError::ureq(&context, err)),
+        };
+        let resp: InterimResponse = client::read_json(resp.into_reader()).map_err(|err| ChangesError::io(&context, err))?;
+        
+        match resp.error {
+            Some(message) => Err(ChangesError::Rejected { context, message }),
+            None => Ok(Response {
+                changes: resp.changes,
+                next_token: resp.next_token,
+                estimated_distance_from_head: resp.estimated_distance_from_head,
+                caught_up: resp.do_sleep_before_returning,
+            }),
+        }
+    }
+    
+    /// Performs the same request as [`Request::call`], but returns the [undecoded response][`RawResponse`].
+    /// 
+    /// Refused requests are returned as a response with a `400` status, rather than as an error.
+    /// 
+    /// # Errors
+    /// See [`Request::call`].
+    pub fn call_raw(&self) -> Result<RawResponse, ChangesError> {
+        let (req, context) = self.request();
+        let resp = match self.client.call(req) {
+            Ok(resp) | Err(ureq::Error::Status(400, resp)) => resp,
+            Err(err) => return Err(ChangesError::ureq(&context, err)),
+        };
+        
+        RawResponse::read(resp).map_err(|err| ChangesError::io(&context, err))
+    }
+    
+    fn request(&self) -> (ureq::Request, RequestContext) {
+        let url = "https://be-api.us.archive.org/changes/v1";
+        let context = RequestContext::new("changes", url, None);
+        
+        let mut req = self.client.request("GET", url)
+            .set("user-agent", &self.useragent);
+        
+        req = match &self.start {
+            Start::Date(date) => req.query("start_date", date),
+            Start::Beginning => req.query("start_from_beginning", "true"),
+            Start::Token(token) => req.query("token", token),
+        };
+        
+        if let Some(creds) = self.credentials.as_ref() {
+            req = req.set_header(creds.into());
+        }
+        
+        (req, context)
+    }
+    
+    /// Creates an [iterator][`Changes`] over every change, which requests batches as needed.
+    pub fn changes(&self) -> Changes {
+        Changes {
+            request: self.clone(),
+            token: None,
+            next: None,
+            buffer: VecDeque::new(),
+            include: vec![],
+            exclude: vec![],
+            filter: None,
+            follow: None,
+            caught_up: false,
+            finished: false,
+        }
+    }
+}
+
+/// Response data returned from a successful [changes request][`Request`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    /// Changed items, in the order they were changed.
+    pub changes: Vec<Change>,
+    
+    /// Token to [request][`Request::from_token`] the changes following this batch.
+    pub next_token: Option<String>,
+    
+    /// Estimated number of changes between this batch and the most recent change, if provided.
+    pub estimated_distance_from_head: Option<u64>,
+    
+    /// True if this batch reached the most recent change. Clients following changes should wait a
+    /// while before requesting the next batch.
+    pub caught_up: bool,
+}
+
+/// A single changed item.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Change {
+    /// Identifier of the item which changed.
+    pub identifier: String,
+    
+    /// All other fields provided for this change, which are not modeled by this crate.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InterimResponse {
+    #[serde(default)]
+    changes: Vec<Change>,
+    next_token: Option<String>,
+    estimated_distance_from_head: Option<u64>,
+    #[serde(default)]
+    do_sleep_before_returning: bool,
+    error: Option<String>,
+}
+
+type Filter = dyn Fn(&Change) -> bool + Send + Sync;
+
+/// Iterator over every change following a [request][`Request`]'s starting point.
+/// 
+/// By default, the iterator ends once it has reached the most recent change. If [following][`Changes::with_follow`]
+/// is enabled, it instead waits for new changes forever. Once an error is returned, the iterator ends.
+/// 
+/// Changes can be filtered by [identifier patterns][`Changes::with_include`] or by an [arbitrary predicate][`Changes::with_filter`].
+/// Filtered changes are discarded as each batch arrives, and are never yielded.
+/// 
+/// # Example
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use iars::changes::Request;
+/// 
+/// Request::from_token("...")
+///     .changes()
+///     .with_include("nasa_*")
+///     .with_exclude("*_test")
+///     .with_follow(Some(Duration::from_secs(60)))
+///     .for_each_change(|change| {
+///         println!("{} changed", change.identifier);
+///         true
+///     })?;
+/// # Ok::<(), iars::changes::ChangesError>(())
+/// ```
+#[derive(Clone)]
+pub struct Changes {
+    request: Request,
+    token: Option<String>,
+    next: Option<String>,
+    buffer: VecDeque<Change>,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    filter: Option<Arc<Filter>>,
+    follow: Option<Duration>,
+    caught_up: bool,
+    finished: bool,
+}
+impl fmt::Debug for Changes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Changes")
+            .field("request", &self.request)
+            .field("token", &self.token)
+            .field("next", &self.next)
+            .field("buffered", &self.buffer.len())
+            .field("include", &self.include)
+            .field("exclude", &self.exclude)
+            .field("follow", &self.follow)
+            .finish_non_exhaustive()
+    }
+}
+impl Changes {
+    /// Adds a glob pattern of identifiers to include (e.g. `nasa_*`). When any are added, only changes
+    /// to items matching at least one include pattern are yielded.
+    /// 
+    /// Patterns support `*` (any number of characters), `?` (a single character), and `[abc]`/`[a-z]`
+    /// character classes.
+    pub fn with_include(mut self, pattern: &str) -> Self {
+        self.include.push(pattern.to_string());
+        
+        self
+    }
+    
+    /// Adds a glob pattern of identifiers to exclude. Changes to items matching any exclude pattern are
+    /// never yielded, even if they match an [include pattern][`Changes::with_include`].
+    pub fn with_exclude(mut self, pattern: &str) -> Self {
+        self.exclude.push(pattern.to_string());
+        
+        self
+    }
+    
+    /// Configures a predicate which each change must satisfy to be yielded, in addition to any identifier
+    /// patterns.
+    /// 
+    /// This can be used to filter on the kind of change, or any other field the Changes API provides in
+    /// [`Change::extra`].
+    pub fn with_filter(mut self, filter: impl Fn(&Change) -> bool + Send + Sync + 'static) -> Self {
+        self.filter = Some(Arc::new(filter));
+        
+        self
+    }
+    
+    /// Configures the iterator to keep waiting for new changes after reaching the most recent change,
+    /// checking for more every `interval`.
+    /// 
+    /// If `None`, the iterator ends once it reaches the most recent change. This is `None` by default.
+    pub fn with_follow(mut self, interval: Option<Duration>) -> Self {
+        self.follow = interval;
+        
+        self
+    }
+    
+    /// Returns a token which resumes the iteration from the start of the most recently requested batch,
+    /// for use with [`Request::from_token`].
+    /// 
+    /// Changes in the batch which have already been yielded will be yielded again when resuming, but
+    /// no change is skipped. Returns `None` if no batch has been requested yet, or if the first batch
+    /// is still being yielded; resuming then requires the original request.
+    pub fn token(&self) -> Option<&str> {
+        match self.buffer.is_empty() {
+            true => self.next.as_deref().or(self.token.as_deref()),
+            false => self.token.as_deref(),
+        }
+    }
+    
+    /// Requests the next batch of changes.
+    fn fetch(&mut self) -> Result<(), ChangesError> {
+        if self.caught_up {
+            thread::sleep(self.follow.unwrap_or_default());
+        }
+        
+        let request = match self.next.as_ref() {
+            Some(token) => Request { start: Start::Token(token.clone()), ..self.request.clone() },
+            None => self.request.clone(),
+        };
+        let resp = request.call()?;
+        
+        self.token = self.next.take();
+        self.next = resp.next_token;
+        let changes: Vec<_> = resp.changes.into_iter().filter(|change| self.selects(change)).collect();
+        self.buffer.extend(changes);
+        self.caught_up = resp.caught_up;
+        self.finished = self.next.is_none() || (resp.caught_up && self.follow.is_none());
+        
+        Ok(())
+    }
+    
+    fn selects(&self, change: &Change) -> bool {
+        glob::selects(&self.include, &self.exclude, &change.identifier)
+            && self.filter.as_ref().is_none_or(|filter| filter(change))
+    }
+    
+    /// Calls `f` with every selected change, as each batch arrives, until it returns false.
+    /// 
+    /// When [following][`Changes::with_follow`], this only returns once `f` returns false or an error occurs.
+    /// On success, returns the number of changes passed to `f`.
+    /// 
+    /// # Errors
+    /// Returns the first error the iterator returns. Changes received before the error have already been
+    /// passed to `f`.
+    pub fn for_each_change(&mut self, mut f: impl FnMut(Change) -> bool) -> Result<usize, ChangesError> {
+        let mut count = 0;
+        for change in self.by_ref() {
+            count += 1;
+            if !f(change?) {
+                break;
+            }
+        }
+        
+        Ok(count)
+    }
+}
+impl Iterator for Changes {
+    type Item = Result<Change, ChangesError>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(change) = self.buffer.pop_front() {
+                return Some(Ok(change));
+            }
+            if self.finished {
+                return None;
+            }
+            
+            if let Err(err) = self.fetch() {
+                self.finished = true;
+                
+                return Some(Err(err));
+            }
+        }
+    }
+}
\ No newline at end of file
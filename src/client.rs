@@ -0,0 +1,1719 @@
+//! Shared HTTP client configuration.
+//! 
+//! Every request made by `iars` goes through a [`Client`], which owns the underlying [`ureq::Agent`]
+//! (and its connection pool) as well as any policies applied to each request, such as automatic
+//! retries.
+//! 
+//! Unless configured otherwise, all [items][`crate::Item`] and request builders use the same shared
+//! client. A custom client can be provided using their respective `with_client` methods.
+//! 
+//! # Retries
+//! The Internet Archive responds with `429 Too Many Requests` or `503 Service Unavailable` when it is
+//! overloaded or when a user is sending requests too quickly. These responses usually include a
+//! `Retry-After` header, stating how long the client should wait before trying again.
+//! 
+//! By default, such requests are automatically retried up to [3 times][`Client::with_retries`],
+//! waiting as long as the server asks (up to a [maximum][`Client::with_max_retry_wait`]). Requests
+//! which stream their body from a reader, such as file uploads, cannot be retried automatically.
+//! 
+//! Retries are idempotency-aware. `GET` and `HEAD` requests are also retried after connection errors
+//! (e.g. a reset connection). Requests which may have side effects (`POST`, `PUT`, and `DELETE`) are
+//! only retried when the failure provably happened before the server processed them: a `429` response,
+//! or a failure to connect. Other failures are only retried if [opted into][`Client::with_non_idempotent_retries`].
+//! 
+//! If automatic retries are disabled or exhausted, a 429 response is returned as a dedicated error
+//! variant (e.g. [`ItemError::TooManyRequests`][`crate::ItemError::TooManyRequests`]), so throttling
+//! can be distinguished from other failures. The wait duration can be retrieved from the returned
+//! error using [`ResponseError::retry_after`], along with any other throttling hints provided by the
+//! response as a [`QuotaInfo`]. A [snapshot][`ResponseSnapshot`] of the failed response's status and
+//! headers is also available from [`ResponseError::response`].
+//! 
+//! # Circuit Breaker
+//! Long-running batch jobs can waste hours retrying requests against an endpoint which is down. An
+//! optional [circuit breaker][`Client::with_circuit_breaker`] stops sending requests to a host after
+//! several consecutive failures, and instead fails immediately with a [`CircuitOpen`] error until a
+//! cool-down period has passed. Such failures can be detected using [`ResponseError::circuit_open`].
+//! 
+//! # Concurrency Limits
+//! When a client is shared by many threads (e.g. when mirroring many items in parallel), the number of
+//! simultaneous requests sent to each host can be [capped][`Client::with_host_limit`]. Threads which
+//! exceed the limit will block until another request to that host has finished.
+//! 
+//! # Name Resolution
+//! Hostnames are resolved by the operating system by default. Some networks resolve the Internet
+//! Archive's datanodes poorly, so a client can [pin hosts][`Client::with_resolved_host`] to specific
+//! addresses, [prefer an IP family][`Client::with_ip_family`], or use a [custom resolver][`Client::with_resolver`]
+//! (e.g. one which caches results).
+//! 
+//! # Connection Pooling
+//! Connections are kept alive and reused for later requests to the same host. By default, at most
+//! [100 idle connections][`Client::with_max_idle_connections`] are kept, and only [one per host][`Client::with_max_idle_connections_per_host`].
+//! Clients shared by many threads sending requests to the same host should raise the per-host limit,
+//! or most connections will be closed after a single request. Conversely, jobs which make hundreds of
+//! thousands of requests to many datanodes can lower the limits to avoid exhausting sockets.
+//! 
+//! The Internet Archive closes connections which have been idle for a while, and reusing one can cause
+//! a request to fail. An [idle timeout][`Client::with_idle_timeout`] discards pooled connections
+//! before that happens.
+//! 
+//! # Buffering
+//! File transfers are copied using [256 KiB][`Client::with_download_buffer_size`] buffers by default,
+//! rather than the 8 KiB buffers used by [`std::io::copy`], as small buffers limit throughput to the
+//! Internet Archive's datanodes on fast links.
+//! 
+//! # Timeouts
+//! Only [connecting][`Client::with_connect_timeout`] is limited by default, since file transfers can
+//! legitimately take hours. Stalled connections can be detected using [read][`Client::with_read_timeout`]
+//! and [write][`Client::with_write_timeout`] timeouts, which limit how long a single I/O operation may
+//! block, while an [overall timeout][`Client::with_timeout`] limits the duration of entire requests.
+//! 
+//! # Hooks
+//! Applications can observe or adjust every request made by a client, without writing a full
+//! [`Middleware`], using hooks: [`Client::with_on_request`] is called before each request is sent
+//! (e.g. to inject headers), [`Client::with_on_response`] after each response or failure is received
+//! (e.g. for logging or metrics), and [`Client::with_on_retry`] before each automatic retry.
+//! 
+//! # Statistics
+//! File uploads and downloads performed by a client can be monitored by attaching a
+//! [`TransferStats`] handle using [`Client::with_transfer_stats`]. Counters of every request, retry,
+//! and error can be collected (e.g. for Prometheus) by attaching a [`Metrics`] handle using [`Client::with_metrics`].
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Formatter;
+use std::fs::File;
+#[cfg(any(feature = "xml", feature = "json"))]
+use std::fs;
+use std::hash::{BuildHasher, Hasher, RandomState};
+use std::io::{self, BufReader, Read, Write};
+#[cfg(any(feature = "xml", feature = "json"))]
+use std::io::{Cursor, Seek, SeekFrom};
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+#[cfg(feature = "json")]
+use serde::de::DeserializeOwned;
+use ureq::{Agent, AgentBuilder, Middleware, MiddlewareNext, Request, Response};
+use crate::metrics::{ErrorCategory, Metrics};
+use crate::stats::{Direction, TrackedReader, Tracker, TransferStats};
+
+/// Default size of the buffers used for file transfers.
+const DEFAULT_BUFFER_SIZE: usize = 256 * 1024;
+
+/// Minimum size of the buffers used for file transfers.
+const MIN_BUFFER_SIZE: usize = 8 * 1024;
+
+/// Maximum number of bytes read from the body of a failed response while looking for an IAS3 error message.
+const MAX_ERROR_BODY: u64 = 64 * 1024;
+
+/// HTTP client shared by items and request builders.
+/// 
+/// Clients are cheap to clone, and clones share the same connection pool.
+#[derive(Clone)]
+pub struct Client {
+    inner: Arc<ClientInner>,
+}
+
+//TODO: Migrate to ureq 3.x. This is a breaking change, as ureq 2's `Error`, `Response`, `Request`, and
+// `Middleware` types appear throughout the public API (e.g. in every error type), so it's tracked separately
+// from the agent-level timeouts and pooling configured here.
+#[derive(Clone)]
+struct ClientInner {
+    pool: Arc<Mutex<Pool>>,
+    idle_timeout: Option<Duration>,
+    max_retries: usize,
+    max_retry_wait: Duration,
+    retry_non_idempotent: bool,
+    breaker: Option<CircuitBreaker>,
+    limiter: Arc<HostLimiter>,
+    stats: Option<TransferStats>,
+    metrics: Option<Metrics>,
+    retry_hook: Option<Arc<RetryHook>>,
+    upload_buffer_size: usize,
+    download_buffer_size: usize,
+    agent_config: AgentConfig,
+}
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Client")
+            .field("max_retries", &self.inner.max_retries)
+            .field("max_retry_wait", &self.inner.max_retry_wait)
+            .field("retry_non_idempotent", &self.inner.retry_non_idempotent)
+            .field("breaker", &self.inner.breaker)
+            .field("host_limits", &self.inner.limiter.state.lock().unwrap_or_else(|err| err.into_inner()).limits)
+            .field("stats", &self.inner.stats)
+            .field("metrics", &self.inner.metrics)
+            .field("upload_buffer_size", &self.inner.upload_buffer_size)
+            .field("download_buffer_size", &self.inner.download_buffer_size)
+            .field("resolved_hosts", &self.inner.agent_config.hosts)
+            .field("ip_family", &self.inner.agent_config.family)
+            .field("timeouts", &self.inner.agent_config.timeouts)
+            .field("pool", &self.inner.agent_config.pool)
+            .field("idle_timeout", &self.inner.idle_timeout)
+            .field("max_redirects", &self.inner.agent_config.max_redirects)
+            .finish_non_exhaustive()
+    }
+}
+impl PartialEq for Client {
+    /// Clients are only equal if they share the same configuration and connection pool.
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+impl Default for Client {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Client {
+    /// Creates a new client with its own connection pool.
+    pub fn new() -> Self {
+        let agent_config = AgentConfig::default();
+        
+        Self {
+            inner: Arc::new(ClientInner {
+                pool: Arc::new(Mutex::new(Pool::new(&agent_config))),
+                idle_timeout: None,
+                max_retries: 3,
+                max_retry_wait: Duration::from_secs(120),
+                retry_non_idempotent: false,
+                breaker: None,
+                limiter: Default::default(),
+                stats: None,
+                metrics: None,
+                retry_hook: None,
+                upload_buffer_size: DEFAULT_BUFFER_SIZE,
+                download_buffer_size: DEFAULT_BUFFER_SIZE,
+                agent_config,
+            }),
+        }
+    }
+    
+    /// Returns the client shared by all items and request builders that haven't been given a custom client.
+    pub fn shared() -> Self {
+        static SHARED: OnceLock<Client> = OnceLock::new();
+        
+        SHARED.get_or_init(Client::new).clone()
+    }
+    
+    /// Sets the maximum number of times a request will be retried after a `429 Too Many Requests`
+    /// or `503 Service Unavailable` response, or a connection error.
+    /// 
+    /// See the [module docs][`crate::client#retries`] for which requests are retried. A value of 0
+    /// disables automatic retries. This is 3 by default.
+    pub fn with_retries(mut self, max_retries: usize) -> Self {
+        Arc::make_mut(&mut self.inner).max_retries = max_retries;
+        
+        self
+    }
+    
+    /// Configures whether or not requests which may have side effects (`POST`, `PUT`, and `DELETE`) are
+    /// retried after failures which may have occurred after the server accepted the request.
+    /// 
+    /// Without this, such requests are only retried when the failure provably happened before the
+    /// server processed them: a `429 Too Many Requests` response, or a failure to connect. Blindly
+    /// retrying them can, for example, queue duplicate tasks. This is false (disabled) by default.
+    pub fn with_non_idempotent_retries(mut self, retry: bool) -> Self {
+        Arc::make_mut(&mut self.inner).retry_non_idempotent = retry;
+        
+        self
+    }
+    
+    /// Sets the longest time the client will wait before retrying a request.
+    /// 
+    /// If the server asks the client to wait longer than this, the request will not be retried,
+    /// and the error will be returned instead. This is 2 minutes by default.
+    pub fn with_max_retry_wait(mut self, max_retry_wait: Duration) -> Self {
+        Arc::make_mut(&mut self.inner).max_retry_wait = max_retry_wait;
+        
+        self
+    }
+    
+    /// Enables a circuit breaker, which short-circuits requests to a host after `threshold` consecutive
+    /// failures, for a period of `cooldown`.
+    /// 
+    /// Connection errors, `429 Too Many Requests`, and `5xx` responses count as failures. Any other
+    /// response resets the count. While a host's circuit is open, requests to it will immediately fail
+    /// with a [`CircuitOpen`] error. Once the cool-down has passed, the next request is sent normally;
+    /// if it fails again, the circuit is reopened straight away.
+    /// 
+    /// Automatic retries count towards the threshold. Disabled by default.
+    pub fn with_circuit_breaker(mut self, threshold: usize, cooldown: Duration) -> Self {
+        Arc::make_mut(&mut self.inner).breaker = Some(CircuitBreaker {
+            threshold: threshold.max(1),
+            cooldown,
+            hosts: Default::default(),
+        });
+        
+        self
+    }
+    
+    /// Limits the number of simultaneous requests this client (and its clones) will send to `host`.
+    /// 
+    /// Limits are set independently for each host, allowing, for example, uploads to `s3.us.archive.org`
+    /// to be restricted more heavily than reads from `archive.org`. Hosts without a limit are unrestricted.
+    /// 
+    /// A request counts towards the limit until its response has been received. For file downloads, this
+    /// includes the time spent transferring the file.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use iars::Client;
+    /// 
+    /// let client = Client::new()
+    ///     .with_host_limit("s3.us.archive.org", 2)
+    ///     .with_host_limit("archive.org", 8);
+    /// ```
+    pub fn with_host_limit(self, host: &str, limit: usize) -> Self {
+        self.inner.limiter.set_limit(host, limit.max(1));
+        
+        self
+    }
+    
+    /// Records statistics of every file upload and download performed by this client (and its clones).
+    /// 
+    /// See [`TransferStats`] for details.
+    pub fn with_transfer_stats(mut self, stats: TransferStats) -> Self {
+        Arc::make_mut(&mut self.inner).stats = Some(stats);
+        
+        self
+    }
+    
+    /// Records [metrics][`Metrics`] of every request performed by this client (and its clones).
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        Arc::make_mut(&mut self.inner).metrics = Some(metrics);
+        
+        self
+    }
+    
+    /// Resolves `host` to the given addresses, instead of looking it up.
+    /// 
+    /// This can be used to pin a datanode (e.g. `ia800000.us.archive.org`) to a known-good address.
+    /// Pinned addresses are still filtered by the [preferred IP family][`Client::with_ip_family`].
+    /// 
+    /// Changing name resolution creates a new connection pool for this client.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use std::net::Ipv4Addr;
+    /// use iars::Client;
+    /// 
+    /// let client = Client::new()
+    ///     .with_resolved_host("archive.org", &[Ipv4Addr::new(207, 241, 224, 2).into()]);
+    /// ```
+    pub fn with_resolved_host(mut self, host: &str, addrs: &[IpAddr]) -> Self {
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.agent_config.hosts.insert(host.to_ascii_lowercase(), addrs.to_vec());
+        inner.rebuild_agents();
+        
+        self
+    }
+    
+    /// Restricts connections to addresses of the given IP family.
+    /// 
+    /// This is [`IpFamily::Any`] by default. Changing name resolution creates a new connection pool
+    /// for this client.
+    pub fn with_ip_family(mut self, family: IpFamily) -> Self {
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.agent_config.family = family;
+        inner.rebuild_agents();
+        
+        self
+    }
+    
+    /// Uses a custom resolver to look up the addresses of hosts which have not been [pinned][`Client::with_resolved_host`].
+    /// 
+    /// The resolver is given a `host:port` pair, and returns every address the host can be reached at.
+    /// Changing name resolution creates a new connection pool for this client.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use std::net::ToSocketAddrs;
+    /// use iars::Client;
+    /// 
+    /// let client = Client::new().with_resolver(|netloc: &str| {
+    ///     println!("resolving {netloc}");
+    ///     netloc.to_socket_addrs().map(Iterator::collect)
+    /// });
+    /// ```
+    pub fn with_resolver(mut self, resolver: impl Fn(&str) -> std::io::Result<Vec<SocketAddr>> + Send + Sync + 'static) -> Self {
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.agent_config.resolver = Some(Arc::new(resolver));
+        inner.rebuild_agents();
+        
+        self
+    }
+    
+    /// Sets the size (in bytes) of the buffer used to read the source of file uploads.
+    /// 
+    /// Larger buffers reduce the number of reads from the source (e.g. a local disk or network share)
+    /// on fast connections. This is 256 KiB by default, and is raised to at least 8 KiB.
+    pub fn with_upload_buffer_size(mut self, size: usize) -> Self {
+        Arc::make_mut(&mut self.inner).upload_buffer_size = size.max(MIN_BUFFER_SIZE);
+        
+        self
+    }
+    
+    /// Sets the size (in bytes) of the buffer used to copy file downloads into their destination.
+    /// 
+    /// Larger buffers reduce the number of reads from the connection and writes to the destination,
+    /// which noticeably improves throughput on fast (e.g. 1 Gbps) links. This is 256 KiB by default,
+    /// and is raised to at least 8 KiB.
+    pub fn with_download_buffer_size(mut self, size: usize) -> Self {
+        Arc::make_mut(&mut self.inner).download_buffer_size = size.max(MIN_BUFFER_SIZE);
+        
+        self
+    }
+    
+    /// Sets the maximum time spent establishing a connection, including name resolution.
+    /// 
+    /// This is 30 seconds by default. Changing a timeout creates a new connection pool for this client.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.agent_config.timeouts.connect = timeout;
+        inner.rebuild_agents();
+        
+        self
+    }
+    
+    /// Sets the maximum time a single read from a connection may block, or `None` for no limit.
+    /// 
+    /// Unlike an [overall timeout][`Client::with_timeout`], this does not limit the duration of large
+    /// downloads, only how long the server may stall. This is disabled by default.
+    pub fn with_read_timeout(mut self, timeout: Option<Duration>) -> Self {
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.agent_config.timeouts.read = timeout;
+        inner.rebuild_agents();
+        
+        self
+    }
+    
+    /// Sets the maximum time a single write to a connection may block, or `None` for no limit.
+    /// 
+    /// This does not limit the duration of large uploads, only how long the server may stall. This is
+    /// disabled by default.
+    pub fn with_write_timeout(mut self, timeout: Option<Duration>) -> Self {
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.agent_config.timeouts.write = timeout;
+        inner.rebuild_agents();
+        
+        self
+    }
+    
+    /// Sets the maximum duration of an entire request, from connecting until the response body has been
+    /// read, or `None` for no limit.
+    /// 
+    /// This applies to file transfers as well, so it should only be set if every request is expected to
+    /// be small, or to a duration long enough for the largest transfer. This is disabled by default.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.agent_config.timeouts.overall = timeout;
+        inner.rebuild_agents();
+        
+        self
+    }
+    
+    /// Sets the maximum number of idle connections kept in the pool, across all hosts.
+    /// 
+    /// Once the pool is full, the least recently used connection is closed. A value of 0 disables
+    /// connection reuse entirely. This is 100 by default. Changing this creates a new connection pool
+    /// for this client.
+    pub fn with_max_idle_connections(mut self, max: usize) -> Self {
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.agent_config.pool.max_idle = max;
+        inner.rebuild_agents();
+        
+        self
+    }
+    
+    /// Sets the maximum number of idle connections kept in the pool for each host.
+    /// 
+    /// This should be at least the number of threads expected to send requests to the same host at
+    /// once, so that their connections can be reused. This is 1 by default. Changing this creates a new
+    /// connection pool for this client.
+    pub fn with_max_idle_connections_per_host(mut self, max: usize) -> Self {
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.agent_config.pool.max_idle_per_host = max;
+        inner.rebuild_agents();
+        
+        self
+    }
+    
+    /// Sets how long the connection pool may sit unused before its connections are discarded, or `None`
+    /// for no limit.
+    /// 
+    /// The underlying [`ureq::Agent`] doesn't track how long each connection has been idle, so this applies
+    /// to the pool as a whole: if no request has been started for longer than `timeout`, every pooled
+    /// connection is closed, and the next request opens a new one. This is disabled by default.
+    pub fn with_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        Arc::make_mut(&mut self.inner).idle_timeout = timeout;
+        
+        self
+    }
+    
+    /// Sets the maximum number of redirects followed by a single request.
+    /// 
+    /// File downloads are usually redirected from `archive.org` to the datanode storing the item. If a
+    /// `GET` or `HEAD` request is redirected more times than this, it fails with a [`TooManyRedirects`]
+    /// error, which includes the last `Location` the request was redirected to. This is 5 by default.
+    /// Changing this creates a new connection pool for this client.
+    pub fn with_max_redirects(mut self, max: usize) -> Self {
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.agent_config.max_redirects = max;
+        inner.rebuild_agents();
+        
+        self
+    }
+    
+    /// Adds a [`Middleware`] layer, which every request made by this client (and its clones) passes through.
+    /// 
+    /// Middleware can inspect or modify requests, and can respond to them without calling the next layer,
+    /// effectively replacing the network transport (e.g. to replay recorded responses, as done by the `vcr` feature).
+    /// Layers are called in the order they are added. Responses returned by middleware are subject to
+    /// the client's retry and circuit breaker policies like any other response.
+    /// 
+    /// Adding middleware creates a new connection pool for this client.
+    pub fn with_middleware(mut self, middleware: impl Middleware) -> Self {
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.agent_config.middleware.push(Arc::new(middleware));
+        inner.rebuild_agents();
+        
+        self
+    }
+    
+    /// Adds a hook which is called with every request before it is sent, and returns the request to
+    /// send in its place (e.g. with additional headers).
+    /// 
+    /// Hooks are implemented as [middleware][`Client::with_middleware`] layers, so they are called for
+    /// every attempt and every redirect, in the order they are added relative to other layers.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use iars::Client;
+    /// 
+    /// let client = Client::new()
+    ///     .with_on_request(|req| req.set("x-request-source", "my-indexer"));
+    /// ```
+    pub fn with_on_request(self, hook: impl Fn(Request) -> Request + Send + Sync + 'static) -> Self {
+        self.with_middleware(move |req: Request, next: MiddlewareNext| next.handle(hook(req)))
+    }
+    
+    /// Adds a hook which is called after every request, with the request, its result, and how long
+    /// it took to receive the response (excluding its body).
+    /// 
+    /// Like [`Client::with_on_request`], this is called for every attempt and every redirect. The
+    /// result is usually `Ok` even for error statuses, as they are only converted into errors after
+    /// passing through every layer.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use iars::Client;
+    /// 
+    /// let client = Client::new().with_on_response(|req, result, elapsed| match result {
+    ///     Ok(resp) => println!("{} {} -> {} in {elapsed:?}", req.method(), req.url(), resp.status()),
+    ///     Err(err) => println!("{} {} failed: {err}", req.method(), req.url()),
+    /// });
+    /// ```
+    pub fn with_on_response(self, hook: impl Fn(&Request, &Result<Response, ureq::Error>, Duration) + Send + Sync + 'static) -> Self {
+        self.with_middleware(move |req: Request, next: MiddlewareNext| {
+            let start = Instant::now();
+            let result = next.handle(req.clone());
+            hook(&req, &result, start.elapsed());
+            
+            result
+        })
+    }
+    
+    /// Sets a hook which is called before each [automatic retry][`crate::client#retries`], with the
+    /// request, the error which caused the retry, the number of the upcoming retry (starting at 1),
+    /// and how long the client will wait before sending it.
+    /// 
+    /// Only one retry hook can be set; setting another replaces it.
+    pub fn with_on_retry(mut self, hook: impl Fn(&Request, &ureq::Error, usize, Duration) + Send + Sync + 'static) -> Self {
+        Arc::make_mut(&mut self.inner).retry_hook = Some(Arc::new(hook));
+        
+        self
+    }
+    
+    /// Creates a new request using this client's agent.
+    pub(crate) fn request(&self, method: &str, url: &str) -> Request {
+        self.agent(true).request(method, url)
+    }
+    
+    /// Returns the agent used for requests which follow redirects automatically (or not), after
+    /// discarding the connection pool if it has been idle longer than the [idle timeout][`Client::with_idle_timeout`].
+    fn agent(&self, redirects: bool) -> Agent {
+        let mut pool = self.inner.pool.lock().unwrap_or_else(|err| err.into_inner());
+        if self.inner.idle_timeout.is_some_and(|timeout| pool.last_used.elapsed() > timeout) {
+            *pool = Pool::new(&self.inner.agent_config);
+        }
+        pool.last_used = Instant::now();
+        
+        match redirects {
+            true => pool.agent.clone(),
+            false => pool.unredirected.clone(),
+        }
+    }
+    
+    /// Performs a request without a body, retrying it if necessary.
+    /// 
+    /// Redirects of `GET` and `HEAD` requests are followed manually, so that exceeding the [redirect limit][`Client::with_max_redirects`]
+    /// produces a [`TooManyRedirects`] error. Authentication headers are not kept after a redirect.
+    pub(crate) fn call(&self, req: Request) -> Result<Response, ureq::Error> {
+        match req.method() {
+            "GET" | "HEAD" => self.follow(&req, true, &mut 0, false),
+            _ => self.retry(&req, true, &mut 0, || req.clone().call()),
+        }
+    }
+    
+    /// Performs a request for a file download, retrying it if necessary.
+    /// 
+    /// The returned [`Download`] counts towards the host's [limit][`Client::with_host_limit`], and is
+    /// recorded as a transfer, until its body has been read.
+    pub(crate) fn call_download(&self, req: Request) -> Result<Download, ureq::Error> {
+        let permit = self.inner.limiter.acquire(&host(&req));
+        
+        let mut retries = 0;
+        let resp = self.follow(&req, false, &mut retries, false)?;
+        
+        let tracker = self.inner.stats.as_ref().map(|stats| {
+            let total = resp.header("content-length").and_then(|len| len.parse().ok());
+            stats.start(resp.get_url(), Direction::Download, total, retries)
+        });
+        
+        Ok(Download {
+            resp,
+            permit,
+            tracker,
+            metrics: self.inner.metrics.clone(),
+        })
+    }
+    
+    /// Performs a request without a body, following redirects manually so that [authentication headers][`is_authenticated_host`]
+    /// are kept when redirected between Internet Archive hosts.
+    pub(crate) fn call_authenticated(&self, req: Request) -> Result<Response, ureq::Error> {
+        self.follow(&req, true, &mut 0, true)
+    }
+    
+    /// Performs a request for a file download like [`Client::call_download`], following redirects
+    /// like [`Client::call_authenticated`].
+    pub(crate) fn call_download_authenticated(&self, req: Request) -> Result<Download, ureq::Error> {
+        let permit = self.inner.limiter.acquire(&host(&req));
+        
+        let mut retries = 0;
+        let resp = self.follow(&req, false, &mut retries, true)?;
+        
+        let tracker = self.inner.stats.as_ref().map(|stats| {
+            let total = resp.header("content-length").and_then(|len| len.parse().ok());
+            stats.start(resp.get_url(), Direction::Download, total, retries)
+        });
+        
+        Ok(Download {
+            resp,
+            permit,
+            tracker,
+            metrics: self.inner.metrics.clone(),
+        })
+    }
+    
+    /// Sends a request (retrying it if necessary), and follows any redirects.
+    /// 
+    /// ureq strips the `Authorization` and `Cookie` headers whenever a redirect changes hosts, which
+    /// breaks downloads of restricted files (`archive.org` redirects them to a datanode). Headers are
+    /// instead kept for every redirect to an Internet Archive host, and stripped for any other host.
+    /// If `authenticated` is false, they're stripped after any redirect instead, like ureq does.
+    /// 
+    /// Once the [redirect limit][`Client::with_max_redirects`] is exceeded, a [`TooManyRedirects`] error is returned.
+    fn follow(&self, req: &Request, limited: bool, retries: &mut usize, authenticated: bool) -> Result<Response, ureq::Error> {
+        let limit = self.inner.agent_config.max_redirects;
+        
+        let mut url = req.url().to_string();
+        for redirect in 0..=limit {
+            let keep_auth = match authenticated {
+                true => is_authenticated_host(&url),
+                false => redirect == 0,
+            };
+            let hop = req.header_names().into_iter()
+                .filter(|name| keep_auth || !(name.eq_ignore_ascii_case("authorization") || name.eq_ignore_ascii_case("cookie")))
+                .fold(self.agent(false).request(req.method(), &url), |hop, name| match req.header(&name) {
+                    Some(value) => hop.set(&name, value),
+                    None => hop,
+                });
+            
+            let resp = self.retry(&hop, limited, retries, || hop.clone().call())?;
+            match (resp.status(), resp.header("location")) {
+                (300..=399, Some(location)) => url = resolve_location(&url, location),
+                _ => return Ok(resp),
+            }
+        }
+        
+        Err(std::io::Error::other(TooManyRedirects {
+            url: req.url().to_string(),
+            location: url,
+            limit,
+        }).into())
+    }
+    
+    /// Performs a request with a form-encoded body, retrying it if necessary.
+    #[cfg(feature = "json")]
+    pub(crate) fn send_form(&self, req: Request, data: &[(&str, &str)]) -> Result<Response, ureq::Error> {
+        self.retry(&req, true, &mut 0, || req.clone().send_form(data))
+    }
+    
+    /// Performs a request with a body streamed from `reader`. These requests cannot be retried.
+    pub(crate) fn send(&self, req: Request, reader: impl Read) -> Result<Response, ureq::Error> {
+        let host = host(&req);
+        self.check_circuit(&host)?;
+        
+        let _permit = self.inner.limiter.acquire(&host);
+        let tracker = self.inner.stats.as_ref().map(|stats| {
+            let total = req.header("content-length").and_then(|len| len.parse().ok());
+            stats.start(req.url(), Direction::Upload, total, 0)
+        });
+        
+        let reader = BufReader::with_capacity(self.inner.upload_buffer_size, reader);
+        let metrics = self.inner.metrics.clone().map(|metrics| (metrics, Direction::Upload));
+        let method = req.method().to_string();
+        let started = Instant::now();
+        let result = req.send(TrackedReader { inner: reader, tracker, metrics });
+        self.record(&host, &result);
+        self.observe(&method, &host, &result, started.elapsed());
+        
+        result
+    }
+    
+    /// Copies the body of a download into `writer`, using the client's [download buffer size][`Client::with_download_buffer_size`].
+    pub(crate) fn copy(&self, mut reader: impl Read, mut writer: impl Write) -> io::Result<u64> {
+        let mut buf = vec![0; self.inner.download_buffer_size];
+        let mut written = 0;
+        loop {
+            let len = match reader.read(&mut buf) {
+                Ok(0) => return Ok(written),
+                Ok(len) => len,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            
+            writer.write_all(&buf[..len])?;
+            written += len as u64;
+        }
+    }
+    
+    /// Sends a request until it succeeds, or fails for a reason other than throttling.
+    /// 
+    /// If `limited` is false, the caller is responsible for holding a [`HostPermit`].
+    fn retry(&self, req: &Request, limited: bool, attempt: &mut usize, mut send: impl FnMut() -> Result<Response, ureq::Error>) -> Result<Response, ureq::Error> {
+        let host = host(req);
+        loop {
+            self.check_circuit(&host)?;
+            
+            let permit = limited.then(|| self.inner.limiter.acquire(&host));
+            let started = Instant::now();
+            let result = send();
+            drop(permit);
+            self.record(&host, &result);
+            self.observe(req.method(), &host, &result, started.elapsed());
+            
+            let err = match result {
+                Err(err) if *attempt < self.inner.max_retries && self.is_retryable(req.method(), &err) => err,
+                result => return result,
+            };
+            
+            // without a Retry-After header, back off exponentially starting at 1 second
+            let wait = match &err {
+                ureq::Error::Status(_, resp) => retry_after(resp),
+                ureq::Error::Transport(_) => None,
+            }.unwrap_or(Duration::from_secs(1 << (*attempt).min(6)));
+            
+            if wait > self.inner.max_retry_wait {
+                return Err(err);
+            }
+            
+            if let Some(hook) = self.inner.retry_hook.as_ref() {
+                hook(req, &err, *attempt + 1, wait);
+            }
+            
+            thread::sleep(wait);
+            *attempt += 1;
+            
+            if let Some(stats) = self.inner.stats.as_ref() {
+                stats.add_retry();
+            }
+            if let Some(metrics) = self.inner.metrics.as_ref() {
+                metrics.add_retry();
+            }
+        }
+    }
+    
+    /// Returns true if a request using `method` may be retried after failing with `err`.
+    fn is_retryable(&self, method: &str, err: &ureq::Error) -> bool {
+        let idempotent = matches!(method, "GET" | "HEAD" | "OPTIONS") || self.inner.retry_non_idempotent;
+        
+        match err {
+            // the request was refused before being processed
+            ureq::Error::Status(429, _) => true,
+            ureq::Error::Status(503, _) => idempotent,
+            ureq::Error::Status(_, _) => false,
+            
+            // nothing was sent
+            ureq::Error::Transport(transport) if transport.kind() == ureq::ErrorKind::ConnectionFailed => true,
+            ureq::Error::Transport(transport) => idempotent && transport.kind() == ureq::ErrorKind::Io,
+        }
+    }
+    
+    fn check_circuit(&self, host: &str) -> Result<(), ureq::Error> {
+        let Some(breaker) = self.inner.breaker.as_ref() else { return Ok(()) };
+        
+        let hosts = breaker.hosts.lock().unwrap_or_else(|err| err.into_inner());
+        match hosts.get(host).and_then(|state| state.open_until) {
+            Some(until) if until > Instant::now() => {
+                if let Some(metrics) = self.inner.metrics.as_ref() {
+                    metrics.add_error(ErrorCategory::CircuitOpen);
+                }
+                
+                Err(std::io::Error::other(CircuitOpen {
+                    host: host.to_string(),
+                    retry_in: until - Instant::now(),
+                }).into())
+            },
+            _ => Ok(()),
+        }
+    }
+    
+    /// Records a request in the client's [metrics][`Client::with_metrics`], if any.
+    fn observe(&self, method: &str, host: &str, result: &Result<Response, ureq::Error>, elapsed: Duration) {
+        if let Some(metrics) = self.inner.metrics.as_ref() {
+            metrics.add_request(method, host, result.as_ref().err().map(ErrorCategory::of), elapsed);
+        }
+    }
+    
+    fn record(&self, host: &str, result: &Result<Response, ureq::Error>) {
+        let Some(breaker) = self.inner.breaker.as_ref() else { return };
+        
+        let failed = match result {
+            Ok(_) => false,
+            Err(ureq::Error::Status(status, _)) => *status == 429 || *status >= 500,
+            Err(ureq::Error::Transport(_)) => true,
+        };
+        
+        let mut hosts = breaker.hosts.lock().unwrap_or_else(|err| err.into_inner());
+        let state = hosts.entry(host.to_string()).or_default();
+        if failed {
+            state.failures += 1;
+            if state.failures >= breaker.threshold {
+                state.open_until = Some(Instant::now() + breaker.cooldown);
+            }
+        } else {
+            *state = HostState::default();
+        }
+    }
+}
+
+/// IP address family used for connections. See [`Client::with_ip_family`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IpFamily {
+    /// Both IPv4 and IPv6 addresses may be used.
+    #[default]
+    Any,
+    
+    /// Only IPv4 addresses are used.
+    V4,
+    
+    /// Only IPv6 addresses are used.
+    V6,
+}
+
+type Resolver = Arc<dyn Fn(&str) -> std::io::Result<Vec<SocketAddr>> + Send + Sync>;
+
+type RetryHook = dyn Fn(&Request, &ureq::Error, usize, Duration) + Send + Sync;
+
+struct SharedMiddleware(Arc<dyn Middleware>);
+impl Middleware for SharedMiddleware {
+    fn handle(&self, request: Request, next: MiddlewareNext) -> Result<Response, ureq::Error> {
+        self.0.handle(request, next)
+    }
+}
+
+/// Settings which require the [`Agent`] to be rebuilt when changed.
+#[derive(Clone)]
+struct AgentConfig {
+    hosts: HashMap<String, Vec<IpAddr>>,
+    family: IpFamily,
+    resolver: Option<Resolver>,
+    middleware: Vec<Arc<dyn Middleware>>,
+    timeouts: Timeouts,
+    pool: PoolLimits,
+    max_redirects: usize,
+}
+impl Default for AgentConfig {
+    fn default() -> Self {
+        Self {
+            hosts: Default::default(),
+            family: Default::default(),
+            resolver: None,
+            middleware: vec![],
+            timeouts: Default::default(),
+            pool: Default::default(),
+            max_redirects: 5,
+        }
+    }
+}
+impl AgentConfig {
+    fn builder(&self) -> AgentBuilder {
+        let resolution = self.clone();
+        
+        let mut builder = self.middleware.iter().fold(AgentBuilder::new(), |builder, middleware| builder.middleware(SharedMiddleware(middleware.clone())))
+            .resolver(move |netloc: &str| resolution.resolve(netloc))
+            .timeout_connect(self.timeouts.connect)
+            .max_idle_connections(self.pool.max_idle)
+            .max_idle_connections_per_host(self.pool.max_idle_per_host)
+            // ureq stops once the number of requests reaches the limit, rather than the number of redirects
+            .redirects(self.max_redirects.saturating_add(1).try_into().unwrap_or(u32::MAX));
+        
+        if let Some(timeout) = self.timeouts.read {
+            builder = builder.timeout_read(timeout);
+        }
+        if let Some(timeout) = self.timeouts.write {
+            builder = builder.timeout_write(timeout);
+        }
+        if let Some(timeout) = self.timeouts.overall {
+            builder = builder.timeout(timeout);
+        }
+        
+        builder
+    }
+    
+    fn resolve(&self, netloc: &str) -> std::io::Result<Vec<SocketAddr>> {
+        let (host, port) = netloc.rsplit_once(':')
+            .and_then(|(host, port)| Some((host, port.parse::<u16>().ok()?)))
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("invalid address: {netloc}")))?;
+        
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        
+        let addrs: Vec<SocketAddr> = match self.hosts.get(&host.to_ascii_lowercase()) {
+            Some(addrs) => addrs.iter().map(|addr| SocketAddr::new(*addr, port)).collect(),
+            None => match self.resolver.as_ref() {
+                Some(resolver) => resolver(netloc)?,
+                None => netloc.to_socket_addrs()?.collect(),
+            },
+        };
+        
+        let addrs: Vec<SocketAddr> = addrs.into_iter().filter(|addr| match self.family {
+            IpFamily::Any => true,
+            IpFamily::V4 => addr.is_ipv4(),
+            IpFamily::V6 => addr.is_ipv6(),
+        }).collect();
+        
+        if addrs.is_empty() {
+            return Err(std::io::Error::new(std::io::ErrorKind::NotFound, format!("no {:?} addresses found for {host}", self.family)));
+        }
+        
+        Ok(addrs)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Timeouts {
+    connect: Duration,
+    read: Option<Duration>,
+    write: Option<Duration>,
+    overall: Option<Duration>,
+}
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            connect: Duration::from_secs(30),
+            read: None,
+            write: None,
+            overall: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PoolLimits {
+    max_idle: usize,
+    max_idle_per_host: usize,
+}
+impl Default for PoolLimits {
+    fn default() -> Self {
+        Self {
+            max_idle: 100,
+            max_idle_per_host: 1,
+        }
+    }
+}
+
+/// Agents sharing a connection pool, and when they were last used.
+struct Pool {
+    agent: Agent,
+    unredirected: Agent,
+    last_used: Instant,
+}
+impl Pool {
+    fn new(config: &AgentConfig) -> Self {
+        Self {
+            agent: config.builder().build(),
+            unredirected: config.builder().redirects(0).build(),
+            last_used: Instant::now(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CircuitBreaker {
+    threshold: usize,
+    cooldown: Duration,
+    hosts: Arc<Mutex<HashMap<String, HostState>>>,
+}
+
+#[derive(Debug, Default)]
+struct HostState {
+    failures: usize,
+    open_until: Option<Instant>,
+}
+
+#[derive(Debug, Default)]
+struct HostLimiter {
+    state: Mutex<LimiterState>,
+    released: Condvar,
+}
+impl HostLimiter {
+    /// Sets the limit of `host`. Requests already in progress keep counting towards the new limit.
+    fn set_limit(&self, host: &str, limit: usize) {
+        self.state.lock().unwrap_or_else(|err| err.into_inner()).limits.insert(host.to_string(), limit);
+        
+        // a raised limit may allow waiting requests to proceed
+        self.released.notify_all();
+    }
+    
+    /// Blocks until a request to `host` is permitted.
+    fn acquire(self: &Arc<Self>, host: &str) -> HostPermit {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        if !state.limits.contains_key(host) {
+            return HostPermit { limiter: None, host: String::new() };
+        }
+        
+        while state.active.get(host).copied().unwrap_or(0) >= state.limits[host] {
+            state = self.released.wait(state).unwrap_or_else(|err| err.into_inner());
+        }
+        *state.active.entry(host.to_string()).or_default() += 1;
+        
+        HostPermit { limiter: Some(self.clone()), host: host.to_string() }
+    }
+}
+
+#[derive(Debug, Default)]
+struct LimiterState {
+    limits: HashMap<String, usize>,
+    active: HashMap<String, usize>,
+}
+
+/// Response to a file download request, whose body has not yet been read.
+pub(crate) struct Download {
+    pub(crate) resp: Response,
+    permit: HostPermit,
+    tracker: Option<Tracker>,
+    metrics: Option<Metrics>,
+}
+impl Download {
+    /// Converts the download into a reader of the response body.
+    pub(crate) fn into_reader(self) -> DownloadReader {
+        DownloadReader {
+            inner: TrackedReader {
+                inner: self.resp.into_reader(),
+                tracker: self.tracker,
+                metrics: self.metrics.map(|metrics| (metrics, Direction::Download)),
+            },
+            _permit: self.permit,
+        }
+    }
+}
+
+/// Reader of a file download's body.
+pub(crate) struct DownloadReader {
+    inner: TrackedReader<Box<dyn Read + Send + Sync + 'static>>,
+    _permit: HostPermit,
+}
+impl Read for DownloadReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+/// Permission to send a request to a host with a [concurrency limit][`Client::with_host_limit`].
+/// 
+/// The request stops counting towards the limit once this is dropped.
+pub(crate) struct HostPermit {
+    limiter: Option<Arc<HostLimiter>>,
+    host: String,
+}
+impl Drop for HostPermit {
+    fn drop(&mut self) {
+        if let Some(limiter) = self.limiter.as_ref() {
+            if let Some(count) = limiter.state.lock().unwrap_or_else(|err| err.into_inner()).active.get_mut(&self.host) {
+                *count -= 1;
+            }
+            limiter.released.notify_all();
+        }
+    }
+}
+
+/// Error produced when a request is short-circuited by the [circuit breaker][`Client::with_circuit_breaker`].
+/// 
+/// This error is provided as the source of an [`std::io::Error`], wrapped within a [`ureq::Error::Transport`]. It can be
+/// retrieved from the returned error using [`ResponseError::circuit_open`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CircuitOpen {
+    /// Host the request was going to be sent to.
+    pub host: String,
+    
+    /// Time remaining until requests to the host will be attempted again.
+    pub retry_in: Duration,
+}
+impl fmt::Display for CircuitOpen {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "circuit breaker is open for {} (retrying in {:?})", self.host, self.retry_in)
+    }
+}
+impl std::error::Error for CircuitOpen {}
+
+/// Error produced when a request is redirected more times than the [limit][`Client::with_max_redirects`].
+/// 
+/// This error is provided as the source of an [`std::io::Error`], wrapped within a [`ureq::Error::Transport`]. It can be
+/// retrieved from the returned error using [`ResponseError::too_many_redirects`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TooManyRedirects {
+    /// URL which was originally requested.
+    pub url: String,
+    
+    /// URL of the last redirect's `Location` header, which was not followed.
+    pub location: String,
+    
+    /// Maximum number of redirects which were allowed.
+    pub limit: usize,
+}
+impl fmt::Display for TooManyRedirects {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "too many redirects (more than {}) requesting {}, last redirected to {}", self.limit, self.url, self.location)
+    }
+}
+impl std::error::Error for TooManyRedirects {}
+
+/// Throttling and quota hints provided by the Internet Archive.
+/// 
+/// These are available from errors using [`ResponseError::quota`], which are populated from the headers
+/// (and any IAS3 error message) of the failed response, as well as from dedicated endpoints such as
+/// [`Item::check_upload_limit`][`crate::Item::check_upload_limit`] and [`tasks::rate_limit`][`crate::tasks::rate_limit`].
+/// Every field is optional, as each source only provides some of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QuotaInfo {
+    /// HTTP status code of the response.
+    pub status: Option<u16>,
+    
+    /// How long the server asked the client to wait before sending more requests.
+    pub retry_after: Option<Duration>,
+    
+    /// Maximum number of requests or tasks allowed.
+    pub limit: Option<u64>,
+    
+    /// Number of requests or tasks still allowed before the limit is reached.
+    pub remaining: Option<u64>,
+    
+    /// Number of tasks which are queued or running, and count towards the limit.
+    pub in_flight: Option<u64>,
+    
+    /// Whether the limit has been reached, and new requests will be refused.
+    pub over_limit: Option<bool>,
+    
+    /// Human readable explanation of why requests are being limited, if given (e.g. the message of an
+    /// IAS3 `SlowDown` error).
+    pub detail: Option<String>,
+}
+impl QuotaInfo {
+    /// Collects the hints provided by a response's status and headers.
+    pub(crate) fn from_response(resp: &Response) -> Self {
+        let header = |names: &[&str]| names.iter().find_map(|name| resp.header(name)?.trim().parse::<u64>().ok());
+        
+        let limit = header(&["x-ratelimit-limit", "ratelimit-limit"]);
+        let remaining = header(&["x-ratelimit-remaining", "ratelimit-remaining"]);
+        
+        Self {
+            status: Some(resp.status()),
+            retry_after: retry_after(resp),
+            limit,
+            remaining,
+            in_flight: None,
+            over_limit: remaining.map(|remaining| remaining == 0).or((resp.status() == 429).then_some(true)),
+            detail: None,
+        }
+    }
+}
+
+/// Describes the request which an error occurred during.
+/// 
+/// Included in errors such as [`ItemError`][`crate::ItemError`], so that a failure partway through a
+/// large batch of operations can be traced back to the item and URL involved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RequestContext {
+    /// Name of the operation being performed (e.g. `"download_file"`).
+    pub operation: &'static str,
+    
+    /// URL of the request, excluding any query parameters.
+    pub url: String,
+    
+    /// Identifier of the item involved, if any.
+    pub identifier: Option<String>,
+    
+    /// Status and headers of the response, if the request failed with a non-2xx status.
+    pub response: Option<ResponseSnapshot>,
+}
+impl RequestContext {
+    pub(crate) fn new(operation: &'static str, url: &str, identifier: Option<&str>) -> Self {
+        Self {
+            operation,
+            url: url.to_string(),
+            identifier: identifier.map(|id| id.to_string()),
+            response: None,
+        }
+    }
+    
+    /// Copies the context, including a snapshot of the response if `err` is a status error.
+    pub(crate) fn with_error(&self, err: &ureq::Error) -> Self {
+        let mut context = self.clone();
+        if let ureq::Error::Status(_, resp) = err {
+            context.response = Some(ResponseSnapshot::new(resp));
+        }
+        
+        context
+    }
+}
+impl fmt::Display for RequestContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.identifier.as_ref() {
+            Some(identifier) => write!(f, "{} of '{identifier}' ({})", self.operation, self.url),
+            None => write!(f, "{} ({})", self.operation, self.url),
+        }
+    }
+}
+
+/// Snapshot of the status and headers of a failed response.
+/// 
+/// The Internet Archive includes diagnostics in some responses (e.g. `x-archive-*` headers), which are
+/// useful to include in bug reports and support requests. Every error produced by a non-2xx response
+/// provides a snapshot through [`ResponseError::response`], even when the response itself is not kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseSnapshot {
+    /// HTTP status code of the response.
+    pub status: u16,
+    
+    /// HTTP status text of the response (e.g. `Not Found`).
+    pub status_text: String,
+    
+    /// Response headers, in the order they were received. Header names are lowercase.
+    pub headers: Vec<(String, String)>,
+    
+    /// Explanation given in the body of the response, such as the `Code` and `Message` of an IAS3 error.
+    /// 
+    /// Only IAS3 error bodies are read, so this is `None` for other APIs.
+    pub detail: Option<String>,
+}
+impl ResponseSnapshot {
+    pub(crate) fn new(resp: &Response) -> Self {
+        Self {
+            status: resp.status(),
+            status_text: resp.status_text().to_string(),
+            headers: response_headers(resp),
+            detail: None,
+        }
+    }
+    
+    /// Returns the value of the first header named `name` (case-insensitive), if present.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+    
+    /// Iterates over the Internet Archive's diagnostic headers, whose names start with `x-archive-`.
+    pub fn archive_headers(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.headers.iter()
+            .filter(|(key, _)| key.starts_with("x-archive-"))
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+}
+impl fmt::Display for ResponseSnapshot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.status, self.status_text)?;
+        for (key, value) in &self.headers {
+            write!(f, "\n{key}: {value}")?;
+        }
+        
+        Ok(())
+    }
+}
+
+pub(crate) mod sealed {
+    use ureq::Response;
+    use super::RequestContext;
+    
+    /// Provides the failed response which [`ResponseError`][`super::ResponseError`] derives its accessors from.
+    pub trait FailedResponse {
+        /// Returns the failed response, if the error kept it.
+        fn failed_response(&self) -> Option<&Response>;
+        
+        /// Returns the context of the request the error occurred during, if any.
+        fn failed_context(&self) -> Option<&RequestContext> {
+            None
+        }
+        
+        /// Returns the transport error the request failed with, if any.
+        fn failed_transport(&self) -> Option<&ureq::Transport> {
+            None
+        }
+    }
+}
+
+/// Details of the failed response of an error, such as [`ItemError`][`crate::ItemError`].
+/// 
+/// Implemented by every error type of this crate which can be caused by a non-2xx response. This trait
+/// is sealed, and cannot be implemented outside of this crate.
+pub trait ResponseError: sealed::FailedResponse {
+    /// Returns how long the server asked the client to wait before retrying the request, if it
+    /// responded with a `Retry-After` header.
+    /// 
+    /// This is usually only present on `429 Too Many Requests` or `503 Service Unavailable` responses,
+    /// after [automatic retries][`crate::client`] have been disabled or exhausted.
+    fn retry_after(&self) -> Option<Duration> {
+        self.failed_response().and_then(retry_after)
+    }
+    
+    /// Returns the throttling and quota hints provided by the failed response, if the error kept it.
+    fn quota(&self) -> Option<QuotaInfo> {
+        let mut quota = QuotaInfo::from_response(self.failed_response()?);
+        quota.detail = self.response().and_then(|resp| resp.detail);
+        
+        Some(quota)
+    }
+    
+    /// Returns the HTTP status code of the failed response, if the request received one.
+    fn status(&self) -> Option<u16> {
+        self.response().map(|resp| resp.status)
+    }
+    
+    /// Returns a snapshot of the status and headers of the failed response, if the request received one.
+    fn response(&self) -> Option<ResponseSnapshot> {
+        match self.failed_context().and_then(|context| context.response.as_ref()) {
+            Some(snapshot) => Some(snapshot.clone()),
+            None => self.failed_response().map(ResponseSnapshot::new),
+        }
+    }
+    
+    /// Returns the [`TooManyRedirects`] error, if the request was redirected more times than the
+    /// [limit][`Client::with_max_redirects`].
+    fn too_many_redirects(&self) -> Option<&TooManyRedirects> {
+        transport_error(self.failed_transport()?)
+    }
+    
+    /// Returns the [`CircuitOpen`] error, if the request was short-circuited by the [circuit breaker][`Client::with_circuit_breaker`].
+    fn circuit_open(&self) -> Option<&CircuitOpen> {
+        transport_error(self.failed_transport()?)
+    }
+}
+
+/// Returns the error produced by this crate (e.g. [`TooManyRedirects`]) which caused a transport error.
+fn transport_error<T: std::error::Error + 'static>(transport: &ureq::Transport) -> Option<&T> {
+    std::error::Error::source(transport)?
+        .downcast_ref::<io::Error>()?
+        .get_ref()?
+        .downcast_ref()
+}
+impl<T: sealed::FailedResponse> ResponseError for T {}
+
+/// Undecoded response of an API request, returned by `*_raw` methods (e.g. [`Item::metadata_raw`][`crate::Item::metadata_raw`]).
+/// 
+/// These methods perform the same request as their typed counterparts, but leave the body unparsed. When
+/// the Internet Archive changes the schema of a response, the raw response can still be inspected, or
+/// [deserialized][`RawResponse::json`] into a custom type, until this crate is updated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawResponse {
+    /// HTTP status code of the response.
+    pub status: u16,
+    
+    /// Response headers, in the order they were received. Header names are lowercase.
+    pub headers: Vec<(String, String)>,
+    
+    /// Response body, after any content encoding (e.g. gzip) has been removed.
+    pub body: Vec<u8>,
+}
+impl RawResponse {
+    pub(crate) fn read(resp: Response) -> io::Result<Self> {
+        let status = resp.status();
+        let headers = response_headers(&resp);
+        
+        let mut body = vec![];
+        resp.into_reader().read_to_end(&mut body)?;
+        
+        Ok(Self { status, headers, body })
+    }
+    
+    /// Returns the value of the first header named `name` (case-insensitive), if present.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+    
+    /// Returns the body as text. Invalid UTF-8 sequences are replaced with `U+FFFD`.
+    pub fn text(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.body)
+    }
+    
+    /// Deserializes the body as JSON, into any type.
+    #[cfg(feature = "json")]
+    pub fn json<T: DeserializeOwned>(&self) -> serde_json::Result<T> {
+        serde_json::from_slice(&self.body)
+    }
+}
+
+impl ClientInner {
+    fn rebuild_agents(&mut self) {
+        self.pool = Arc::new(Mutex::new(Pool::new(&self.agent_config)));
+    }
+}
+
+/// Returns true if authentication headers may be sent to the host of `url` (`archive.org` or any of
+/// its subdomains).
+fn is_authenticated_host(url: &str) -> bool {
+    let host = url.split_once("://")
+        .map(|(_, rest)| rest.split(['/', '?', '#']).next().unwrap_or(rest))
+        .and_then(|netloc| netloc.rsplit('@').next())
+        .map(|netloc| netloc.split(':').next().unwrap_or(netloc).to_ascii_lowercase())
+        .unwrap_or_default();
+    
+    host == "archive.org" || host.ends_with(".archive.org")
+}
+
+/// Resolves the `Location` header of a redirect against the URL that was requested.
+fn resolve_location(base: &str, location: &str) -> String {
+    if location.contains("://") {
+        return location.to_string();
+    }
+    
+    let (scheme, rest) = base.split_once("://").unwrap_or(("https", base));
+    if let Some(location) = location.strip_prefix("//") {
+        return format!("{scheme}://{location}");
+    }
+    
+    let origin = rest.find('/').map_or(rest, |i| &rest[..i]);
+    match location.strip_prefix('/') {
+        Some(path) => format!("{scheme}://{origin}/{path}"),
+        None => {
+            let path = rest.split(['?', '#']).next().unwrap_or(rest);
+            let dir = path.rfind('/').map_or(origin, |i| &path[..i]);
+            format!("{scheme}://{dir}/{location}")
+        },
+    }
+}
+
+/// Returns the host a request will be sent to.
+fn host(req: &Request) -> String {
+    req.request_url()
+        .map(|url| url.host().to_string())
+        .unwrap_or_default()
+}
+
+/// Response body which is read from the connection, from memory, or from a temporary file.
+#[cfg(any(feature = "xml", feature = "json"))]
+pub(crate) struct Spooled {
+    reader: Box<dyn Read + Send + Sync>,
+    path: Option<PathBuf>,
+}
+#[cfg(any(feature = "xml", feature = "json"))]
+impl Read for Spooled {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+#[cfg(any(feature = "xml", feature = "json"))]
+impl Drop for Spooled {
+    fn drop(&mut self) {
+        if let Some(path) = self.path.take() {
+            // the file must be closed before it can be removed on some platforms
+            self.reader = Box::new(io::empty());
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Deserializes JSON incrementally as it is read from `reader` (e.g. a response body), without first
+/// collecting the whole body or building an intermediate [`serde_json::Value`].
+/// 
+/// Reads are buffered, as the parser otherwise reads a single byte at a time.
+#[cfg(feature = "json")]
+pub(crate) fn read_json<T: DeserializeOwned>(reader: impl Read) -> io::Result<T> {
+    Ok(serde_json::from_reader(BufReader::with_capacity(64 * 1024, reader))?)
+}
+
+/// Prepares a response body for parsing, spooling it to a temporary file if it is larger than `threshold`
+/// bytes. If `threshold` is `None`, the body is read directly from the connection.
+/// 
+/// When the response does not state its length, up to `threshold` bytes are buffered in memory before
+/// the body is spooled.
+#[cfg(any(feature = "xml", feature = "json"))]
+pub(crate) fn spool(resp: Response, threshold: Option<u64>) -> io::Result<Spooled> {
+    let len: Option<u64> = resp.header("content-length").and_then(|len| len.parse().ok());
+    let mut body = resp.into_reader();
+    
+    let threshold = match threshold {
+        Some(threshold) if len.is_none_or(|len| len > threshold) => threshold,
+        _ => return Ok(Spooled { reader: body, path: None }),
+    };
+    
+    let mut head = vec![];
+    if len.is_none() {
+        body.by_ref().take(threshold + 1).read_to_end(&mut head)?;
+        if head.len() as u64 <= threshold {
+            return Ok(Spooled { reader: Box::new(Cursor::new(head)), path: None });
+        }
+    }
+    
+    let (path, mut file) = create_temp_file("iars-spool")?;
+    let result = (|| {
+        file.write_all(&head)?;
+        io::copy(&mut body, &mut file)?;
+        file.seek(SeekFrom::Start(0))?;
+        
+        Ok(file)
+    })();
+    
+    match result {
+        Ok(file) => Ok(Spooled { reader: Box::new(BufReader::new(file)), path: Some(path) }),
+        Err(err) => {
+            let _ = fs::remove_file(&path);
+            Err(err)
+        },
+    }
+}
+
+/// Creates a new file in the system's temporary directory, opened for reading and writing, and returns its path.
+/// 
+/// Names are randomized, and the file is only created if it doesn't already exist, so an existing file
+/// (e.g. one planted by another user of a shared temporary directory) is never opened or truncated.
+#[cfg(any(feature = "xml", feature = "json"))]
+pub(crate) fn create_temp_file(prefix: &str) -> io::Result<(PathBuf, File)> {
+    create_temp_file_in(&std::env::temp_dir(), prefix, "")
+}
+
+/// Creates a new file in `dir`, named with `prefix` and `suffix` around a random part, in the same way as
+/// [`create_temp_file`].
+pub(crate) fn create_temp_file_in(dir: &Path, prefix: &str, suffix: &str) -> io::Result<(PathBuf, File)> {
+    let state = RandomState::new();
+    for attempt in 0u32..16 {
+        let mut hasher = state.build_hasher();
+        hasher.write_u32(attempt);
+        hasher.write_u128(SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos());
+        
+        let path = dir.join(format!("{prefix}-{}-{:016x}{suffix}", std::process::id(), hasher.finish()));
+        match File::options().read(true).write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    
+    Err(io::Error::new(io::ErrorKind::AlreadyExists, "failed to create a unique temporary file"))
+}
+
+/// Returns every header of a response, in the order they were received.
+fn response_headers(resp: &Response) -> Vec<(String, String)> {
+    let names = resp.headers_names();
+    
+    let mut headers = vec![];
+    for (i, name) in names.iter().enumerate() {
+        // repeated headers are returned by `all`, so only the first occurrence of each name is used
+        if !names[..i].contains(name) {
+            headers.extend(resp.all(name).into_iter().map(|value| (name.clone(), value.to_string())));
+        }
+    }
+    
+    headers
+}
+
+/// Parses the `Retry-After` header of a response, if present.
+/// 
+/// The header may contain either a number of seconds, or an HTTP date.
+pub(crate) fn retry_after(resp: &Response) -> Option<Duration> {
+    let val = resp.header("retry-after")?.trim();
+    
+    if let Ok(secs) = val.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    
+    let target = parse_http_date(val)?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs() as i64;
+    
+    Some(Duration::from_secs(target.saturating_sub(now).max(0) as u64))
+}
+
+/// Reads the code and message of an IAS3 error from the body of a failed response.
+/// 
+/// IAS3 explains errors (e.g. `SlowDown`) in an XML body, which would otherwise be discarded. Reading the
+/// body consumes the response, so it is rebuilt from its status, headers, and the body which was read.
+/// The rebuilt response does not keep the request's URL.
+pub(crate) fn read_s3_detail(err: ureq::Error) -> (ureq::Error, Option<String>) {
+    let resp = match err {
+        ureq::Error::Status(_, resp) if resp.content_type().ends_with("xml") => resp,
+        err => return (err, None),
+    };
+    
+    let snapshot = ResponseSnapshot::new(&resp);
+    let mut body = vec![];
+    let _ = resp.into_reader().take(MAX_ERROR_BODY).read_to_end(&mut body);
+    let body = String::from_utf8_lossy(&body);
+    
+    let detail = match (xml_text(&body, "Code"), xml_text(&body, "Message")) {
+        (Some(code), Some(message)) => Some(format!("{code}: {message}")),
+        (code, message) => message.or(code).map(str::to_string),
+    };
+    
+    let mut raw = format!("HTTP/1.1 {} {}\r\n", snapshot.status, snapshot.status_text);
+    for (name, value) in snapshot.headers.iter().filter(|(name, _)| !matches!(name.as_str(), "content-length" | "content-encoding" | "transfer-encoding")) {
+        raw.push_str(&format!("{name}: {value}\r\n"));
+    }
+    raw.push_str(&format!("content-length: {}\r\n\r\n{body}", body.len()));
+    
+    match raw.parse::<Response>() {
+        Ok(resp) => (ureq::Error::Status(snapshot.status, resp), detail),
+        Err(err) => (err, detail),
+    }
+}
+
+/// Returns the trimmed text of the first `<tag>` element in `xml`, if present and not empty.
+fn xml_text<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let start = xml.find(&format!("<{tag}>"))? + tag.len() + 2;
+    let end = start + xml[start..].find(&format!("</{tag}>"))?;
+    
+    Some(xml[start..end].trim()).filter(|text| !text.is_empty())
+}
+
+/// Percent-encodes every character of `s` which is not unreserved in a URL path segment.
+pub(crate) fn encode_segment(s: &str) -> String {
+    s.bytes().map(|b| match b {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+        _ => format!("%{b:02X}"),
+    }).collect()
+}
+
+/// Parses an IMF-fixdate (e.g. `Wed, 21 Oct 2015 07:28:00 GMT`) into seconds since the UNIX epoch.
+pub(crate) fn parse_http_date(date: &str) -> Option<i64> {
+    let mut parts = date.split_whitespace().skip(1);
+    let day: i64 = parts.next()?.parse().ok()?;
+    let month = match parts.next()? {
+        "Jan" => 1, "Feb" => 2, "Mar" => 3, "Apr" => 4, "May" => 5, "Jun" => 6,
+        "Jul" => 7, "Aug" => 8, "Sep" => 9, "Oct" => 10, "Nov" => 11, "Dec" => 12,
+        _ => return None,
+    };
+    let year: i64 = parts.next()?.parse().ok()?;
+    
+    let mut time = parts.next()?.split(':').map(|part| part.parse::<i64>().ok());
+    let (hour, min, sec) = (time.next()??, time.next()??, time.next()??);
+    
+    Some(days_from_civil(year, month, day) * 86400 + hour * 3600 + min * 60 + sec)
+}
+
+/// Number of days between the UNIX epoch and the given date, using Howard Hinnant's `days_from_civil` algorithm.
+pub(crate) fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    
+    era * 146097 + doe - 719468
+}
+
+/// Date `(year, month, day)` of the given number of days since the UNIX epoch, using Howard Hinnant's
+/// `civil_from_days` algorithm.
+#[cfg(feature = "json")]
+pub(crate) fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    
+    (yoe + era * 400 + i64::from(month <= 2), month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn reads_s3_detail() {
+        let body = "<?xml version='1.0' encoding='UTF-8'?>\n<Error><Code>SlowDown</Code><Message>Please reduce your request rate.</Message><Resource>/test_item/file.txt</Resource></Error>";
+        let raw = format!("HTTP/1.1 503 Service Unavailable\r\ncontent-type: application/xml\r\nretry-after: 30\r\ncontent-length: {}\r\n\r\n{body}", body.len());
+        let (err, detail) = read_s3_detail(ureq::Error::Status(503, raw.parse().unwrap()));
+        
+        assert_eq!(detail.as_deref(), Some("SlowDown: Please reduce your request rate."));
+        let ureq::Error::Status(503, resp) = err else { panic!("expected a status error") };
+        assert_eq!(retry_after(&resp), Some(Duration::from_secs(30)));
+        assert_eq!(resp.into_string().unwrap(), body);
+    }
+    
+    #[test]
+    fn keeps_permits_when_host_limits_change() {
+        let limiter = Arc::new(HostLimiter::default());
+        let active = || limiter.state.lock().unwrap().active.get("archive.org").copied();
+        
+        assert!(limiter.acquire("archive.org").limiter.is_none());
+        
+        limiter.set_limit("archive.org", 1);
+        let first = limiter.acquire("archive.org");
+        limiter.set_limit("archive.org", 2);
+        let second = limiter.acquire("archive.org");
+        assert_eq!(active(), Some(2));
+        
+        drop(first);
+        drop(second);
+        assert_eq!(active(), Some(0));
+    }
+    
+    #[cfg(feature = "json")]
+    #[test]
+    fn finds_transport_errors() {
+        let redirects = TooManyRedirects { url: "https://archive.org/a".into(), location: "https://archive.org/b".into(), limit: 5 };
+        let err = crate::views::ViewsError::from(ureq::Error::from(io::Error::other(redirects.clone())));
+        
+        assert_eq!(err.too_many_redirects(), Some(&redirects));
+        assert_eq!(err.circuit_open(), None);
+        assert_eq!(crate::views::ViewsError::from(ureq::Error::from(io::Error::other("reset"))).too_many_redirects(), None);
+    }
+    
+    #[test]
+    fn creates_unique_temp_files() {
+        // the files are closed right away, as open files can't be removed on some platforms
+        let (first, _) = create_temp_file_in(&std::env::temp_dir(), ".page.png", ".iars-part").unwrap();
+        let (second, _) = create_temp_file_in(&std::env::temp_dir(), ".page.png", ".iars-part").unwrap();
+        
+        assert_ne!(first, second);
+        for path in [&first, &second] {
+            let name = path.file_name().unwrap().to_str().unwrap();
+            assert!(name.starts_with(".page.png-") && name.ends_with(".iars-part"), "{name}");
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+    
+    #[test]
+    fn ignores_non_xml_bodies() {
+        let raw = "HTTP/1.1 503 Service Unavailable\r\ncontent-type: text/html\r\ncontent-length: 22\r\n\r\n<Message>no</Message>\n";
+        let (err, detail) = read_s3_detail(ureq::Error::Status(503, raw.parse().unwrap()));
+        
+        assert_eq!(detail, None);
+        assert!(matches!(err, ureq::Error::Status(503, _)));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,219 @@
+//! Matching of file paths against glob patterns.
+//! 
+//! Patterns support the following wildcards:
+//! * `*` matches any number of characters, except `/`.
+//! * `**` matches any number of characters, including `/`. `**/` also matches no directories at all.
+//! * `?` matches a single character, except `/`.
+//! * `[abc]` matches one of the listed characters, and `[a-z]` matches a range. `[!abc]` (or `[^abc]`)
+//!   matches any character that isn't listed.
+//! 
+//! Patterns which don't contain a `/` are matched against the file's name only, so `*_thumb.jpg`
+//! matches thumbnails in every directory. Other patterns are matched against the whole path, relative
+//! to the root of the item (a leading `/` is ignored).
+//! 
+//! Lists of patterns can also be combined in the style of a `.gitignore` file, using [`Ignore`].
+
+/// Returns true if `path` matches the glob `pattern`.
+pub(crate) fn matches(pattern: &str, path: &str) -> bool {
+    let path = match pattern.contains('/') {
+        true => path,
+        false => path.rsplit('/').next().unwrap_or(path),
+    };
+    let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+    
+    matches_chars(&pattern.chars().collect::<Vec<_>>(), &path.chars().collect::<Vec<_>>())
+}
+
+/// Returns true if `path` is selected by the include and exclude patterns.
+/// 
+/// Paths are selected when they match any include pattern (or there are none), and no exclude pattern.
+pub(crate) fn selects(include: &[String], exclude: &[String], path: &str) -> bool {
+    (include.is_empty() || include.iter().any(|pattern| matches(pattern, path)))
+        && !exclude.iter().any(|pattern| matches(pattern, path))
+}
+
+fn matches_chars(pattern: &[char], path: &[char]) -> bool {
+    match pattern {
+        [] => path.is_empty(),
+        ['*', '*', '/', rest @ ..] => matches_chars(rest, path) || (0..path.len()).any(|i| path[i] == '/' && matches_chars(rest, &path[i + 1..])),
+        ['*', '*', rest @ ..] => (0..=path.len()).any(|i| matches_chars(rest, &path[i..])),
+        ['*', rest @ ..] => (0..=path.len())
+            .take_while(|&i| i == 0 || path[i - 1] != '/')
+            .any(|i| matches_chars(rest, &path[i..])),
+        ['?', rest @ ..] => path.first().is_some_and(|c| *c != '/') && matches_chars(rest, &path[1..]),
+        ['[', rest @ ..] => match class(rest) {
+            Some((matcher, rest)) => path.first().is_some_and(|c| *c != '/' && matcher(*c)) && matches_chars(rest, &path[1..]),
+            // an unclosed bracket is matched literally
+            None => path.first() == Some(&'[') && matches_chars(rest, &path[1..]),
+        },
+        [c, rest @ ..] => path.first() == Some(c) && matches_chars(rest, &path[1..]),
+    }
+}
+
+/// Parses a character class (following its opening `[`), returning a matcher and the remaining pattern.
+fn class(pattern: &[char]) -> Option<(impl Fn(char) -> bool + '_, &[char])> {
+    let (negated, body) = match pattern.first() {
+        Some('!' | '^') => (true, &pattern[1..]),
+        _ => (false, pattern),
+    };
+    
+    // a `]` directly after the opening bracket is part of the class
+    let end = body.iter().skip(1).position(|c| *c == ']')? + 1;
+    let (set, rest) = (&body[..end], &body[end + 1..]);
+    
+    let matcher = move |c: char| {
+        let mut i = 0;
+        let mut found = false;
+        while i < set.len() {
+            if i + 2 < set.len() && set[i + 1] == '-' {
+                found |= (set[i]..=set[i + 2]).contains(&c);
+                i += 3;
+            } else {
+                found |= set[i] == c;
+                i += 1;
+            }
+        }
+        
+        found != negated
+    };
+    
+    Some((matcher, rest))
+}
+
+/// Gitignore-style list of patterns, used to skip local files. Later patterns take precedence over
+/// earlier ones, and patterns starting with `!` re-include paths excluded by an earlier pattern.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Ignore {
+    rules: Vec<Rule>,
+}
+impl Ignore {
+    /// Adds every line of a `.gitignore`-style file.
+    pub(crate) fn add_lines(&mut self, text: &str) {
+        text.lines().for_each(|line| self.add(line));
+    }
+    
+    /// Adds a single pattern. Blank lines and comments (starting with `#`) are skipped, and a trailing
+    /// `/` only matches directories. A leading `\` escapes a literal `#` or `!`.
+    pub(crate) fn add(&mut self, line: &str) {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return;
+        }
+        
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(line) => (true, line),
+            None => (false, line),
+        };
+        let line = line.strip_prefix('\\').unwrap_or(line);
+        let (dir_only, pattern) = match line.strip_suffix('/') {
+            Some(pattern) => (true, pattern),
+            None => (false, line),
+        };
+        
+        if !pattern.is_empty() {
+            self.rules.push(Rule { pattern: pattern.to_string(), negated, dir_only });
+        }
+    }
+    
+    /// Returns true if `path` (a directory if `is_dir`, otherwise a file) is ignored.
+    /// 
+    /// Directories are checked before their contents are listed, so files within an ignored directory
+    /// are never considered, even if a later pattern would re-include them (as with `.gitignore`).
+    pub(crate) fn is_ignored(&self, path: &str, is_dir: bool) -> bool {
+        self.rules.iter().rev()
+            .find(|rule| (is_dir || !rule.dir_only) && matches(&rule.pattern, path))
+            .is_some_and(|rule| !rule.negated)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    pattern: String,
+    negated: bool,
+    dir_only: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn matches_wildcards() {
+        assert!(matches("*.jpg", "scans/page.jpg"));
+        assert!(matches("scans/*.jpg", "scans/page.jpg"));
+        assert!(!matches("scans/*.jpg", "scans/extra/page.jpg"));
+        assert!(matches("/scans/page?.jpg", "scans/page1.jpg"));
+        assert!(!matches("scans/a?b", "scans/a/b"));
+        assert!(matches("scans/**", "scans/a/b/page.jpg"));
+        assert!(matches("scans/**.jpg", "scans/a/page.jpg"));
+    }
+    
+    #[test]
+    fn matches_double_star_slash() {
+        assert!(matches("**/page.jpg", "page.jpg"));
+        assert!(matches("**/page.jpg", "a/b/page.jpg"));
+        assert!(matches("scans/**/page.jpg", "scans/page.jpg"));
+        assert!(matches("scans/**/page.jpg", "scans/a/b/page.jpg"));
+        assert!(!matches("scans/**/page.jpg", "scans/apage.jpg"));
+    }
+    
+    #[test]
+    fn matches_classes() {
+        assert!(matches("[abc].txt", "b.txt"));
+        assert!(matches("[a-c].txt", "c.txt"));
+        assert!(!matches("[a-c].txt", "d.txt"));
+        assert!(matches("[!a-c].txt", "d.txt"));
+        assert!(!matches("[^a].txt", "a.txt"));
+        assert!(matches("[]a].txt", "].txt"));
+        assert!(matches("[a-].txt", "-.txt"));
+        assert!(!matches("scans[/]x", "scans/x"));
+    }
+    
+    #[test]
+    fn matches_unclosed_bracket_literally() {
+        assert!(matches("[abc.txt", "[abc.txt"));
+        assert!(!matches("[abc.txt", "a.txt"));
+        assert!(matches("page[*", "page[1].jpg"));
+    }
+    
+    #[test]
+    fn selects_included_and_not_excluded() {
+        let include = vec!["*.jpg".to_string(), "*.png".to_string()];
+        let exclude = vec!["*_thumb.jpg".to_string()];
+        
+        assert!(selects(&include, &exclude, "scans/page.jpg"));
+        assert!(!selects(&include, &exclude, "scans/page_thumb.jpg"));
+        assert!(!selects(&include, &exclude, "scans/page.gif"));
+        assert!(selects(&[], &exclude, "scans/page.gif"));
+    }
+    
+    #[test]
+    fn ignores_with_reincludes() {
+        let mut ignore = Ignore::default();
+        ignore.add_lines("# scratch files\n*.tmp\n!keep.tmp\n\n\\#notes.txt\n\\!important.txt\n");
+        
+        assert!(ignore.is_ignored("a.tmp", false));
+        assert!(ignore.is_ignored("sub/a.tmp", false));
+        assert!(!ignore.is_ignored("keep.tmp", false));
+        assert!(!ignore.is_ignored("sub/keep.tmp", false));
+        assert!(ignore.is_ignored("#notes.txt", false));
+        assert!(ignore.is_ignored("!important.txt", false));
+        assert!(!ignore.is_ignored("scratch files", false));
+        
+        // later patterns take precedence
+        ignore.add("*.tmp");
+        assert!(ignore.is_ignored("keep.tmp", false));
+    }
+    
+    #[test]
+    fn ignores_directories_only() {
+        let mut ignore = Ignore::default();
+        ignore.add_lines("build/\n/docs/drafts/\n");
+        
+        assert!(ignore.is_ignored("build", true));
+        assert!(ignore.is_ignored("src/build", true));
+        assert!(!ignore.is_ignored("build", false));
+        assert!(ignore.is_ignored("docs/drafts", true));
+        assert!(!ignore.is_ignored("other/docs/drafts", true));
+    }
+}
\ No newline at end of file
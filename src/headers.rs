@@ -1,6 +1,9 @@
+use std::fmt;
+use std::fmt::Formatter;
 use ureq::Request;
+use crate::client::encode_segment;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Clone, PartialEq)]
 pub enum Header {
     /// Normally added automatically when sending bytes.
     ContentLength(usize),
@@ -10,9 +13,13 @@ pub enum Header {
     },
     ContentType(String),
     ContentMd5(String),
+    /// Session cookies, in the form `name=value; name=value`.
+    Cookie(String),
     
     XAutoMakeBucket(bool),
     XCascadeDelete(bool),
+    /// Source of a server-side copy, in the form `/{identifier}/{filepath}`, with each segment of the path percent-encoded.
+    XCopySource(String),
     XIgnorePreexistingBucket(bool),
     XKeepOldVersion(bool),
     XMeta {
@@ -25,6 +32,32 @@ pub enum Header {
     Custom(String, String),
 }
 
+impl fmt::Debug for Header {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use Header::*;
+        
+        // the secret key and session cookies grant access to the account, so they're never printed
+        match self {
+            ContentLength(val) => f.debug_tuple("ContentLength").field(val).finish(),
+            Authorization { access, .. } => f.debug_struct("Authorization").field("access", access).field("secret", &"<redacted>").finish(),
+            ContentType(val) => f.debug_tuple("ContentType").field(val).finish(),
+            ContentMd5(val) => f.debug_tuple("ContentMd5").field(val).finish(),
+            Cookie(_) => f.debug_tuple("Cookie").field(&"<redacted>").finish(),
+            
+            XAutoMakeBucket(val) => f.debug_tuple("XAutoMakeBucket").field(val).finish(),
+            XCascadeDelete(val) => f.debug_tuple("XCascadeDelete").field(val).finish(),
+            XCopySource(val) => f.debug_tuple("XCopySource").field(val).finish(),
+            XIgnorePreexistingBucket(val) => f.debug_tuple("XIgnorePreexistingBucket").field(val).finish(),
+            XKeepOldVersion(val) => f.debug_tuple("XKeepOldVersion").field(val).finish(),
+            XMeta { name, value } => f.debug_struct("XMeta").field("name", name).field("value", value).finish(),
+            XQueueDerive(val) => f.debug_tuple("XQueueDerive").field(val).finish(),
+            XSizeHint(val) => f.debug_tuple("XSizeHint").field(val).finish(),
+            
+            Custom(key, val) => f.debug_tuple("Custom").field(key).field(val).finish(),
+        }
+    }
+}
+
 pub trait RequestHeaderExt {
     /// Set a header field used by Internet Archive's S3-like API.
     fn set_header(self, header: Header) -> Self;
@@ -38,16 +71,41 @@ impl RequestHeaderExt for Request {
             Authorization { access, secret } => self.set("authorization", &format!("LOW {access}:{secret}")),
             ContentType(val) => self.set("content-type", &val),
             ContentMd5(val) => self.set("content-md5", &val),
+            Cookie(val) => self.set("cookie", &val),
             
             XAutoMakeBucket(val) => self.set("x-amz-auto-make-bucket", &(val as u8).to_string()),
             XCascadeDelete(val) => self.set("x-archive-cascade-delete", &(val as u8).to_string()),
+            XCopySource(val) => self.set("x-amz-copy-source", &val),
             XIgnorePreexistingBucket(val) => self.set("x-archive-ignore-preexisting-bucket", &(val as u8).to_string()),
             XKeepOldVersion(val) => self.set("x-archive-keep-old-version", &(val as u8).to_string()),
-            XMeta { name, value } => self.set(format!("x-archive-meta-{name}").as_str(), &value),
+            XMeta { name, value } => self.set(format!("x-archive-meta-{name}").as_str(), &meta_value(&value)),
             XQueueDerive(val) => self.set("x-archive-queue-derive", &(val as u8).to_string()),
             XSizeHint(val) => self.set("x-archive-size-hint", &val.to_string()),
             
             Custom(key, val) => self.set(key.as_str(), &val),
         }
     }
+}
+
+/// Encodes a metadata value for use in an `x-archive-meta-*` header.
+/// 
+/// Header values may only contain visible ASCII characters and spaces, so any other value is percent-encoded
+/// and wrapped in `uri(...)`, which the Internet Archive decodes when applying the metadata.
+pub(crate) fn meta_value(value: &str) -> String {
+    if value.bytes().all(|b| matches!(b, b' '..=b'~')) {
+        return value.to_string();
+    }
+    
+    format!("uri({})", encode_segment(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn encodes_meta_values() {
+        assert_eq!(meta_value("A Title (2nd ed.)"), "A Title (2nd ed.)");
+        assert_eq!(meta_value("Caf\u{e9}\nline"), "uri(Caf%C3%A9%0Aline)");
+    }
 }
\ No newline at end of file
@@ -0,0 +1,461 @@
+//! Access to item images through the IIIF APIs.
+//! 
+//! The Internet Archive provides [IIIF](https://iiif.io/) endpoints for the images and books it hosts:
+//! * The [Image API](https://iiif.io/api/image/3.0/) serves image [information][`Image::info`] and
+//!   [regions, sizes, and rotations][`Image::download`] of any image file within an item.
+//! * The [Presentation API](https://iiif.io/api/presentation/3.0/) describes an item as a [`Manifest`]
+//!   containing one [`Canvas`] per image or book page. See [`ManifestRequest`].
+//! 
+//! See also: [IA IIIF docs](https://iiif.archive.org/iiif/documentation)
+//! 
+//! # Example
+//! ```rust,no_run
+//! use std::fs::File;
+//! use iars::iiif::{Image, Region, Size};
+//! 
+//! let image = Image::new("test_item", "page_0001.jp2")
+//!     .with_region(Region::Pixels { x: 0, y: 0, width: 1024, height: 1024 })
+//!     .with_size(Size::Width(512));
+//! 
+//! let info = image.info()?;
+//! println!("full image is {}x{}", info.width, info.height);
+//! 
+//! image.download(File::create("tile.jpg")?)?;
+//! # Ok::<(), iars::iiif::IiifError>(())
+//! ```
+
+use std::fmt;
+use std::fmt::Formatter;
+use std::io::Write;
+use serde::Deserialize;
+use crate::default_user_agent;
+use crate::client::{self, Client};
+
+const IMAGE_API: &str = "https://iiif.archive.org/image/iiif/3";
+const PRESENTATION_API: &str = "https://iiif.archive.org/iiif/3";
+
+/// Errors which may occur while using the IIIF APIs.
+#[derive(Debug)]
+pub enum IiifError {
+    /// An error while performing [`std::io`] operations.
+    Io(std::io::Error),
+    
+    /// An error while processing a [`ureq`] request.
+    Ureq(ureq::Error),
+    
+    /// A [`ureq`] request was successful, but returned a 403 Forbidden error code.
+    Forbidden(ureq::Response),
+    
+    /// A [`ureq`] request was successful, but returned a 429 Too Many Requests error code.
+    /// 
+    /// This is only returned after any [automatic retries][`crate::client`] have been exhausted.
+    TooManyRequests(ureq::Response),
+}
+impl fmt::Display for IiifError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Ureq(err) => write!(f, "{err}"),
+            Self::Forbidden(_) => write!(f, "403 Forbidden"),
+            Self::TooManyRequests(_) => write!(f, "429 Too Many Requests"),
+        }
+    }
+}
+impl std::error::Error for IiifError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Ureq(err) => Some(err),
+            Self::Forbidden(_) | Self::TooManyRequests(_) => None,
+        }
+    }
+}
+impl client::sealed::FailedResponse for IiifError {
+    fn failed_response(&self) -> Option<&ureq::Response> {
+        match self {
+            Self::Ureq(ureq::Error::Status(_, resp)) | Self::Forbidden(resp) | Self::TooManyRequests(resp) => Some(resp),
+            _ => None,
+        }
+    }
+    
+    fn failed_transport(&self) -> Option<&ureq::Transport> {
+        match self {
+            Self::Ureq(ureq::Error::Transport(transport)) => Some(transport),
+            _ => None,
+        }
+    }
+}
+impl From<std::io::Error> for IiifError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+impl From<ureq::Error> for IiifError {
+    fn from(value: ureq::Error) -> Self {
+        match value {
+            ureq::Error::Status(403, resp) => Self::Forbidden(resp),
+            ureq::Error::Status(429, resp) => Self::TooManyRequests(resp),
+            _ => Self::Ureq(value)
+        }
+    }
+}
+
+/// Rectangular portion of an image to request.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Region {
+    /// The whole image.
+    Full,
+    
+    /// The largest square region centered within the image.
+    Square,
+    
+    /// A region measured in pixels of the full image.
+    Pixels { x: u32, y: u32, width: u32, height: u32 },
+    
+    /// A region measured in percentages (0-100) of the full image's dimensions.
+    Percent { x: f32, y: f32, width: f32, height: f32 },
+}
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full => write!(f, "full"),
+            Self::Square => write!(f, "square"),
+            Self::Pixels { x, y, width, height } => write!(f, "{x},{y},{width},{height}"),
+            Self::Percent { x, y, width, height } => write!(f, "pct:{x},{y},{width},{height}"),
+        }
+    }
+}
+
+/// Dimensions to scale the requested region to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Size {
+    /// The largest size the server allows, without upscaling.
+    Max,
+    
+    /// Scales to the given width, maintaining the aspect ratio.
+    Width(u32),
+    
+    /// Scales to the given height, maintaining the aspect ratio.
+    Height(u32),
+    
+    /// Scales to fit within the given width and height, maintaining the aspect ratio.
+    Fit(u32, u32),
+    
+    /// Scales to exactly the given width and height, distorting the image if necessary.
+    Exact(u32, u32),
+    
+    /// Scales by a percentage (0-100) of the region's dimensions.
+    Percent(f32),
+}
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Max => write!(f, "max"),
+            Self::Width(width) => write!(f, "{width},"),
+            Self::Height(height) => write!(f, ",{height}"),
+            Self::Fit(width, height) => write!(f, "!{width},{height}"),
+            Self::Exact(width, height) => write!(f, "{width},{height}"),
+            Self::Percent(pct) => write!(f, "pct:{pct}"),
+        }
+    }
+}
+
+/// Color quality of the requested image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quality {
+    /// The server's default quality (usually color).
+    Default,
+    Color,
+    Gray,
+    Bitonal,
+}
+impl fmt::Display for Quality {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", match self {
+            Self::Default => "default",
+            Self::Color => "color",
+            Self::Gray => "gray",
+            Self::Bitonal => "bitonal",
+        })
+    }
+}
+
+/// Request builder for an image file within an item, using the IIIF Image API.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    useragent: String,
+    client: Client,
+    id: String,
+    region: Region,
+    size: Size,
+    rotation: u16,
+    quality: Quality,
+    format: String,
+}
+impl Image {
+    /// Creates a request for the image file at `filepath` within the item `identifier`.
+    /// 
+    /// By default, the full image is requested at its maximum size, as a JPEG.
+    pub fn new(identifier: &str, filepath: &str) -> Self {
+        Self::from_id(&client::encode_segment(&format!("{identifier}/{filepath}")))
+    }
+    
+    /// Creates a request for an image using its IIIF image identifier, as found in a [`Canvas`].
+    pub fn from_id(id: &str) -> Self {
+        Self {
+            useragent: default_user_agent(),
+            client: Client::shared(),
+            id: id.trim_start_matches(IMAGE_API).trim_matches('/').to_string(),
+            region: Region::Full,
+            size: Size::Max,
+            rotation: 0,
+            quality: Quality::Default,
+            format: "jpg".to_string(),
+        }
+    }
+    
+    /// Configures the User-Agent string provided in this request.
+    /// 
+    /// If `None` or if the string is empty, a [default][`default_user_agent`] will be used.
+    pub fn with_useragent(mut self, useragent: Option<String>) -> Self {
+        self.useragent = match useragent {
+            Some(useragent) if !useragent.is_empty() => useragent,
+            _ => default_user_agent(),
+        };
+        
+        self
+    }
+    
+    /// Configures the [`Client`] used to perform this request.
+    /// 
+    /// By default, the [shared client][`Client::shared`] is used.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        
+        self
+    }
+    
+    /// Portion of the image to request.
+    pub fn with_region(mut self, region: Region) -> Self {
+        self.region = region;
+        
+        self
+    }
+    
+    /// Dimensions to scale the requested region to.
+    pub fn with_size(mut self, size: Size) -> Self {
+        self.size = size;
+        
+        self
+    }
+    
+    /// Clockwise rotation to apply, in degrees (usually 0, 90, 180, or 270).
+    pub fn with_rotation(mut self, rotation: u16) -> Self {
+        self.rotation = rotation;
+        
+        self
+    }
+    
+    /// Color quality of the image.
+    pub fn with_quality(mut self, quality: Quality) -> Self {
+        self.quality = quality;
+        
+        self
+    }
+    
+    /// Image format to request, as a file extension (e.g. `jpg`, `png`, `webp`).
+    pub fn with_format(mut self, format: &str) -> Self {
+        self.format = format.to_string();
+        
+        self
+    }
+    
+    /// Returns the URL of the image, as configured by this request.
+    pub fn url(&self) -> String {
+        format!("{IMAGE_API}/{}/{}/{}/{}/{}.{}", self.id, self.region, self.size, self.rotation, self.quality, self.format)
+    }
+    
+    /// Retrieves information about the full image, such as its dimensions and available tiles.
+    /// 
+    /// # Errors
+    /// This may return [`IiifError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    /// 
+    /// If any [I/O errors][`std::io::Error`] occur or the response fails to be deserialized, an [`IiifError::Io`] is returned.
+    pub fn info(&self) -> Result<ImageInfo, IiifError> {
+        let req = self.client.request("GET", &format!("{IMAGE_API}/{}/info.json", self.id))
+            .set("user-agent", &self.useragent);
+        
+        Ok(self.client.call(req)?.into_json()?)
+    }
+    
+    /// Downloads the image, as configured by this request, into the `writer`.
+    /// 
+    /// On success, the number of bytes written is returned.
+    /// 
+    /// # Errors
+    /// This may return [`IiifError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    /// 
+    /// If any [I/O errors][`std::io::Error`] occur while transfering data into the `writer`, an [`IiifError::Io`] is returned.
+    pub fn download(&self, mut writer: impl Write) -> Result<u64, IiifError> {
+        let req = self.client.request("GET", &self.url())
+            .set("user-agent", &self.useragent);
+        
+        let download = self.client.call_download(req)?;
+        
+        Ok(self.client.copy(download.into_reader(), &mut writer)?)
+    }
+}
+
+/// Information about an image, returned by the IIIF Image API.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ImageInfo {
+    /// Base URL of the image.
+    #[serde(alias = "@id")]
+    pub id: String,
+    
+    /// Width of the full image in pixels.
+    pub width: u32,
+    
+    /// Height of the full image in pixels.
+    pub height: u32,
+    
+    /// Preferred sizes the server can deliver efficiently.
+    #[serde(default)]
+    pub sizes: Vec<ImageSize>,
+    
+    /// Tile sizes the server can deliver efficiently.
+    #[serde(default)]
+    pub tiles: Vec<Tile>,
+}
+
+/// A preferred size of an image.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ImageSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A tile size of an image, and the scale factors it is available at.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tile {
+    pub width: u32,
+    pub height: Option<u32>,
+    #[serde(default)]
+    pub scale_factors: Vec<u32>,
+}
+
+/// Description of an item, returned by the IIIF Presentation API.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Manifest {
+    /// URL of the manifest.
+    pub id: String,
+    
+    /// Language map of the item's title (e.g. `{"none": ["Title"]}`).
+    #[serde(default)]
+    pub label: serde_json::Value,
+    
+    /// Each image, or page of a book, within the item.
+    #[serde(default)]
+    pub items: Vec<Canvas>,
+}
+
+/// A single image, or page of a book, within a [`Manifest`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Canvas {
+    /// URL of the canvas.
+    pub id: String,
+    
+    /// Language map of the canvas' label (e.g. a page number).
+    #[serde(default)]
+    pub label: serde_json::Value,
+    
+    /// Width of the canvas in pixels.
+    pub width: Option<u32>,
+    
+    /// Height of the canvas in pixels.
+    pub height: Option<u32>,
+    
+    #[serde(default)]
+    items: serde_json::Value,
+}
+impl Canvas {
+    /// Returns the URL of the canvas' full image.
+    pub fn image_url(&self) -> Option<&str> {
+        self.items.pointer("/0/items/0/body/id")?.as_str()
+    }
+    
+    /// Returns an [`Image`] request for the canvas' image service, which can be used to request
+    /// regions or other sizes of the image.
+    pub fn image(&self) -> Option<Image> {
+        let body = self.items.pointer("/0/items/0/body")?;
+        let service = body.pointer("/service/0/id")
+            .or_else(|| body.pointer("/service/0/@id"))?
+            .as_str()?;
+        
+        Some(Image::from_id(service))
+    }
+}
+
+/// Request builder for the IIIF [`Manifest`] of an item, using the IIIF Presentation API.
+/// 
+/// # Example
+/// ```rust,no_run
+/// use iars::iiif::ManifestRequest;
+/// 
+/// let manifest = ManifestRequest::new("test_item").call()?;
+/// for canvas in &manifest.items {
+///     println!("{:?}", canvas.image_url());
+/// }
+/// # Ok::<(), iars::iiif::IiifError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestRequest {
+    useragent: String,
+    client: Client,
+    identifier: String,
+}
+impl ManifestRequest {
+    /// Creates a request for the manifest of the item `identifier`.
+    pub fn new(identifier: &str) -> Self {
+        Self {
+            useragent: default_user_agent(),
+            client: Client::shared(),
+            identifier: identifier.to_string(),
+        }
+    }
+    
+    /// Configures the User-Agent string provided in this request.
+    /// 
+    /// If `None` or if the string is empty, a [default][`default_user_agent`] will be used.
+    pub fn with_useragent(mut self, useragent: Option<String>) -> Self {
+        self.useragent = match useragent {
+            Some(useragent) if !useragent.is_empty() => useragent,
+            _ => default_user_agent(),
+        };
+        
+        self
+    }
+    
+    /// Configures the [`Client`] used to perform this request.
+    /// 
+    /// By default, the [shared client][`Client::shared`] is used.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        
+        self
+    }
+    
+    /// Retrieves the manifest.
+    /// 
+    /// # Errors
+    /// This may return [`IiifError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    /// 
+    /// If any [I/O errors][`std::io::Error`] occur or the response fails to be deserialized, an [`IiifError::Io`] is returned.
+    pub fn call(&self) -> Result<Manifest, IiifError> {
+        let req = self.client.request("GET", &format!("{PRESENTATION_API}/{}/manifest.json", self.identifier))
+            .set("user-agent", &self.useragent);
+        
+        Ok(self.client.call(req)?.into_json()?)
+    }
+}
\ No newline at end of file
@@ -11,55 +11,322 @@
 //! Creating a new archive on IA is as simple as creating an `Item` using an unused identifier, and
 //! [uploading a file][Item::upload_file] to it.
 
+#[cfg(feature = "json")]
 use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::fmt::Formatter;
 use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
 use std::string::ToString;
+use flate2::read::MultiGzDecoder;
 use serde::Deserialize;
-use crate::{Credentials, DEFAULT_USER_AGENT, validate_identifier};
-use crate::headers::Header::{XAutoMakeBucket, XKeepOldVersion, XMeta, XQueueDerive, XSizeHint};
-use crate::headers::RequestHeaderExt;
+#[cfg(feature = "json")]
+use serde::Deserializer;
+use crate::{Credentials, default_user_agent, Identifier, SessionCookies, validate_identifier};
+use crate::client::{self, Client, RawResponse, RequestContext};
+#[cfg(feature = "json")]
+use crate::client::QuotaInfo;
+use crate::headers::Header::{ContentLength, Custom, XAutoMakeBucket, XCascadeDelete, XCopySource, XIgnorePreexistingBucket, XKeepOldVersion, XMeta, XQueueDerive, XSizeHint};
+use crate::headers::{meta_value, RequestHeaderExt};
+#[cfg(feature = "json")]
+use crate::related::{RelatedItem, RelatedResponse};
+#[cfg(feature = "json")]
+use crate::item::metadata::MetaValue;
+#[cfg(feature = "json")]
+use crate::reviews::Review;
+#[cfg(feature = "json")]
+use crate::tasks;
+#[cfg(feature = "json")]
+use crate::tasks::TaskError;
+#[cfg(feature = "json")]
+use crate::tasks::search::Filter;
+#[cfg(feature = "json")]
+use crate::views;
+#[cfg(feature = "json")]
+use crate::views::ViewsError;
 
+#[cfg(feature = "json")]
+pub mod bagit;
+pub mod builder;
+#[cfg(feature = "json")]
+pub mod clone;
+#[cfg(feature = "json")]
+pub mod derive;
+#[cfg(feature = "json")]
+pub mod manifest;
+#[cfg(feature = "json")]
+pub mod metadata;
+#[cfg(feature = "xml")]
+pub mod multipart;
+#[cfg(feature = "json")]
+pub mod snapshot;
+#[cfg(feature = "json")]
+pub mod stage;
+#[cfg(feature = "json")]
+pub mod sync;
+#[cfg(feature = "json")]
+pub mod text;
+
+/// Errors which may occur while performing operations on an [`Item`].
+/// 
+/// Errors produced by a request include the [`RequestContext`] of that request, identifying the
+/// operation, URL, and item involved.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum ItemError {
     /// An error while performing [`std::io`] operations.
-    Io(std::io::Error),
+    /// 
+    /// `context` is `None` if the error did not occur during a request (e.g. while reading a local file).
+    Io {
+        context: Option<RequestContext>,
+        source: std::io::Error,
+    },
     
     /// An error while processing a [`ureq`] request.
-    Ureq(ureq::Error),
+    Ureq {
+        context: RequestContext,
+        source: ureq::Error,
+    },
     
     /// An error while attempting to parse XML.
-    XmlParseFailed(serde_xml_rs::Error),
+    #[cfg(feature = "xml")]
+    XmlParseFailed {
+        context: RequestContext,
+        source: quick_xml::DeError,
+    },
     
     /// A [`ureq`] request was successful, but returned a 403 Forbidden error code.
     /// 
-    /// This is usually caused by not having valid [authentication][`Item`].
-    Forbidden(ureq::Response),
+    /// This is usually caused by not having valid [authentication][`Item`]. For downloads, it is also
+    /// returned when a file is access-restricted, and neither the credentials nor the [session cookies][`Item::with_cookies`]
+    /// are entitled to it.
+    Forbidden {
+        context: RequestContext,
+        response: ureq::Response,
+    },
+    
+    /// A [`ureq`] request was successful, but returned a 429 Too Many Requests error code.
+    /// 
+    /// This is only returned after any [automatic retries][`crate::client`] have been exhausted. Use
+    /// [`ResponseError::retry_after`][`crate::client::ResponseError::retry_after`] to find out how long the server asked the client to wait.
+    TooManyRequests {
+        context: RequestContext,
+        response: ureq::Response,
+    },
+    
+    /// The requested item or file does not exist.
+    /// 
+    /// Returned for 404 Not Found responses, as well as when the Metadata API returns an empty
+    /// record (which it does for nonexistent items).
+    NotFound {
+        context: RequestContext,
+    },
+    
+    /// The item is darked (hidden), and the request was denied.
+    /// 
+    /// Darked items can only be accessed by their owner and privileged accounts, so this usually means
+    /// the [credentials][`Item::with_credentials`] are missing or lack access to the item.
+    Dark {
+        context: RequestContext,
+    },
+    
+    /// An upload was refused because the file already exists, and [overwrite protection][`Item::with_overwrite_protection`]
+    /// is enabled.
+    AlreadyExists {
+        context: RequestContext,
+    },
+    
+    /// A modification was refused because the item's tasks are in a problematic state, and the
+    /// [task guard][`Item::with_task_guard`] is enabled.
+    /// 
+    /// `summary` holds the counts of the item's active tasks at the time of the check.
+    #[cfg(feature = "json")]
+    TasksPending {
+        context: RequestContext,
+        summary: tasks::search::Summary,
+    },
+    
+    /// The request was received, but refused by the Internet Archive with an error message.
+    Rejected {
+        context: RequestContext,
+        message: String,
+    },
+    
+    /// An argument of the operation is invalid (e.g. a date range which ends before it starts, or an
+    /// out-of-range task priority), so the request was not sent.
+    InvalidArgument {
+        context: RequestContext,
+        message: String,
+    },
     
     /// Item identifier is invalid according to [`validate_identifier`].
     InvalidIdentifier(String),
+    
+    /// An option passed to an [item builder][`builder::Builder`] is invalid, or conflicts with another option.
+    /// 
+    /// `option` is the name of the builder method which set the invalid option.
+    InvalidConfig {
+        option: &'static str,
+        reason: &'static str,
+    },
+    
+    /// A local path cannot be represented as a file path within an item (see [`sync::normalize_path`]),
+    /// or is a symbolic link refused by the sync's [symlink policy][`sync::SymlinkPolicy`]. Also returned
+    /// when a file path within an item can't be safely placed within a local directory (e.g. it contains
+    /// a `..` component).
+    InvalidPath {
+        path: PathBuf,
+        reason: &'static str,
+    },
 }
-impl From<std::io::Error> for ItemError {
-    fn from(value: std::io::Error) -> Self {
-        Self::Io(value)
+impl ItemError {
+    /// Returns the context of the request this error occurred during, if any.
+    pub fn context(&self) -> Option<&RequestContext> {
+        match self {
+            Self::Io { context, .. } => context.as_ref(),
+            #[cfg(feature = "xml")]
+            Self::XmlParseFailed { context, .. } => Some(context),
+            #[cfg(feature = "json")]
+            Self::TasksPending { context, .. } => Some(context),
+            Self::Ureq { context, .. } | Self::Forbidden { context, .. } | Self::TooManyRequests { context, .. } | Self::NotFound { context } | Self::Dark { context } | Self::AlreadyExists { context } | Self::Rejected { context, .. } | Self::InvalidArgument { context, .. } => Some(context),
+            Self::InvalidIdentifier(_) | Self::InvalidConfig { .. } | Self::InvalidPath { .. } => None,
+        }
+    }
+    
+    pub(crate) fn io(context: &RequestContext, source: std::io::Error) -> Self {
+        Self::Io { context: Some(context.clone()), source }
+    }
+    
+    pub(crate) fn ureq(context: &RequestContext, source: ureq::Error) -> Self {
+        let (source, detail) = client::read_s3_detail(source);
+        let mut context = context.with_error(&source);
+        if let Some(response) = context.response.as_mut() {
+            response.detail = detail;
+        }
+        
+        match source {
+            ureq::Error::Status(403, response) => Self::Forbidden { context, response },
+            ureq::Error::Status(404, _) => Self::NotFound { context },
+            ureq::Error::Status(429, response) => Self::TooManyRequests { context, response },
+            _ => Self::Ureq { context, source },
+        }
+    }
+    
+    #[cfg(feature = "xml")]
+    pub(crate) fn xml(context: &RequestContext, source: quick_xml::DeError) -> Self {
+        Self::XmlParseFailed { context: context.clone(), source }
+    }
+    
+    #[cfg(feature = "json")]
+    pub(crate) fn views(context: &RequestContext, source: ViewsError) -> Self {
+        match source {
+            ViewsError::Io(err) => Self::io(context, err),
+            ViewsError::Ureq(err) => Self::ureq(context, err),
+            ViewsError::Forbidden(response) => Self::Forbidden { context: context.clone(), response },
+            ViewsError::TooManyRequests(response) => Self::TooManyRequests { context: context.clone(), response },
+            ViewsError::InvalidDateRange(start, end) => Self::InvalidArgument { context: context.clone(), message: format!("invalid date range: {start} to {end}") },
+        }
+    }
+    
+    #[cfg(feature = "json")]
+    pub(crate) fn tasks(context: &RequestContext, source: TaskError) -> Self {
+        match source {
+            TaskError::Io { source, .. } => Self::io(context, source),
+            TaskError::Ureq { source, .. } => Self::ureq(context, source),
+            TaskError::Forbidden { response, .. } => Self::Forbidden { context: context.clone(), response },
+            TaskError::TooManyRequests { response, .. } => Self::TooManyRequests { context: context.clone(), response },
+            TaskError::Rejected { message, .. } => Self::Rejected { context: context.clone(), message },
+            TaskError::InvalidPriority(priority) => Self::InvalidArgument { context: context.clone(), message: format!("invalid priority: {priority}") },
+        }
+    }
+}
+impl fmt::Display for ItemError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { context: Some(context), source } => write!(f, "{context}: {source}"),
+            Self::Io { context: None, source } => write!(f, "{source}"),
+            Self::Ureq { context, source } => write!(f, "{context}: {source}"),
+            #[cfg(feature = "xml")]
+            Self::XmlParseFailed { context, source } => write!(f, "{context}: failed to parse XML: {source}"),
+            Self::Forbidden { context, .. } => write!(f, "{context}: 403 Forbidden"),
+            Self::TooManyRequests { context, .. } => write!(f, "{context}: 429 Too Many Requests"),
+            Self::NotFound { context } => write!(f, "{context}: not found"),
+            Self::Dark { context } => write!(f, "{context}: item is dark"),
+            Self::AlreadyExists { context } => write!(f, "{context}: file already exists"),
+            #[cfg(feature = "json")]
+            Self::TasksPending { context, summary } => write!(f, "{context}: item has {} queued, {} running, {} failed, and {} paused tasks", summary.queued, summary.running, summary.error, summary.paused),
+            Self::Rejected { context, message } => write!(f, "{context}: rejected: {message}"),
+            Self::InvalidArgument { context, message } => write!(f, "{context}: {message}"),
+            Self::InvalidIdentifier(identifier) => write!(f, "invalid item identifier: '{identifier}'"),
+            Self::InvalidConfig { option, reason } => write!(f, "invalid item option '{option}': {reason}"),
+            Self::InvalidPath { path, reason } => write!(f, "invalid file path '{}': {reason}", path.display()),
+        }
+    }
+}
+impl std::error::Error for ItemError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Ureq { source, .. } => Some(source),
+            #[cfg(feature = "xml")]
+            Self::XmlParseFailed { source, .. } => Some(source),
+            _ => None,
+        }
     }
 }
-impl From<ureq::Error> for ItemError {
-    fn from(value: ureq::Error) -> Self {
-        match value {
-            ureq::Error::Status(403, resp) => Self::Forbidden(resp),
-            _ => Self::Ureq(value)
+impl client::sealed::FailedResponse for ItemError {
+    fn failed_response(&self) -> Option<&ureq::Response> {
+        match self {
+            Self::Ureq { source: ureq::Error::Status(_, resp), .. } | Self::Forbidden { response: resp, .. } | Self::TooManyRequests { response: resp, .. } => Some(resp),
+            _ => None,
+        }
+    }
+    
+    fn failed_context(&self) -> Option<&RequestContext> {
+        self.context()
+    }
+    
+    fn failed_transport(&self) -> Option<&ureq::Transport> {
+        match self {
+            Self::Ureq { source: ureq::Error::Transport(transport), .. } => Some(transport),
+            _ => None,
         }
     }
 }
-impl From<serde_xml_rs::Error> for ItemError {
-    fn from(value: serde_xml_rs::Error) -> Self {
-        Self::XmlParseFailed(value)
+impl From<std::io::Error> for ItemError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io { context: None, source: value }
     }
 }
 
+#[cfg(feature = "json")]
+#[derive(Debug, Deserialize)]
+struct UploadLimitResponse {
+    #[serde(default, deserialize_with = "deserialize_number")]
+    over_limit: Option<u8>,
+    detail: Option<UploadLimitDetail>,
+}
+
+#[cfg(feature = "json")]
+#[derive(Debug, Default, Deserialize)]
+struct UploadLimitDetail {
+    #[serde(default, deserialize_with = "deserialize_number")]
+    accesskey_ration: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_number")]
+    accesskey_tasks_queued: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_string")]
+    limit_reason: Option<String>,
+}
+
+#[cfg(feature = "xml")]
 #[derive(Debug, Clone, Deserialize, PartialEq)]
 #[serde(rename_all = "PascalCase")]
 struct ListBucketResult {
+    #[serde(default)]
     contents: Vec<FileEntry>
 }
 
@@ -73,16 +340,201 @@ pub struct FileEntry {
     pub len: usize,
 }
 
+/// Metadata of a single file within an item, as reported by the Metadata API.
+/// 
+/// The Metadata API usually reports every value as a string, but some items include numbers, booleans,
+/// or lists. Commonly used keys are parsed into their respective fields, while any other keys are kept
+/// in [`extra`][`FileMetadata::extra`].
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct FileMetadata {
+    /// Path of the file, relative to the root of the item.
+    pub name: String,
+    
+    /// Where the file came from (e.g. "original", "derivative", "metadata").
+    #[serde(default, deserialize_with = "deserialize_string")]
+    pub source: Option<String>,
+    
+    /// Textual name of the file's format (e.g. "MPEG4", "Thumbnail").
+    #[serde(default, deserialize_with = "deserialize_string")]
+    pub format: Option<String>,
+    
+    /// Name of the file this file was derived from. Usually only present on derivatives.
+    #[serde(default, deserialize_with = "deserialize_string")]
+    pub original: Option<String>,
+    
+    /// Size of the file in bytes.
+    #[serde(default, deserialize_with = "deserialize_number")]
+    pub size: Option<u64>,
+    
+    /// UNIX epoch timestamp of when the file was last modified.
+    #[serde(default, deserialize_with = "deserialize_number")]
+    pub mtime: Option<i64>,
+    
+    /// MD5 checksum, as a hex string.
+    #[serde(default, deserialize_with = "deserialize_string")]
+    pub md5: Option<String>,
+    
+    /// CRC32 checksum, as a hex string.
+    #[serde(default, deserialize_with = "deserialize_string")]
+    pub crc32: Option<String>,
+    
+    /// SHA1 checksum, as a hex string.
+    #[serde(default, deserialize_with = "deserialize_string")]
+    pub sha1: Option<String>,
+    
+    /// All other keys provided for this file, normalized the same way as the [item's metadata][`MetadataResponse::metadata`].
+    #[serde(flatten, deserialize_with = "deserialize_metadata")]
+    pub extra: HashMap<String, MetaValue>,
+}
+#[cfg(feature = "json")]
+impl FileMetadata {
+    /// Returns true if this file was uploaded to the item, rather than produced by a derive or
+    /// other Internet Archive process.
+    pub fn is_original(&self) -> bool {
+        self.source.as_deref() == Some("original")
+    }
+}
+
+/// Parses a number which the Metadata API has encoded as either a string or a number.
+/// 
+/// Values that cannot be parsed are treated as missing.
+#[cfg(feature = "json")]
+pub(crate) fn deserialize_number<'de, D: Deserializer<'de>, T: FromStr>(de: D) -> Result<Option<T>, D::Error> {
+    Ok(match Option::<serde_json::Value>::deserialize(de)? {
+        Some(serde_json::Value::String(s)) => s.trim().parse().ok(),
+        Some(serde_json::Value::Number(n)) => n.to_string().parse().ok(),
+        _ => None,
+    })
+}
+
+/// Parses a string which the Metadata API may have encoded as a number or boolean.
+#[cfg(feature = "json")]
+pub(crate) fn deserialize_string<'de, D: Deserializer<'de>>(de: D) -> Result<Option<String>, D::Error> {
+    Ok(Option::<serde_json::Value>::deserialize(de)?.and_then(value_to_string))
+}
+
+/// Parses a boolean which the Metadata API has encoded as a boolean, string, or number.
+/// 
+/// Values that cannot be parsed are treated as false.
+#[cfg(feature = "json")]
+pub(crate) fn deserialize_bool<'de, D: Deserializer<'de>>(de: D) -> Result<bool, D::Error> {
+    Ok(Option::<serde_json::Value>::deserialize(de)?.is_some_and(|value| value_to_bool(&value)))
+}
+
+/// Converts a boolean, string, or number into a boolean, as done by [`deserialize_bool`].
+#[cfg(feature = "json")]
+pub(crate) fn value_to_bool(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Bool(b) => *b,
+        serde_json::Value::String(s) => matches!(s.trim().to_ascii_lowercase().as_str(), "true" | "1" | "yes"),
+        serde_json::Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        _ => false,
+    }
+}
+
+/// Parses an item's metadata, normalizing every value into either a string or a list of strings.
+/// 
+/// Old items in particular may contain numbers, booleans, or nested values. Scalars are converted
+/// to strings, nested values are kept as their JSON text, and nulls are dropped.
+#[cfg(feature = "json")]
+pub(crate) fn deserialize_metadata<'de, D: Deserializer<'de>>(de: D) -> Result<HashMap<String, MetaValue>, D::Error> {
+    use serde_json::Value;
+    
+    let map = Option::<HashMap<String, Value>>::deserialize(de)?.unwrap_or_default();
+    
+    Ok(map.into_iter().filter_map(|(key, value)| {
+        let value = match value {
+            Value::Array(values) => MetaValue::Multiple(values.into_iter().filter_map(value_to_string).collect()),
+            value => MetaValue::Single(value_to_string(value)?),
+        };
+        
+        Some((key, value))
+    }).collect())
+}
+
+/// Converts a scalar into a string. Nested values are kept as their JSON text, and nulls are dropped.
+#[cfg(feature = "json")]
+pub(crate) fn value_to_string(value: serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(s) => Some(s),
+        value => Some(value.to_string()),
+    }
+}
+
+/// Formats the `x-amz-copy-source` of a server-side copy, percent-encoding each segment of the path.
+fn copy_source(source_item: &str, source_path: &str) -> String {
+    let path: Vec<String> = source_path.trim_start_matches('/').split('/').map(client::encode_segment).collect();
+    
+    format!("/{source_item}/{}", path.join("/"))
+}
+
+/// Adds an `x-archive-meta-*` header for each key-value pair.
+/// 
+/// A repeated key gives the field multiple values, which are sent as numbered headers (e.g.
+/// `x-archive-meta01-subject` and `x-archive-meta02-subject`).
+fn set_meta(mut req: ureq::Request, meta: &[(&str, &str)]) -> ureq::Request {
+    for (i, (key, val)) in meta.iter().enumerate() {
+        let count = meta.iter().filter(|(other, _)| other == key).count();
+        let index = meta[..i].iter().filter(|(other, _)| other == key).count() + 1;
+        
+        req = match count {
+            1 => req.set_header(XMeta { name: key.to_string(), value: val.to_string() }),
+            _ => req.set_header(Custom(format!("x-archive-meta{index:02}-{key}"), meta_value(val))),
+        };
+    }
+    
+    req
+}
+
+/// Converts a path within an item into a path within the local directory.
+/// 
+/// File names come from the item's metadata (or a bag's manifest), so each component is checked before
+/// it is joined, ensuring the resulting path can't escape `root`.
+/// 
+/// # Errors
+/// Returns [`ItemError::InvalidPath`] if a component of `name` is empty, `.`, or `..`, or isn't a plain
+/// file name on this platform (e.g. a Windows drive prefix such as `C:`, or a component containing `\`).
+pub(crate) fn local_path(root: &Path, name: &str) -> Result<PathBuf, ItemError> {
+    let mut path = root.to_path_buf();
+    for part in name.split('/') {
+        let mut components = Path::new(part).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(component)), None) if component == part => path.push(part),
+            _ => return Err(ItemError::InvalidPath { path: PathBuf::from(name), reason: "contains an empty, `.`, `..`, or non-file name component" }),
+        }
+    }
+    
+    Ok(path)
+}
+
+/// Information about a file, as returned by [`Item::head_file`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileHead {
+    /// Size of the file in bytes.
+    pub size: Option<u64>,
+    
+    /// UNIX epoch timestamp of when the file was last modified.
+    pub last_modified: Option<i64>,
+    
+    /// Entity tag of the file's current contents, without quotes. Changes whenever the file changes.
+    pub etag: Option<String>,
+}
+
 /// Contains the metadata for an item and additional meta-metadata.
+#[cfg(feature = "json")]
 #[derive(Debug, Clone, Deserialize, PartialEq)]
 pub struct MetadataResponse {
     /// UNIX epoch timestamp of when this [metadata record][`MetadataRecord`] was created.
     /// 
     /// To determine the item's creation time, check for `"addeddate"` in the [`field@metadata`] field instead.
-    created: isize,
+    #[serde(default, deserialize_with = "deserialize_number")]
+    created: Option<i64>,
     
     /// Pseudo-random value used internally by the Metadata API.
-    uniq: isize,
+    #[serde(default, deserialize_with = "deserialize_number")]
+    uniq: Option<i64>,
     
     /// URL of the primary data server the item is stored on.
     /// 
@@ -91,6 +543,7 @@ pub struct MetadataResponse {
     /// value is **not recommended**.
     /// 
     /// For download URLs, use `https://archive.org/download/{identifier}/{relative_file_path}` instead.
+    #[serde(default)]
     d1: String,
     
     /// URL of the secondary (backup) data server the item is stored on. May be `None` or empty in rare cases.
@@ -100,6 +553,7 @@ pub struct MetadataResponse {
     /// value is **not recommended**.
     /// 
     /// For download URLs, use `https://archive.org/download/{identifier}/{relative_file_path}` instead.
+    #[serde(default)]
     d2: Option<String>,
     
     /// Absolute path of the item located on both data nodes.
@@ -109,16 +563,19 @@ pub struct MetadataResponse {
     /// value is **not recommended**.
     /// 
     /// For download URLs, use `https://archive.org/download/{identifier}/{relative_file_path}` instead.
+    #[serde(default)]
     dir: String,
     
     /// Preferred server (domain only) for reading the item's contents.
+    #[serde(default)]
     server: String,
     
     /// List of data servers that are currently available for accessing the item's contents.
+    #[serde(default)]
     workable_servers: Vec<String>,
     
     /// True if one or both of the primary and backup servers are unavailable/inaccessible.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_bool")]
     servers_unavailable: bool,
     
     /// The metadata of the item itself.
@@ -129,56 +586,53 @@ pub struct MetadataResponse {
     /// 
     /// Metadata is usually made up of string key-value pairs, but some keys may correspond to a
     /// list of values.
-    metadata: HashMap<String, serde_json::Value>,
+    #[serde(default, deserialize_with = "deserialize_metadata")]
+    metadata: HashMap<String, MetaValue>,
     
     /// Total size (bytes) of all files within the item.
-    item_size: usize,
+    #[serde(default, deserialize_with = "deserialize_number")]
+    item_size: Option<u64>,
     
     /// UNIX epoch timestamp of when the item was last modified.
-    item_last_updated: isize,
+    #[serde(default, deserialize_with = "deserialize_number")]
+    item_last_updated: Option<i64>,
     
     /// Total number of files in the item.
-    files_count: usize,
+    #[serde(default, deserialize_with = "deserialize_number")]
+    files_count: Option<usize>,
     
     /// The metadata of each file within the item.
     /// 
     /// This data is also stored in the `<identifier>_files.xml` file. Keep in mind,
     /// recent metadata changes may not have yet been written to disk, but will be available
     /// in this field.
-    /// 
-    /// # Common Keys
-    /// * `name`: Filename
-    /// * `crc32`: CRC32 checksum
-    /// * `md5`: MD5 checksum
-    /// * `sha1`: SHA1 checksum
-    /// * `format`: Textual name of the file's format (e.g. "MPEG4", "Thumbnail")
-    /// * `mtime`: UNIX epoch timestamp of when the file was last modified
-    /// * `size`: Size of the file in bytes
-    /// * `source`: Where the file came from (e.g. "original", "derivative")
-    files: Vec<HashMap<String, String>>,
+    #[serde(default)]
+    files: Vec<FileMetadata>,
     
     /// True if one or more catalog [tasks][`crate::tasks`] are queued or running.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_bool")]
     pending_tasks: bool,
     
     /// True if one or more catalog [tasks][`crate::tasks`] were halted due to an error.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_bool")]
     has_redrow: bool,
     
     //TODO: tasks: ?, // List of queued tasks https://archive.org/developers/md-record.html#catalog-fields
     
-    //TODO: reviews: ?, // List of reviews given by IA users https://archive.org/developers/md-record.html#reviews-field
+    /// List of reviews given by Internet Archive users.
+    #[serde(default)]
+    reviews: Vec<Review>,
     
     /// True if the item is darked (hidden) and unavailable.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_bool")]
     is_dark: bool,
     
     /// True if the item is not ready for downloading yet.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_bool")]
     nodownload: bool,
     
     /// True if the item is a collection.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_bool")]
     is_collection: bool
     
     //TODO: simplelists: SimpleLists, // Holds the SimpleLists structure for the item https://archive.org/developers/simplelists.html
@@ -186,6 +640,140 @@ pub struct MetadataResponse {
     //TODO: User JSON fields https://archive.org/developers/md-record.html#user-json-fields
 }
 
+#[cfg(feature = "json")]
+impl MetadataResponse {
+    /// Returns the metadata of the item itself (e.g. `title`, `collection`, `subject`).
+    /// 
+    /// Each value is either a single string, or a list of strings for keys with multiple values.
+    pub fn metadata(&self) -> &HashMap<String, MetaValue> {
+        &self.metadata
+    }
+    
+    /// Returns the value of a single metadata field, if the item has it.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use iars::Item;
+    /// 
+    /// let meta = Item::new("test_item")?.metadata()?;
+    /// 
+    /// let title = meta.field("title").and_then(|title| title.first()).unwrap_or("untitled");
+    /// for subject in meta.field("subject").into_iter().flat_map(|subject| subject.iter()) {
+    ///     println!("{title}: {subject}");
+    /// }
+    /// # Ok::<(), iars::ItemError>(())
+    /// ```
+    pub fn field(&self, key: &str) -> Option<&MetaValue> {
+        self.metadata.get(key)
+    }
+    
+    /// Returns the metadata of every file within the item.
+    pub fn files(&self) -> &[FileMetadata] {
+        &self.files
+    }
+    
+    /// Returns every review of the item.
+    pub fn reviews(&self) -> &[Review] {
+        &self.reviews
+    }
+    
+    /// Returns true if the item is darked (hidden).
+    /// 
+    /// The full record of a darked item is only returned to its owner and privileged accounts. For
+    /// everyone else, [`Item::metadata`] returns an [`ItemError::Dark`] instead.
+    pub fn is_dark(&self) -> bool {
+        self.is_dark
+    }
+    
+    /// Finds the metadata of a file by its path within the item.
+    pub fn file(&self, name: &str) -> Option<&FileMetadata> {
+        self.files.iter().find(|file| file.name == name)
+    }
+    
+    /// Returns every file whose [format][`FileMetadata::format`] exactly matches `format` (e.g. "MPEG4").
+    pub fn files_by_format(&self, format: &str) -> Vec<&FileMetadata> {
+        self.files.iter().filter(|file| file.format.as_deref() == Some(format)).collect()
+    }
+    
+    /// Returns every file that was [originally uploaded][`FileMetadata::is_original`] to the item.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use iars::Item;
+    /// 
+    /// let meta = Item::new("test_item")?.metadata()?;
+    /// 
+    /// let md5 = meta.originals()
+    ///     .into_iter()
+    ///     .find(|file| file.format.as_deref() == Some("MPEG4"))
+    ///     .and_then(|file| file.md5.clone());
+    /// # Ok::<(), iars::ItemError>(())
+    /// ```
+    pub fn originals(&self) -> Vec<&FileMetadata> {
+        self.files.iter().filter(|file| file.is_original()).collect()
+    }
+    
+    /// Returns the item's BitTorrent file (`{identifier}_archive.torrent`), if one exists.
+    /// 
+    /// The Internet Archive generates a torrent for most public items, which can be used to download
+    /// the item's files using a BitTorrent client. See [`Item::download_torrent`].
+    pub fn torrent(&self) -> Option<&FileMetadata> {
+        self.files.iter().find(|file| file.format.as_deref() == Some("Archive BitTorrent") || file.name.ends_with("_archive.torrent"))
+    }
+    
+    /// Maps the name of every [original][`FileMetadata::is_original`] file to the files derived from it.
+    /// 
+    /// Derivatives are usually produced from an original, but some are produced from other
+    /// derivatives (e.g. thumbnails of a derived video). These are followed back to the original
+    /// they ultimately came from. Originals without any derivatives are included with an empty list,
+    /// and derivatives whose original cannot be found are omitted.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use iars::Item;
+    /// 
+    /// let meta = Item::new("test_item")?.metadata()?;
+    /// 
+    /// for (original, derivatives) in meta.derivatives() {
+    ///     if !derivatives.iter().any(|file| file.format.as_deref() == Some("MPEG4")) {
+    ///         println!("{original} has no MPEG4 derivative");
+    ///     }
+    /// }
+    /// # Ok::<(), iars::ItemError>(())
+    /// ```
+    pub fn derivatives(&self) -> HashMap<&str, Vec<&FileMetadata>> {
+        let mut map: HashMap<&str, Vec<&FileMetadata>> = self.originals()
+            .into_iter()
+            .map(|file| (file.name.as_str(), vec![]))
+            .collect();
+        
+        for file in self.files.iter().filter(|file| !file.is_original()) {
+            let mut original = file.original.as_deref();
+            let mut depth = 0;
+            while let Some(name) = original {
+                if map.contains_key(name) {
+                    break;
+                }
+                
+                // guard against cycles in malformed metadata
+                depth += 1;
+                if depth > self.files.len() {
+                    original = None;
+                    break;
+                }
+                
+                original = self.file(name).and_then(|parent| parent.original.as_deref());
+            }
+            
+            if let Some(derivatives) = original.and_then(|name| map.get_mut(name)) {
+                derivatives.push(file);
+            }
+        }
+        
+        map
+    }
+}
+
 /// Represents a particular item on the Internet Archive.
 /// 
 /// An item could be a book, a song, a movie, a file or set of files, etc. Each item uses an identifier
@@ -196,20 +784,62 @@ pub struct MetadataResponse {
 /// Some actions involving an item may require authentication by making use of an access key and a
 /// secret key. Users can get these API keys from <https://archive.org/account/s3.php> and are provided
 /// to this representation using the [`Credentials`] type.
+/// 
+/// # Sharing
+/// An `Item` is cheap to clone, as its configuration is reference-counted and only copied if a clone
+/// is later reconfigured using one of the `with_*` methods. Items are also [`Send`] and [`Sync`], so a
+/// single item can be shared by (or cloned into) multiple worker threads.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Item {
+    inner: Arc<ItemInner>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ItemInner {
     identifier: String,
     credentials: Option<Credentials>,
+    cookies: Option<SessionCookies>,
     keep_old_versions: bool,
     auto_make_bucket: bool,
+    overwrite_protection: bool,
+    #[cfg(feature = "json")]
+    task_guard: Option<usize>,
+    decompress_gzip: bool,
+    preserve_mtime: bool,
     use_test_collection: bool,
+    metadata_params: Vec<(String, String)>,
+    spool_threshold: Option<u64>,
     useragent: String,
+    client: Client,
+}
+impl ItemInner {
+    /// Creates the default configuration of an item, without validating its identifier.
+    fn new(identifier: String) -> Self {
+        Self {
+            identifier,
+            credentials: None,
+            cookies: None,
+            keep_old_versions: false,
+            auto_make_bucket: true,
+            overwrite_protection: false,
+            #[cfg(feature = "json")]
+            task_guard: None,
+            decompress_gzip: false,
+            preserve_mtime: false,
+            use_test_collection: false,
+            metadata_params: vec![],
+            spool_threshold: None,
+            useragent: default_user_agent(),
+            client: Client::shared(),
+        }
+    }
 }
 impl Item {
     /// Creates a new reference to an item on the Internet Archive.
     /// 
     /// Some actions on this item may require authentication. [`Credentials`] can be provided using
-    /// [`Self::with_credentials`].
+    /// [`Self::with_credentials`]. To validate the whole configuration of an item when it is created,
+    /// use [`Item::builder`] instead.
     /// 
     /// # Errors
     /// If [`validate_identifier`] determines the provided identifier is invalid, an
@@ -221,15 +851,15 @@ impl Item {
         }
         
         Ok(Self {
-            identifier,
-            credentials: None,
-            keep_old_versions: false,
-            auto_make_bucket: true,
-            use_test_collection: false,
-            useragent: DEFAULT_USER_AGENT.to_string(),
+            inner: Arc::new(ItemInner::new(identifier)),
         })
     }
     
+    /// Returns the item's identifier.
+    pub fn identifier(&self) -> &str {
+        &self.inner.identifier
+    }
+    
     /// Provide authentication credentials to be used with all queries for this item.
     /// 
     /// Many operations on the Internet Archive, such as uploading or deleting files, require
@@ -239,20 +869,39 @@ impl Item {
     /// Operations that require authentication but where none are provided, or when the keys are invalid,
     /// will result in a 403 Forbidden error.
     pub fn with_credentials(mut self, credentials: Option<Credentials>) -> Self {
-        self.credentials = credentials;
+        Arc::make_mut(&mut self.inner).credentials = credentials;
+        
+        self
+    }
+    
+    /// Provide session cookies to be used when downloading files from this item.
+    /// 
+    /// Some access-restricted files (e.g. stream-only or lending files) can only be downloaded by a
+    /// logged-in session, even when valid [credentials][`Item::with_credentials`] are provided. Cookies
+    /// are only sent with downloads, and only to Internet Archive hosts.
+    pub fn with_cookies(mut self, cookies: Option<SessionCookies>) -> Self {
+        Arc::make_mut(&mut self.inner).cookies = cookies;
         
         self
     }
     
     /// Configures the User-Agent string provided in all API queries for this item.
     /// 
-    /// If `None` or if the string is empty, a [default][`DEFAULT_USER_AGENT`] will be used.
+    /// If `None` or if the string is empty, a [default][`default_user_agent`] will be used.
     pub fn with_useragent(mut self, useragent: Option<String>) -> Self {
-        if useragent.is_none() || useragent.as_ref().unwrap().is_empty() {
-            self.useragent = DEFAULT_USER_AGENT.to_string();
-        } else {
-            self.useragent = useragent.unwrap();
-        }
+        Arc::make_mut(&mut self.inner).useragent = match useragent {
+            Some(useragent) if !useragent.is_empty() => useragent,
+            _ => default_user_agent(),
+        };
+        
+        self
+    }
+    
+    /// Configures the [`Client`] used to perform all API queries for this item.
+    /// 
+    /// By default, the [shared client][`Client::shared`] is used.
+    pub fn with_client(mut self, client: Client) -> Self {
+        Arc::make_mut(&mut self.inner).client = client;
         
         self
     }
@@ -264,7 +913,7 @@ impl Item {
     /// 
     /// The old version of the file will be moved by the Internet Archive into `history/files/{filename}.~N~`.
     pub fn with_keep_old_versions(mut self, keep_old_versions: bool) -> Self {
-        self.keep_old_versions = keep_old_versions;
+        Arc::make_mut(&mut self.inner).keep_old_versions = keep_old_versions;
         
         self
     }
@@ -274,126 +923,522 @@ impl Item {
     /// 
     /// This is true (enabled) by default.
     pub fn with_auto_make(mut self, auto_make_bucket: bool) -> Self {
-        self.auto_make_bucket = auto_make_bucket;
+        Arc::make_mut(&mut self.inner).auto_make_bucket = auto_make_bucket;
         
         self
     }
     
-    /// Uploads a file to this item.
+    /// Configures whether or not a newly created item should be placed in `test_collection`.
     /// 
-    /// After uploads are completed, the files may not be immediately available on Internet Archive.
-    /// Use the [tasks][`crate::tasks`] module to check the status of the uploaded files.
+    /// Items in `test_collection` are automatically removed after 30 days, making it suitable for
+    /// experimenting and testing. This only applies to uploads which create the item, and is ignored if
+    /// the upload's initial metadata already sets a `collection`.
     /// 
-    /// # Derivation
-    /// Normally, file uploads will cause the Internet Archive to queue a "derive" process on the item.
-    /// This process produces secondary files to improve usability of the uploaded data. Setting the
-    /// `derive` argument to `false` will prevent this process.
+    /// This is false (disabled) by default.
+    pub fn with_test_collection(mut self, use_test_collection: bool) -> Self {
+        Arc::make_mut(&mut self.inner).use_test_collection = use_test_collection;
+        
+        self
+    }
+    
+    /// Configures whether or not [`Item::upload_file`] (and [multipart uploads][`multipart`]) should
+    /// refuse to replace files which already exist in the item.
     /// 
-    /// # Metadata
-    /// Item metadata can be provided in key-value pairs. **If the Internet
-    /// Archive item already exists, or is not [automatically created][`Item::with_auto_make`],
-    /// this metadata will be silently discarded.**
+    /// When enabled, each upload first checks whether the remote file exists, and fails with
+    /// [`ItemError::AlreadyExists`] before any data is transferred if it does. This costs an extra
+    /// request per upload, and cannot guard against another client uploading the same file at the same time.
     /// 
-    /// Use [TODO] to add metadata to existing items.
+    /// This is false (disabled) by default.
+    pub fn with_overwrite_protection(mut self, overwrite_protection: bool) -> Self {
+        Arc::make_mut(&mut self.inner).overwrite_protection = overwrite_protection;
+        
+        self
+    }
+    
+    /// Configures a guard which checks the item's tasks before modifying it, refusing to pile more
+    /// tasks onto an item which is already in a problematic state.
     /// 
-    /// # Data Transfer
-    /// The data is read using any [reader][`Read`] implementation. However, the `size` (number of
-    /// bytes to be transfered) **must** be known before the upload begins. The Internet Archive
-    /// _requires_ a `Content-Length` and this length _must_ be accurate.
+    /// When `Some(max_pending)`, uploads, server-side copies, deletes, and metadata writes first
+    /// [search][`Item::tasks`] the item's active tasks. If any task has failed (shown as a red row in
+    /// the item's history) or is paused, or more than `max_pending` tasks are queued or running, the
+    /// modification fails with [`ItemError::TasksPending`] before anything is sent. This costs an extra
+    /// request per modification.
     /// 
-    /// Sizes larger than what the `reader` can provide will stall the upload. Also, no more bytes
-    /// than the specified size will be transfered (meaning if the caller wishes to upload "Hello World!"
-    /// but provides a size of 5, only "Hello" will be uploaded).
+    /// This is `None` (disabled) by default.
     /// 
     /// # Example
     /// ```rust,no_run
-    /// use iars::{Credentials, Item};
-    ///
-    /// let item = Item::new("test_item")
-    ///     .with_credentials(Some(Credentials::new("abcdefghijklmnop", "1234567890123456")));
+    /// use iars::{Credentials, Item, ItemError};
     /// 
-    /// let data = "Hello World!".as_bytes();
+    /// let item = Item::new("test_item")?
+    ///     .with_credentials(Some(Credentials::new("abcdefghijklmnop", "1234567890123456")))
+    ///     .with_task_guard(Some(5));
     /// 
-    /// item.upload_file(true, &[("foo", "bar")], "a_directory/myfile.txt", data, data.len())?;
+    /// match item.delete_file("old.txt", true) {
+    ///     Err(ItemError::TasksPending { summary, .. }) => println!("skipped, {} failed tasks", summary.error),
+    ///     result => { result?; },
+    /// }
     /// # Ok::<(), iars::ItemError>(())
     /// ```
-    /// If successful, the file will be viewable at `https://archive.org/download/test_item/a_directory/myfile.txt`,
-    /// and if the archive item didn't already exist, its metadata will include `foo: "bar"`.
-    /// 
-    /// # Errors
-    /// Possibly returns [`ItemError::Ureq`] if a [`ureq::Error`] is encountered while uploading.
-    pub fn upload_file(&self, derive: bool, initial_meta: &[(&str, &str)], filepath: &str, reader: impl Read, size: usize) -> Result<ureq::Response, ItemError> {
-        let mut req = ureq::put(&format!("https://s3.us.archive.org/{}/{filepath}", self.identifier))
-            .set("user-agent", &self.useragent)
-            .set_header(XKeepOldVersion(self.keep_old_versions))
-            .set_header(XAutoMakeBucket(self.auto_make_bucket))
-            .set_header(XQueueDerive(derive))
-            .set_header(XSizeHint(size))
-            .set("content-length", &size.to_string());
-        
-        for (key, val) in initial_meta {
-            req = req.set_header(XMeta { name: key.to_string(), value: val.to_string() });
-        }
-        
-        if let Some(creds) = self.credentials.as_ref() {
-            req = req.set_header(creds.into());
-        }
+    #[cfg(feature = "json")]
+    pub fn with_task_guard(mut self, max_pending: Option<usize>) -> Self {
+        Arc::make_mut(&mut self.inner).task_guard = max_pending;
         
-        Ok(req.send(reader)?)
+        self
     }
     
-    /// Retrieves a list of all files contained in this item.
-    /// 
-    /// # Errors
-    /// Possibly returns [`ItemError::Ureq`] if a [`ureq::Error`] is encountered while downloading
-    /// the list of files (an XML string).
+    /// Configures whether or not [`Item::download_file`] should transparently decompress gzipped files.
     /// 
-    /// If the query succeeds but the response cannot be parsed, an [`ItemError::XmlParseFailed`]
-    /// is returned.
+    /// When enabled, files whose path ends in `.gz` (such as `_djvu.txt.gz` derivatives) are
+    /// decompressed while being downloaded, so the writer receives the original uncompressed data.
+    /// Other files are downloaded unchanged.
     /// 
-    /// # Panics
-    /// Upon requesting the file list, if the `Content-Length` of the response is larger than 1 GiB,
-    /// this method will panic. Please open a Github issue if this is a concern for your use-case.
-    pub fn list(&self) -> Result<Vec<FileEntry>, ItemError> {
-        let mut req = ureq::get(&format!("https://s3.us.archive.org/{}", self.identifier))
-            .set("user-agent", &self.useragent);
+    /// This is false (disabled) by default.
+    pub fn with_gzip_decompression(mut self, decompress_gzip: bool) -> Self {
+        Arc::make_mut(&mut self.inner).decompress_gzip = decompress_gzip;
+        
+        self
+    }
+    
+    /// Configures whether or not [`Item::download_file_to_path`] should set the modification time of
+    /// downloaded files to that of the remote file.
+    /// 
+    /// The time is taken from the download's `Last-Modified` header, or the file's [`mtime`][`FileMetadata::mtime`]
+    /// metadata if the header is missing. This allows timestamp-based sync tools to work correctly
+    /// with mirrors created by `iars`.
+    /// 
+    /// This is false (disabled) by default.
+    pub fn with_preserve_mtime(mut self, preserve_mtime: bool) -> Self {
+        Arc::make_mut(&mut self.inner).preserve_mtime = preserve_mtime;
+        
+        self
+    }
+    
+    /// Adds an arbitrary query parameter to the requests made by [`Item::metadata`] (and other methods
+    /// which retrieve the item's metadata).
+    /// 
+    /// This allows parameters which aren't modeled by this crate yet to be used. Parameters are sent
+    /// as-is, and can be added multiple times.
+    pub fn with_metadata_param(mut self, key: &str, value: &str) -> Self {
+        Arc::make_mut(&mut self.inner).metadata_params.push((key.to_string(), value.to_string()));
+        
+        self
+    }
+    
+    /// Configures the size (in bytes) above which responses parsed by [`Item::metadata`] and [`Item::list`]
+    /// are spooled to a temporary file, and parsed from disk.
+    /// 
+    /// Items with enormous metadata records or file lists can produce responses of several gigabytes.
+    /// Spooling them frees the connection as soon as possible, and avoids buffering the response in memory.
+    /// Responses which do not state their length are buffered in memory until they exceed the threshold.
+    /// 
+    /// If `None`, responses are always parsed directly from the connection. This is `None` by default.
+    pub fn with_spool_threshold(mut self, threshold: Option<u64>) -> Self {
+        Arc::make_mut(&mut self.inner).spool_threshold = threshold;
+        
+        self
+    }
+    
+    /// Creates the context of a request performed on this item, for use in errors.
+    fn context(&self, operation: &'static str, url: &str) -> RequestContext {
+        RequestContext::new(operation, url, Some(&self.inner.identifier))
+    }
+    
+    /// Uploads a file to this item.
+    /// 
+    /// After uploads are completed, the files may not be immediately available on Internet Archive.
+    /// Use the [tasks][`crate::tasks`] module to check the status of the uploaded files.
+    /// 
+    /// # Derivation
+    /// Normally, file uploads will cause the Internet Archive to queue a "derive" process on the item.
+    /// This process produces secondary files to improve usability of the uploaded data. Setting the
+    /// `derive` argument to `false` will prevent this process.
+    /// 
+    /// # Metadata
+    /// Item metadata can be provided in key-value pairs. A key may be repeated to give a field multiple
+    /// values. **If the Internet Archive item already exists, or is not [automatically created][`Item::with_auto_make`],
+    /// this metadata will be silently discarded.**
+    /// 
+    /// Use [`Item::modify_metadata`] to add metadata to existing items.
+    /// 
+    /// # Data Transfer
+    /// The data is read using any [reader][`Read`] implementation. However, the `size` (number of
+    /// bytes to be transfered) **must** be known before the upload begins. The Internet Archive
+    /// _requires_ a `Content-Length` and this length _must_ be accurate.
+    /// 
+    /// Sizes larger than what the `reader` can provide will stall the upload. Also, no more bytes
+    /// than the specified size will be transfered (meaning if the caller wishes to upload "Hello World!"
+    /// but provides a size of 5, only "Hello" will be uploaded).
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use iars::{Credentials, Item};
+    /// 
+    /// let item = Item::new("test_item")?
+    ///     .with_credentials(Some(Credentials::new("abcdefghijklmnop", "1234567890123456")));
+    /// 
+    /// let data = "Hello World!".as_bytes();
+    /// 
+    /// item.upload_file(true, &[("foo", "bar")], "a_directory/myfile.txt", data, data.len())?;
+    /// # Ok::<(), iars::ItemError>(())
+    /// ```
+    /// If successful, the file will be viewable at `https://archive.org/download/test_item/a_directory/myfile.txt`,
+    /// and if the archive item didn't already exist, its metadata will include `foo: "bar"`.
+    /// 
+    /// # Errors
+    /// Possibly returns [`ItemError::Ureq`] if a [`ureq::Error`] is encountered while uploading.
+    /// 
+    /// If [overwrite protection][`Item::with_overwrite_protection`] is enabled and the file already
+    /// exists, an [`ItemError::AlreadyExists`] is returned.
+    pub fn upload_file(&self, derive: bool, initial_meta: &[(&str, &str)], filepath: &str, reader: impl Read, size: usize) -> Result<ureq::Response, ItemError> {
+        let url = format!("https://s3.us.archive.org/{}/{filepath}", self.inner.identifier);
+        let context = self.context("upload_file", &url);
+        
+        self.check_tasks(&context)?;
+        self.check_overwrite(&url, &context)?;
+        
+        let mut req = self.upload_request("PUT", &url, derive, initial_meta)
+            .set_header(XSizeHint(size))
+            .set("content-length", &size.to_string());
+        
+        if let Some(creds) = self.inner.credentials.as_ref() {
+            req = req.set_header(creds.into());
+        }
+        
+        self.inner.client.send(req, reader).map_err(|err| ItemError::ureq(&context, err))
+    }
+    
+    /// Copies a file from another item (or from elsewhere in this item) into this item, without
+    /// downloading or re-uploading its contents.
+    /// 
+    /// The copy is performed by the Internet Archive, and behaves like an [upload][`Item::upload_file`] of
+    /// the file to `dest_path`: a derive is queued afterwards, the item is created if [enabled][`Item::with_auto_make`],
+    /// and [overwrite protection][`Item::with_overwrite_protection`] applies. Authentication is required,
+    /// and the credentials must have read access to the source item.
+    /// 
+    /// # Errors
+    /// If the source file does not exist, an [`ItemError::NotFound`] is returned. See [`Item::upload_file`]
+    /// for other errors.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use iars::{Credentials, Item};
+    /// 
+    /// let item = Item::new("new_item")?
+    ///     .with_credentials(Some(Credentials::new("abcdefghijklmnop", "1234567890123456")));
+    /// 
+    /// item.copy_file_from("old_item", "videos/talk.mp4", "talk.mp4")?;
+    /// # Ok::<(), iars::ItemError>(())
+    /// ```
+    pub fn copy_file_from(&self, source_item: &str, source_path: &str, dest_path: &str) -> Result<ureq::Response, ItemError> {
+        self.copy_file(true, &[], source_item, source_path, dest_path)
+    }
+    
+    /// Performs a server-side copy, optionally queueing a derive afterwards. `initial_meta` behaves the
+    /// same as in [`Item::upload_file`].
+    fn copy_file(&self, derive: bool, initial_meta: &[(&str, &str)], source_item: &str, source_path: &str, dest_path: &str) -> Result<ureq::Response, ItemError> {
+        if !validate_identifier(source_item) {
+            return Err(ItemError::InvalidIdentifier(source_item.to_string()));
+        }
+        
+        let url = format!("https://s3.us.archive.org/{}/{dest_path}", self.inner.identifier);
+        let context = self.context("copy_file_from", &url);
+        
+        self.check_tasks(&context)?;
+        self.check_overwrite(&url, &context)?;
+        
+        let mut req = self.upload_request("PUT", &url, derive, initial_meta)
+            .set_header(XCopySource(copy_source(source_item, source_path)))
+            .set_header(ContentLength(0));
+        
+        if let Some(creds) = self.inner.credentials.as_ref() {
+            req = req.set_header(creds.into());
+        }
+        
+        self.inner.client.send(req, std::io::empty()).map_err(|err| ItemError::ureq(&context, err))
+    }
+    
+    /// Creates a request which creates a file, with the headers shared by all kinds of file upload.
+    fn upload_request(&self, method: &str, url: &str, derive: bool, initial_meta: &[(&str, &str)]) -> ureq::Request {
+        let mut req = self.inner.client.request(method, url)
+            .set("user-agent", &self.inner.useragent)
+            .set_header(XKeepOldVersion(self.inner.keep_old_versions))
+            .set_header(XAutoMakeBucket(self.inner.auto_make_bucket))
+            .set_header(XQueueDerive(derive));
+        
+        req = set_meta(req, initial_meta);
+        
+        if self.inner.use_test_collection && !initial_meta.iter().any(|(key, _)| *key == "collection") {
+            req = req.set_header(XMeta { name: "collection".to_string(), value: "test_collection".to_string() });
+        }
+        
+        req
+    }
+    
+    /// Fails with [`ItemError::TasksPending`] if the [task guard][`Item::with_task_guard`] is enabled
+    /// and the item's tasks are in a problematic state.
+    #[cfg(feature = "json")]
+    fn check_tasks(&self, context: &RequestContext) -> Result<(), ItemError> {
+        let Some(max_pending) = self.inner.task_guard else {
+            return Ok(());
+        };
+        
+        let resp = self.tasks()
+            .with_categories(true, false, false)
+            .call(None)
+            .map_err(|err| ItemError::tasks(context, err))?;
         
-        if let Some(creds) = self.credentials.as_ref() {
+        match resp.summary {
+            Some(summary) if summary.error > 0 || summary.paused > 0 || summary.queued + summary.running > max_pending => {
+                Err(ItemError::TasksPending { context: context.clone(), summary })
+            },
+            _ => Ok(()),
+        }
+    }
+    
+    /// The task guard requires the `json` feature, so there is nothing to check without it.
+    #[cfg(not(feature = "json"))]
+    fn check_tasks(&self, _context: &RequestContext) -> Result<(), ItemError> {
+        Ok(())
+    }
+    
+    /// Fails with [`ItemError::AlreadyExists`] if [overwrite protection][`Item::with_overwrite_protection`]
+    /// is enabled and the file at `url` already exists.
+    fn check_overwrite(&self, url: &str, context: &RequestContext) -> Result<(), ItemError> {
+        if !self.inner.overwrite_protection {
+            return Ok(());
+        }
+        
+        let mut req = self.inner.client.request("HEAD", url)
+            .set("user-agent", &self.inner.useragent);
+        
+        if let Some(creds) = self.inner.credentials.as_ref() {
             req = req.set_header(creds.into());
         }
         
-        let resp = req.call()?;
+        match self.inner.client.call(req) {
+            Ok(_) => Err(ItemError::AlreadyExists { context: context.clone() }),
+            Err(ureq::Error::Status(404, _)) => Ok(()),
+            Err(err) => Err(ItemError::ureq(context, err)),
+        }
+    }
+    
+    /// Deletes a file from this item.
+    /// 
+    /// If `cascade` is true, any files [derived][`MetadataResponse::derivatives`] from this file are
+    /// deleted as well. If [enabled][`Item::with_keep_old_versions`], the deleted file is backed up
+    /// by the Internet Archive.
+    /// 
+    /// # Errors
+    /// Possibly returns [`ItemError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    /// If the file does not exist, an [`ItemError::NotFound`] is returned instead.
+    pub fn delete_file(&self, filepath: &str, cascade: bool) -> Result<ureq::Response, ItemError> {
+        let url = format!("https://s3.us.archive.org/{}/{filepath}", self.inner.identifier);
+        let context = self.context("delete_file", &url);
+        
+        self.check_tasks(&context)?;
+        
+        let mut req = self.inner.client.request("DELETE", &url)
+            .set("user-agent", &self.inner.useragent)
+            .set_header(XKeepOldVersion(self.inner.keep_old_versions))
+            .set_header(XCascadeDelete(cascade));
+        
+        if let Some(creds) = self.inner.credentials.as_ref() {
+            req = req.set_header(creds.into());
+        }
+        
+        self.inner.client.call(req).map_err(|err| ItemError::ureq(&context, err))
+    }
+    
+    /// Replaces **all** of the item's metadata with the provided key-value pairs.
+    /// 
+    /// This uses the IAS3 technique of re-creating the item's bucket with the
+    /// `x-archive-ignore-preexisting-bucket` header. The item's files are not affected, but every
+    /// existing metadata field that is not included in `meta` will be **removed**, including fields
+    /// such as `collection`, `mediatype`, and `title`. Be sure to include every field that should
+    /// be kept.
+    /// 
+    /// The new metadata is applied by a task queued on the item, so it may not be visible immediately.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use iars::{Credentials, Item};
+    /// 
+    /// let item = Item::new("test_item")?
+    ///     .with_credentials(Some(Credentials::new("abcdefghijklmnop", "1234567890123456")));
+    /// 
+    /// item.overwrite_all_metadata(&[
+    ///     ("collection", "test_collection"),
+    ///     ("mediatype", "texts"),
+    ///     ("title", "A New Title"),
+    /// ])?;
+    /// # Ok::<(), iars::ItemError>(())
+    /// ```
+    /// 
+    /// # Errors
+    /// Possibly returns [`ItemError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    pub fn overwrite_all_metadata(&self, meta: &[(&str, &str)]) -> Result<ureq::Response, ItemError> {
+        let url = format!("https://s3.us.archive.org/{}", self.inner.identifier);
+        let context = self.context("overwrite_all_metadata", &url);
+        
+        self.check_tasks(&context)?;
         
-        const MAX_LEN: usize = 1 * 1024 * 1024 * 1024; // 1 GiB
-        let len: usize = resp
-            .header("content-length")
-            .unwrap_or("")
-            .parse()
-            .unwrap_or(MAX_LEN);
+        let mut req = self.inner.client.request("PUT", &url)
+            .set("user-agent", &self.inner.useragent)
+            .set_header(XIgnorePreexistingBucket(true))
+            .set_header(ContentLength(0));
         
-        if len > MAX_LEN {
-            todo!("Response body is over size limit of {MAX_LEN} bytes!");
+        req = set_meta(req, meta);
+        
+        if let Some(creds) = self.inner.credentials.as_ref() {
+            req = req.set_header(creds.into());
         }
         
-        let result: ListBucketResult = serde_xml_rs::from_reader(resp.into_reader())?;
+        self.inner.client.call(req).map_err(|err| ItemError::ureq(&context, err))
+    }
+    
+    /// Checks whether uploads to this item are currently being rate limited.
+    /// 
+    /// The S3-like API limits how many tasks may be queued per access key and per item. When either
+    /// limit is reached, uploads are refused with a `503 Slow Down` error until some tasks finish. The
+    /// returned [`QuotaInfo`] describes the limit of the [credentials'][`Item::with_credentials`] access key:
+    /// * [`limit`][`QuotaInfo::limit`] is the number of tasks the access key may have queued.
+    /// * [`in_flight`][`QuotaInfo::in_flight`] is the number of tasks currently queued.
+    /// * [`over_limit`][`QuotaInfo::over_limit`] is true if uploads would currently be refused.
+    /// 
+    /// # Errors
+    /// Possibly returns [`ItemError::Ureq`] if a [`ureq::Error`] is encountered while performing the
+    /// request. If the response cannot be parsed, an [`ItemError::Io`] is returned.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use iars::{Credentials, Item};
+    /// 
+    /// let item = Item::new("test_item")?
+    ///     .with_credentials(Some(Credentials::new("abcdefghijklmnop", "1234567890123456")));
+    /// 
+    /// while item.check_upload_limit()?.over_limit == Some(true) {
+    ///     thread::sleep(Duration::from_secs(60));
+    /// }
+    /// # Ok::<(), iars::ItemError>(())
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn check_upload_limit(&self) -> Result<QuotaInfo, ItemError> {
+        let url = "https://s3.us.archive.org/";
+        let context = self.context("check_upload_limit", url);
+        let mut req = self.inner.client.request("GET", url)
+            .set("user-agent", &self.inner.useragent)
+            .query("check_limit", "1")
+            .query("bucket", &self.inner.identifier);
+        
+        if let Some(creds) = self.inner.credentials.as_ref() {
+            req = req.query("accesskey", &creds.access).set_header(creds.into());
+        }
+        
+        let resp: UploadLimitResponse = self.inner.client.call(req)
+            .map_err(|err| ItemError::ureq(&context, err))?
+            .into_json()
+            .map_err(|err| ItemError::io(&context, err))?;
+        
+        let detail = resp.detail.unwrap_or_default();
+        Ok(QuotaInfo {
+            status: None,
+            retry_after: None,
+            limit: detail.accesskey_ration,
+            remaining: detail.accesskey_ration.zip(detail.accesskey_tasks_queued).map(|(limit, queued)| limit.saturating_sub(queued)),
+            in_flight: detail.accesskey_tasks_queued,
+            over_limit: resp.over_limit.map(|over| over != 0),
+            detail: detail.limit_reason.filter(|reason| !reason.is_empty()),
+        })
+    }
+    
+    /// Retrieves a list of all files contained in this item.
+    /// 
+    /// # Errors
+    /// Possibly returns [`ItemError::Ureq`] if a [`ureq::Error`] is encountered while downloading
+    /// the list of files (an XML string).
+    /// 
+    /// If the query succeeds but the response cannot be parsed, an [`ItemError::XmlParseFailed`]
+    /// is returned. If the response is [spooled][`Item::with_spool_threshold`] and the temporary file
+    /// cannot be written, an [`ItemError::Io`] is returned.
+    #[cfg(feature = "xml")]
+    pub fn list(&self) -> Result<Vec<FileEntry>, ItemError> {
+        let (req, context) = self.list_request();
+        let resp = self.inner.client.call(req).map_err(|err| ItemError::ureq(&context, err))?;
+        let body = client::spool(resp, self.inner.spool_threshold).map_err(|err| ItemError::io(&context, err))?;
+        
+        let result: ListBucketResult = quick_xml::de::from_reader(std::io::BufReader::new(body))
+            .map_err(|err| ItemError::xml(&context, err))?;
         
         Ok(result.contents)
     }
     
+    /// Performs the same request as [`Item::list`], but returns the [undecoded response][`RawResponse`]
+    /// (an XML document).
+    /// 
+    /// This does not require the `xml` feature.
+    /// 
+    /// # Errors
+    /// Possibly returns [`ItemError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    pub fn list_raw(&self) -> Result<RawResponse, ItemError> {
+        let (req, context) = self.list_request();
+        let resp = self.inner.client.call(req).map_err(|err| ItemError::ureq(&context, err))?;
+        
+        RawResponse::read(resp).map_err(|err| ItemError::io(&context, err))
+    }
+    
+    fn list_request(&self) -> (ureq::Request, RequestContext) {
+        let url = format!("https://s3.us.archive.org/{}", self.inner.identifier);
+        let context = self.context("list", &url);
+        let mut req = self.inner.client.request("GET", &url)
+            .set("user-agent", &self.inner.useragent);
+        
+        if let Some(creds) = self.inner.credentials.as_ref() {
+            req = req.set_header(creds.into());
+        }
+        
+        (req, context)
+    }
+    
+    /// Retrieves a list of the [original][`FileMetadata::is_original`] files contained in this item,
+    /// excluding derivatives and metadata files produced by the Internet Archive.
+    /// 
+    /// The Metadata API is used to determine which files are originals, so this performs one more
+    /// request than [`Item::list`].
+    /// 
+    /// # Errors
+    /// See [`Item::list`] and [`Item::metadata`].
+    #[cfg(all(feature = "xml", feature = "json"))]
+    pub fn list_originals(&self) -> Result<Vec<FileEntry>, ItemError> {
+        let meta = self.metadata()?;
+        let originals: std::collections::HashSet<&str> = meta.originals().into_iter().map(|file| file.name.as_str()).collect();
+        
+        let mut files = self.list()?;
+        files.retain(|file| originals.contains(file.path.as_str()));
+        
+        Ok(files)
+    }
+    
     /// Downloads a file from this item.
     /// 
     /// The `filepath` corresponds to the location of the file within the item. Use [`Item::list`] to
     /// get a list of all available files in the item.
     /// 
-    /// The data will be streamed into the `writer` (via [`std::io::copy`]). This method does _not_
-    /// provide any size restictions or safeguards on downloads. If the `writer` is resizable and stores
-    /// data in system memory (e.g. [`Vec`]), be sure the file is not larger than available memory or
-    /// else use another [writer][`Write`] implementation.
+    /// The data will be streamed into the `writer` (using the client's [download buffer][`Client::with_download_buffer_size`]).
+    /// This method does _not_ provide any size restictions or safeguards on downloads. If the `writer`
+    /// is resizable and stores data in system memory (e.g. [`Vec`]), be sure the file is not larger than
+    /// available memory or else use another [writer][`Write`] implementation.
     /// 
-    /// On success, the number of bytes written (size of the file) is returned.
+    /// On success, the number of bytes written (size of the file) is returned. If [gzip decompression][`Item::with_gzip_decompression`]
+    /// is enabled and the file is gzipped, this is the size of the decompressed data.
     /// 
     /// # Errors
     /// Possibly returns [`ItemError::Ureq`] if a [`ureq::Error`] is encountered while downloading.
+    /// If the file does not exist, an [`ItemError::NotFound`] is returned instead. If the item is darked
+    /// and access is denied, an [`ItemError::Dark`] is returned. If the file is access-restricted and
+    /// access is denied, an [`ItemError::Forbidden`] is returned.
     /// 
     /// If any [I/O errors][`std::io::Error`] occur while transfering data into the `writer`,
     /// an [`ItemError::Io`] is returned.
@@ -402,51 +1447,652 @@ impl Item {
     /// ```rust,no_run
     /// use std::fs::File;
     /// use iars::Item;
-    ///
-    /// let item = Item::new("test_item");
-    ///
+    /// 
+    /// let item = Item::new("test_item")?;
+    /// 
     /// let mut file = File::create("download.txt")?;
     /// item.download_file("path/to/archived/file.txt", &mut file)?;
     /// # Ok::<(), iars::ItemError>(())
     /// ```
-    pub fn download_file(&self, filepath: &str, mut writer: impl Write) -> Result<u64, ItemError> {
-        let mut req = ureq::get(&format!("https://archive.org/download/{}/{filepath}", self.identifier))
-            .set("user-agent", &self.useragent);
+    pub fn download_file(&self, filepath: &str, writer: impl Write) -> Result<u64, ItemError> {
+        Ok(self.download(filepath, writer)?.0)
+    }
+    
+    /// Downloads a file into the `writer`, returning the number of bytes written along with the
+    /// file's `Last-Modified` time (as a UNIX epoch timestamp), if provided.
+    fn download(&self, filepath: &str, mut writer: impl Write) -> Result<(u64, Option<i64>), ItemError> {
+        let (download, context) = self.open_download("download_file", filepath)?;
+        let last_modified = download.resp.header("last-modified").and_then(client::parse_http_date);
+        
+        let result = if self.inner.decompress_gzip && filepath.ends_with(".gz") {
+            self.inner.client.copy(MultiGzDecoder::new(download.into_reader()), &mut writer)
+        } else {
+            self.inner.client.copy(download.into_reader(), &mut writer)
+        };
         
-        if let Some(creds) = self.credentials.as_ref() {
+        Ok((result.map_err(|err| ItemError::io(&context, err))?, last_modified))
+    }
+    
+    /// Starts downloading a file, returning the response before any of the body has been read.
+    pub(crate) fn open_download(&self, operation: &'static str, filepath: &str) -> Result<(client::Download, RequestContext), ItemError> {
+        let url = format!("https://archive.org/download/{}/{filepath}", self.inner.identifier);
+        let context = self.context(operation, &url);
+        let req = self.download_request("GET", &url);
+        
+        let result = match self.is_authenticated() {
+            true => self.inner.client.call_download_authenticated(req),
+            false => self.inner.client.call_download(req),
+        };
+        let download = match result {
+            Ok(download) => download,
+            Err(err @ ureq::Error::Status(403, _)) => {
+                // a darked item denies downloads with a plain 403, which is only distinguishable via its metadata
+                #[cfg(feature = "json")]
+                if let Err(ItemError::Dark { .. }) = self.metadata_json() {
+                    return Err(ItemError::Dark { context });
+                }
+                
+                return Err(ItemError::ureq(&context, err));
+            },
+            Err(err) => return Err(ItemError::ureq(&context, err)),
+        };
+        
+        Ok((download, context))
+    }
+    
+    /// Creates a request for a file within `archive.org/download`, with any credentials and cookies.
+    fn download_request(&self, method: &str, url: &str) -> ureq::Request {
+        let mut req = self.inner.client.request(method, url)
+            .set("user-agent", &self.inner.useragent);
+        
+        if let Some(creds) = self.inner.credentials.as_ref() {
             req = req.set_header(creds.into());
         }
+        if let Some(cookies) = self.inner.cookies.as_ref() {
+            req = req.set_header(cookies.into());
+        }
         
-        let resp = req.call()?;
+        req
+    }
+    
+    fn is_authenticated(&self) -> bool {
+        self.inner.credentials.is_some() || self.inner.cookies.is_some()
+    }
+    
+    /// Retrieves the size, modification time, and ETag of a file, without downloading its contents.
+    /// 
+    /// This is a cheap way to check whether a file exists, or whether it has changed since it was
+    /// last downloaded.
+    /// 
+    /// # Errors
+    /// If the file does not exist, an [`ItemError::NotFound`] is returned. Possibly returns [`ItemError::Ureq`]
+    /// if a [`ureq::Error`] is encountered while performing the request.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use iars::Item;
+    /// 
+    /// let head = Item::new("test_item")?.head_file("path/to/archived/file.txt")?;
+    /// println!("{:?} bytes, etag {:?}", head.size, head.etag);
+    /// # Ok::<(), iars::ItemError>(())
+    /// ```
+    pub fn head_file(&self, filepath: &str) -> Result<FileHead, ItemError> {
+        let url = format!("https://archive.org/download/{}/{filepath}", self.inner.identifier);
+        let context = self.context("head_file", &url);
+        let req = self.download_request("HEAD", &url);
         
-        Ok(std::io::copy(&mut resp.into_reader(), &mut writer)?)
+        let resp = match self.is_authenticated() {
+            true => self.inner.client.call_authenticated(req),
+            false => self.inner.client.call(req),
+        }.map_err(|err| ItemError::ureq(&context, err))?;
+        
+        Ok(FileHead {
+            size: resp.header("content-length").and_then(|len| len.parse().ok()),
+            last_modified: resp.header("last-modified").and_then(client::parse_http_date),
+            etag: resp.header("etag").map(|etag| etag.trim_matches('"').to_string()),
+        })
+    }
+    
+    /// Downloads the item's BitTorrent file (`{identifier}_archive.torrent`) into the `writer`.
+    /// 
+    /// Use [`MetadataResponse::torrent`] to check whether the item has a torrent beforehand.
+    /// 
+    /// # Errors
+    /// If the item has no torrent, an [`ItemError::NotFound`] is returned. See [`Item::download_file`]
+    /// for other errors.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use std::fs::File;
+    /// use iars::Item;
+    /// 
+    /// let item = Item::new("test_item")?;
+    /// 
+    /// # #[cfg(feature = "json")]
+    /// if item.metadata()?.torrent().is_some() {
+    ///     item.download_torrent(File::create("test_item.torrent")?)?;
+    /// }
+    /// # Ok::<(), iars::ItemError>(())
+    /// ```
+    pub fn download_torrent(&self, writer: impl Write) -> Result<u64, ItemError> {
+        self.download_file(&format!("{}_archive.torrent", self.inner.identifier), writer)
+    }
+    
+    /// Downloads many files from this item into a local directory, one after another.
+    /// 
+    /// Each file is written to the same relative path within `dest_dir`, using [`Item::download_file_to_path`].
+    /// All downloads are performed by this item's [`Client`], whose connections are kept alive and reused
+    /// between files, avoiding the cost of a new connection (and TLS handshake) for each small file.
+    /// 
+    /// A failed download does not stop the remaining files from being downloaded. The result of each
+    /// download is returned in the same order as `paths`. A path which can't be safely placed within
+    /// `dest_dir` (e.g. one containing a `..` component) fails with an [`ItemError::InvalidPath`],
+    /// without being downloaded.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use std::path::Path;
+    /// use iars::Item;
+    /// 
+    /// let item = Item::new("test_book")?;
+    /// 
+    /// let pages: Vec<String> = (1..=300).map(|n| format!("scans/page_{n:04}.jpg")).collect();
+    /// for (path, result) in item.download_files(&pages, Path::new("test_book")) {
+    ///     if let Err(err) = result {
+    ///         eprintln!("{path}: {err}");
+    ///     }
+    /// }
+    /// # Ok::<(), iars::ItemError>(())
+    /// ```
+    pub fn download_files<S: AsRef<str>>(&self, paths: &[S], dest_dir: &Path) -> Vec<(String, Result<u64, ItemError>)> {
+        paths.iter().map(|path| {
+            let path = path.as_ref().trim_start_matches('/');
+            let result = local_path(dest_dir, path).and_then(|dest| self.download_file_to_path(path, &dest));
+            
+            (path.to_string(), result)
+        }).collect()
+    }
+    
+    /// Downloads a file from this item directly to a local path.
+    /// 
+    /// Any missing parent directories of `dest` are created. The data is first written to a uniquely named
+    /// temporary file in the same directory, which is flushed to disk and then renamed to `dest` once the
+    /// download has finished. As a result, `dest` will never contain a partially downloaded file, even if
+    /// several downloads to it run at once; if the download fails, any existing file at `dest` is left untouched.
+    /// 
+    /// If [enabled][`Item::with_preserve_mtime`], the modification time of the local file is set to that
+    /// of the remote file.
+    /// 
+    /// On success, the number of bytes written (size of the file) is returned.
+    /// 
+    /// # Errors
+    /// See [`Item::download_file`]. Errors while creating, writing, or renaming the local file are
+    /// returned as an [`ItemError::Io`].
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use std::path::Path;
+    /// use iars::Item;
+    /// 
+    /// let item = Item::new("test_item")?;
+    /// 
+    /// item.download_file_to_path("path/to/archived/file.txt", Path::new("mirror/test_item/path/to/archived/file.txt"))?;
+    /// # Ok::<(), iars::ItemError>(())
+    /// ```
+    pub fn download_file_to_path(&self, filepath: &str, dest: &Path) -> Result<u64, ItemError> {
+        let dir = dest.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        fs::create_dir_all(dir)?;
+        
+        // each download gets its own partial file, so concurrent downloads to the same path can't corrupt each other
+        let prefix = format!(".{}", dest.file_name().unwrap_or_default().to_string_lossy());
+        let (tmp, mut file) = client::create_temp_file_in(dir, &prefix, ".iars-part")?;
+        
+        let result = (|| {
+            let (len, last_modified) = self.download(filepath, &mut file)?;
+            
+            if self.inner.preserve_mtime {
+                #[cfg(feature = "json")]
+                let last_modified = match last_modified {
+                    Some(mtime) => Some(mtime),
+                    None => self.metadata()?.file(filepath).and_then(|file| file.mtime),
+                };
+                
+                if let Some(mtime) = last_modified.and_then(|mtime| u64::try_from(mtime).ok()) {
+                    file.set_modified(UNIX_EPOCH + Duration::from_secs(mtime))?;
+                }
+            }
+            
+            file.sync_all()?;
+            fs::rename(&tmp, dest)?;
+            
+            Ok(len)
+        })();
+        drop(file);
+        
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+        
+        result
     }
     
     /// Retrieves the item's metadata.
     /// 
     /// Any recent changes submitted via the Metadata API will be present in the response, even if
     /// the changes have not been written to disk yet.
+    /// 
+    /// Darked items are included only if the item's [credentials][`Item::with_credentials`] have access
+    /// to them.
+    /// 
+    /// # Errors
+    /// If the item does not exist, an [`ItemError::NotFound`] is returned. If the item is darked and
+    /// access is denied, an [`ItemError::Dark`] is returned.
+    #[cfg(feature = "json")]
     pub fn metadata(&self) -> Result<MetadataResponse, ItemError> {
-        let mut req = ureq::get(&format!("https://archive.org/metadata/{}", self.identifier))
-            .set("user-agent", &self.useragent);
+        let (req, context) = self.metadata_request();
+        let resp = self.inner.client.call(req).map_err(|err| ItemError::ureq(&context, err))?;
+        let body = client::spool(resp, self.inner.spool_threshold).map_err(|err| ItemError::io(&context, err))?;
+        
+        // parsed directly from the body, as large records would otherwise be held twice while converted from a `serde_json::Value`
+        let record: MetadataResponse = client::read_json(body).map_err(|err| ItemError::io(&context, err))?;
+        if record.created.is_none() && record.metadata.is_empty() && record.files.is_empty() && !record.is_dark {
+            return Err(ItemError::NotFound { context });
+        }
+        
+        // without access, the record of a darked item only includes a few catalog fields
+        if record.is_dark && record.metadata.is_empty() {
+            return Err(ItemError::Dark { context });
+        }
+        
+        Ok(record)
+    }
+    
+    /// Retrieves the item's metadata record as unparsed JSON, along with the context of the request.
+    #[cfg(feature = "json")]
+    fn metadata_json(&self) -> Result<(serde_json::Value, RequestContext), ItemError> {
+        let (req, context) = self.metadata_request();
+        let resp = self.inner.client.call(req).map_err(|err| ItemError::ureq(&context, err))?;
+        let body = client::spool(resp, self.inner.spool_threshold).map_err(|err| ItemError::io(&context, err))?;
+        
+        let value: serde_json::Value = client::read_json(body).map_err(|err| ItemError::io(&context, err))?;
+        if value.as_object().is_some_and(|obj| obj.is_empty()) {
+            return Err(ItemError::NotFound { context });
+        }
+        
+        // without access, the record of a darked item only includes a few catalog fields
+        if value_to_bool(&value["is_dark"]) && value.get("metadata").is_none() {
+            return Err(ItemError::Dark { context });
+        }
+        
+        Ok((value, context))
+    }
+    
+    /// Performs the same request as [`Item::metadata`], but returns the [undecoded response][`RawResponse`].
+    /// 
+    /// Unlike [`Item::metadata`], missing and darked items are not detected. The Metadata API responds
+    /// to them with an empty (or mostly empty) JSON object.
+    /// 
+    /// # Errors
+    /// Possibly returns [`ItemError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    pub fn metadata_raw(&self) -> Result<RawResponse, ItemError> {
+        let (req, context) = self.metadata_request();
+        let resp = self.inner.client.call(req).map_err(|err| ItemError::ureq(&context, err))?;
+        
+        RawResponse::read(resp).map_err(|err| ItemError::io(&context, err))
+    }
+    
+    fn metadata_request(&self) -> (ureq::Request, RequestContext) {
+        let url = format!("https://archive.org/metadata/{}", self.inner.identifier);
+        let context = self.context("metadata", &url);
+        let mut req = self.inner.client.request("GET", &url)
+            .set("user-agent", &self.inner.useragent)
+            .query_pairs(self.inner.metadata_params.iter().map(|(key, val)| (key.as_str(), val.as_str())));
         
-        if let Some(creds) = self.credentials.as_ref() {
+        if let Some(creds) = self.inner.credentials.as_ref() {
             req = req.set_header(creds.into());
         }
         
-        let resp = req.call()?;
+        (req, context)
+    }
+    
+    /// Writes the item's metadata to files within a local directory, for storing alongside mirrored content.
+    /// 
+    /// Three files are written, named after the item's identifier:
+    /// * `{identifier}_metadata.json`: the full [metadata record][`Item::metadata`], as returned by the Metadata API.
+    /// * `{identifier}_meta.xml`: the item's metadata, in the form stored within the item.
+    /// * `{identifier}_files.xml`: the item's list of files, in the form stored within the item.
+    /// 
+    /// The XML files are downloaded from the item itself, so they may not reflect very recent changes
+    /// which are already present in the JSON record. The directory is created if it does not exist.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use std::path::Path;
+    /// use iars::Item;
+    /// 
+    /// Item::new("test_item")?.export_metadata(Path::new("mirror/test_item"))?;
+    /// # Ok::<(), iars::ItemError>(())
+    /// ```
+    /// 
+    /// # Errors
+    /// See [`Item::metadata`] and [`Item::download_file_to_path`].
+    #[cfg(feature = "json")]
+    pub fn export_metadata(&self, dir: &Path) -> Result<(), ItemError> {
+        let identifier = &self.inner.identifier;
+        let (value, _) = self.metadata_json()?;
         
-        const MAX_LEN: usize = 1 * 1024 * 1024 * 1024; // 1 GiB
-        let len: usize = resp
-            .header("content-length")
-            .unwrap_or("")
-            .parse()
-            .unwrap_or(MAX_LEN);
+        fs::create_dir_all(dir)?;
+        let json = serde_json::to_vec_pretty(&value).map_err(std::io::Error::from)?;
+        fs::write(dir.join(format!("{identifier}_metadata.json")), json)?;
         
-        if len > MAX_LEN {
-            todo!("Response body is over size limit of {MAX_LEN} bytes!");
+        for suffix in ["_meta.xml", "_files.xml"] {
+            let name = format!("{identifier}{suffix}");
+            self.download_file_to_path(&name, &dir.join(&name))?;
         }
         
-        Ok(resp.into_json()?)
+        Ok(())
+    }
+    
+    /// Retrieves the item's [view statistics][`crate::views`].
+    /// 
+    /// # Errors
+    /// Possibly returns [`ItemError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    #[cfg(feature = "json")]
+    pub fn views(&self) -> Result<views::Summary, ItemError> {
+        let context = self.context("views", &format!("https://be-api.us.archive.org/views/v1/short/{}", self.inner.identifier));
+        let mut summaries = views::Request::new()
+            .with_credentials(self.inner.credentials.clone())
+            .with_useragent(Some(self.inner.useragent.clone()))
+            .with_client(self.inner.client.clone())
+            .with_identifier(&self.inner.identifier)
+            .call()
+            .map_err(|err| ItemError::views(&context, err))?;
+        
+        Ok(summaries.remove(&self.inner.identifier).unwrap_or_default())
+    }
+    
+    /// Retrieves the item's detailed, per-day [view statistics][`crate::views::Detail`].
+    /// 
+    /// Returns the dates of each per-day entry, along with the statistics themselves.
+    /// 
+    /// # Errors
+    /// Possibly returns [`ItemError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    #[cfg(feature = "json")]
+    pub fn views_detail(&self) -> Result<(Vec<String>, views::Detail), ItemError> {
+        let context = self.context("views_detail", &format!("https://be-api.us.archive.org/views/v1/long/{}", self.inner.identifier));
+        let mut resp = views::Request::new()
+            .with_credentials(self.inner.credentials.clone())
+            .with_useragent(Some(self.inner.useragent.clone()))
+            .with_client(self.inner.client.clone())
+            .with_identifier(&self.inner.identifier)
+            .call_detail()
+            .map_err(|err| ItemError::views(&context, err))?;
+        
+        Ok((resp.days, resp.items.remove(&self.inner.identifier).unwrap_or_default()))
+    }
+    
+    /// Retrieves all reviews of this item.
+    /// 
+    /// Only the reviews portion of the item's metadata record is requested, making this cheaper
+    /// than calling [`Item::metadata`].
+    /// 
+    /// # Errors
+    /// Possibly returns [`ItemError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    /// 
+    /// If any [I/O errors][`std::io::Error`] occur or the response fails to be deserialized, an [`ItemError::Io`] is returned.
+    #[cfg(feature = "json")]
+    pub fn reviews(&self) -> Result<Vec<Review>, ItemError> {
+        #[derive(Deserialize)]
+        struct ReviewsResponse {
+            #[serde(default)]
+            result: Vec<Review>,
+        }
+        
+        let url = format!("https://archive.org/metadata/{}/reviews", self.inner.identifier);
+        let context = self.context("reviews", &url);
+        let mut req = self.inner.client.request("GET", &url)
+            .set("user-agent", &self.inner.useragent);
+        
+        if let Some(creds) = self.inner.credentials.as_ref() {
+            req = req.set_header(creds.into());
+        }
+        
+        let resp: ReviewsResponse = self.inner.client.call(req)
+            .map_err(|err| ItemError::ureq(&context, err))?
+            .into_json()
+            .map_err(|err| ItemError::io(&context, err))?;
+        
+        Ok(resp.result)
+    }
+    
+    /// Retrieves the review of this item written by the authenticated user, if they have written one.
+    /// 
+    /// This uses the [Reviews API](https://archive.org/developers/reviews.html), which only returns
+    /// the caller's own review, so [credentials][`Item::with_credentials`] are required.
+    /// 
+    /// # Errors
+    /// If no credentials were provided, or they are invalid, an [`ItemError::Forbidden`] is likely returned.
+    /// If the Reviews API refuses the request, an [`ItemError::Rejected`] is returned. Possibly returns
+    /// [`ItemError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    /// 
+    /// If any [I/O errors][`std::io::Error`] occur or the response fails to be deserialized, an [`ItemError::Io`] is returned.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use iars::{Credentials, Item};
+    /// 
+    /// let item = Item::new("test_item")?
+    ///     .with_credentials(Some(Credentials::new("access", "secret")));
+    /// 
+    /// match item.own_review()? {
+    ///     Some(review) => println!("already reviewed: {}", review.title),
+    ///     None => println!("not reviewed yet"),
+    /// }
+    /// # Ok::<(), iars::ItemError>(())
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn own_review(&self) -> Result<Option<Review>, ItemError> {
+        #[derive(Deserialize)]
+        struct OwnReviewResponse {
+            #[serde(default)]
+            success: bool,
+            value: Option<Review>,
+            error: Option<String>,
+        }
+        
+        let url = "https://archive.org/services/reviews.php";
+        let context = self.context("own_review", url);
+        let mut req = self.inner.client.request("GET", url)
+            .set("user-agent", &self.inner.useragent)
+            .query("identifier", &self.inner.identifier);
+        
+        if let Some(creds) = self.inner.credentials.as_ref() {
+            req = req.set_header(creds.into());
+        }
+        
+        let resp = match self.inner.client.call(req) {
+            Ok(resp) => resp,
+            Err(ureq::Error::Status(404, _)) => return Ok(None),
+            Err(err) => return Err(ItemError::ureq(&context, err)),
+        };
+        let resp: OwnReviewResponse = resp.into_json().map_err(|err| ItemError::io(&context, err))?;
+        
+        match resp.success {
+            true => Ok(resp.value),
+            false => Err(ItemError::Rejected { context, message: resp.error.unwrap_or_default() }),
+        }
+    }
+    
+    /// Retrieves the metadata of a single file within this item.
+    /// 
+    /// Only the files portion of the item's metadata record is requested, making this cheaper than
+    /// calling [`Item::metadata`].
+    /// 
+    /// # Errors
+    /// If the file does not exist, an [`ItemError::NotFound`] is returned. Possibly returns [`ItemError::Ureq`]
+    /// if a [`ureq::Error`] is encountered while performing the request.
+    /// 
+    /// If any [I/O errors][`std::io::Error`] occur or the response fails to be deserialized, an [`ItemError::Io`] is returned.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use iars::Item;
+    /// 
+    /// let file = Item::new("test_item")?.file_metadata("path/to/file.txt")?;
+    /// println!("md5: {:?}", file.md5);
+    /// # Ok::<(), iars::ItemError>(())
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn file_metadata(&self, filepath: &str) -> Result<FileMetadata, ItemError> {
+        #[derive(Deserialize)]
+        struct FilesResponse {
+            #[serde(default)]
+            result: Vec<FileMetadata>,
+        }
+        
+        let url = format!("https://archive.org/metadata/{}/files", self.inner.identifier);
+        let context = self.context("file_metadata", &url);
+        let mut req = self.inner.client.request("GET", &url)
+            .set("user-agent", &self.inner.useragent);
+        
+        if let Some(creds) = self.inner.credentials.as_ref() {
+            req = req.set_header(creds.into());
+        }
+        
+        let resp: FilesResponse = self.inner.client.call(req)
+            .map_err(|err| ItemError::ureq(&context, err))?
+            .into_json()
+            .map_err(|err| ItemError::io(&context, err))?;
+        
+        let filepath = filepath.trim_start_matches('/');
+        resp.result.into_iter()
+            .find(|file| file.name == filepath)
+            .ok_or(ItemError::NotFound { context })
+    }
+    
+    /// Retrieves items recommended as related to this item, ordered from most to least relevant.
+    /// 
+    /// # Errors
+    /// Possibly returns [`ItemError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    /// 
+    /// If any [I/O errors][`std::io::Error`] occur or the response fails to be deserialized, an [`ItemError::Io`] is returned.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use iars::Item;
+    /// 
+    /// for related in Item::new("test_item")?.related()? {
+    ///     println!("{} ({:.2}): {:?}", related.identifier, related.score, related.title());
+    /// }
+    /// # Ok::<(), iars::ItemError>(())
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn related(&self) -> Result<Vec<RelatedItem>, ItemError> {
+        let url = format!("https://be-api.us.archive.org/mds/v1/get_related/all/{}", self.inner.identifier);
+        let context = self.context("related", &url);
+        let mut req = self.inner.client.request("GET", &url)
+            .set("user-agent", &self.inner.useragent);
+        
+        if let Some(creds) = self.inner.credentials.as_ref() {
+            req = req.set_header(creds.into());
+        }
+        
+        let resp: RelatedResponse = self.inner.client.call(req)
+            .map_err(|err| ItemError::ureq(&context, err))?
+            .into_json()
+            .map_err(|err| ItemError::io(&context, err))?;
+        
+        let mut items = resp.hits.hits;
+        items.sort_by(|a, b| b.score.total_cmp(&a.score));
+        
+        Ok(items)
+    }
+    
+    /// Creates a task [search request][`tasks::search::Request`] for this item.
+    /// 
+    /// The request is pre-filtered to this item's identifier, and uses the same credentials and
+    /// User-Agent as this item. Additional filters and options may be added before calling it.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use iars::Item;
+    /// 
+    /// let item = Item::new("test_item").unwrap();
+    /// 
+    /// let resp = item.tasks().with_categories(true, true, false).call(None)?;
+    /// for task in resp.catalog {
+    ///     println!("{} is {:?}", task.cmd, task.status);
+    /// }
+    /// # Ok::<(), iars::tasks::TaskError>(())
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn tasks(&self) -> tasks::search::Request {
+        tasks::search()
+            .with_credentials(self.inner.credentials.clone())
+            .with_useragent(Some(self.inner.useragent.clone()))
+            .with_client(self.inner.client.clone())
+            .with_filter(Filter::Identifier(self.inner.identifier.clone()))
+    }
+}
+impl From<Identifier> for Item {
+    fn from(value: Identifier) -> Self {
+        Self {
+            inner: Arc::new(ItemInner::new(value.into_string())),
+        }
+    }
+}
+impl TryFrom<&str> for Item {
+    type Error = ItemError;
+    
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+impl FromStr for Item {
+    type Err = ItemError;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn encodes_copy_source() {
+        assert_eq!(copy_source("old_item", "/videos/talk #1 (50%).mp4"), "/old_item/videos/talk%20%231%20%2850%25%29.mp4");
+        assert_eq!(copy_source("old_item", "caf\u{e9}/a+b?.txt"), "/old_item/caf%C3%A9/a%2Bb%3F.txt");
+    }
+    
+    #[test]
+    fn joins_local_paths() {
+        let root = Path::new("mirror");
+        
+        assert_eq!(local_path(root, "scans/page 1.png").unwrap(), root.join("scans").join("page 1.png"));
+        assert_eq!(local_path(root, "notes..txt").unwrap(), root.join("notes..txt"));
+    }
+    
+    #[test]
+    fn rejects_escaping_local_paths() {
+        let root = Path::new("mirror");
+        
+        for name in ["../outside.txt", "scans/../../outside.txt", "/etc/passwd", "scans//page.png", "./page.png", "scans/", ""] {
+            assert!(matches!(local_path(root, name), Err(ItemError::InvalidPath { .. })), "{name:?} was accepted");
+        }
+    }
+    
+    #[cfg(feature = "json")]
+    #[test]
+    fn parses_lenient_bools() {
+        use serde_json::json;
+        
+        for value in [json!(true), json!("true"), json!(" True "), json!("1"), json!("yes"), json!(1), json!(0.5)] {
+            assert!(value_to_bool(&value), "{value}");
+        }
+        for value in [json!(false), json!("false"), json!("0"), json!(""), json!(0), json!(null), json!([true])] {
+            assert!(!value_to_bool(&value), "{value}");
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,397 @@
+//! Import and export of items as [BagIt](https://datatracker.ietf.org/doc/html/rfc8493) bags.
+//! 
+//! A bag is a directory containing a `data/` payload directory, along with tag files describing it:
+//! `bagit.txt` declares the bag, `bag-info.txt` holds metadata about the bag, and payload manifests
+//! (e.g. `manifest-md5.txt`) list the checksum of every payload file.
+//! 
+//! # Uploading
+//! [`Item::upload_bag`] verifies a bag's payload against its MD5 manifest, then uploads each payload
+//! file (without the `data/` prefix) to the item. Fields of `bag-info.txt` are used as the item's
+//! [initial metadata][`Bag::metadata`] if the item is created by the upload. Once uploaded, the
+//! Internet Archive records its own MD5 checksum of every file, which can be compared against the
+//! bag's manifest later using [`Bag::verify_item`].
+//! 
+//! # Exporting
+//! [`Item::export_bag`] downloads every original file of an item into the payload of a new bag,
+//! verifies each file against the item's checksums, and writes `bagit.txt`, `bag-info.txt`,
+//! `manifest-md5.txt`, and `tagmanifest-md5.txt`.
+//! 
+//! # Example
+//! ```rust,no_run
+//! use std::path::Path;
+//! use iars::{Credentials, Item};
+//! use iars::item::bagit::Bag;
+//! 
+//! let item = Item::new("my_new_item")?
+//!     .with_credentials(Some(Credentials::new("abcdefghijklmnop", "1234567890123456")));
+//! 
+//! let bag = Bag::open(Path::new("bags/accession_042"))?;
+//! item.upload_bag(&bag)?;
+//! 
+//! Item::new("other_item")?.export_bag(Path::new("bags/other_item"))?;
+//! # Ok::<(), iars::ItemError>(())
+//! ```
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::client::civil_from_days;
+use crate::item::{local_path, FileMetadata, Item, ItemError};
+use crate::item::sync::{md5_file, normalize_path, Mismatch, Verification};
+
+/// Fields of `bag-info.txt` which correspond to a standard item metadata field.
+const FIELD_MAP: &[(&str, &str)] = &[
+    ("External-Description", "description"),
+    ("External-Identifier", "external-identifier"),
+    ("Source-Organization", "contributor"),
+];
+
+/// A BagIt bag stored in a local directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bag {
+    root: PathBuf,
+    info: Vec<(String, String)>,
+    manifest: Vec<(String, String)>,
+}
+impl Bag {
+    /// Opens the bag at `root`, reading its `bag-info.txt` (if present) and `manifest-md5.txt`.
+    /// 
+    /// # Errors
+    /// If `bagit.txt` or `manifest-md5.txt` is missing, or a tag file is malformed, an error is returned.
+    /// Bags which only have manifests using other algorithms (e.g. SHA-256) are not supported.
+    pub fn open(root: &Path) -> io::Result<Self> {
+        let declaration = fs::read_to_string(root.join("bagit.txt"))?;
+        if !declaration.starts_with("BagIt-Version:") {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "bagit.txt is not a bag declaration"));
+        }
+        
+        let info = match fs::read_to_string(root.join("bag-info.txt")) {
+            Ok(text) => parse_info(&text),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => vec![],
+            Err(err) => return Err(err),
+        };
+        
+        let manifest = fs::read_to_string(root.join("manifest-md5.txt"))?.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let (md5, path) = line.split_once(char::is_whitespace)
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("malformed manifest line: {line}")))?;
+                let path = decode_path(path.trim_start());
+                let path = path.strip_prefix("data/")
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("manifest path outside of payload: {path}")))?;
+                
+                Ok((path.to_string(), md5.to_ascii_lowercase()))
+            })
+            .collect::<io::Result<_>>()?;
+        
+        Ok(Self {
+            root: root.to_path_buf(),
+            info,
+            manifest,
+        })
+    }
+    
+    /// Returns the directory the bag is stored in.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+    
+    /// Returns the fields of `bag-info.txt`, in order. Fields may be repeated.
+    pub fn info(&self) -> &[(String, String)] {
+        &self.info
+    }
+    
+    /// Returns the path (relative to `data/`) and MD5 checksum of every payload file.
+    pub fn manifest(&self) -> &[(String, String)] {
+        &self.manifest
+    }
+    
+    /// Converts the fields of `bag-info.txt` into item metadata.
+    /// 
+    /// `External-Description`, `External-Identifier`, and `Source-Organization` become `description`,
+    /// `external-identifier`, and `contributor` respectively. Every other field is prefixed with `bagit-`
+    /// and lowercased (e.g. `Bagging-Date` becomes `bagit-bagging-date`). Values of repeated fields
+    /// are joined with `; `.
+    pub fn metadata(&self) -> Vec<(String, String)> {
+        let mut meta: Vec<(String, String)> = vec![];
+        for (label, value) in &self.info {
+            let key = match FIELD_MAP.iter().find(|(field, _)| field.eq_ignore_ascii_case(label)) {
+                Some((_, key)) => key.to_string(),
+                None => format!("bagit-{}", label.to_ascii_lowercase().replace(|c: char| !c.is_ascii_alphanumeric(), "-")),
+            };
+            
+            match meta.iter_mut().find(|(existing, _)| *existing == key) {
+                Some((_, existing)) => *existing = format!("{existing}; {value}"),
+                None => meta.push((key, value.clone())),
+            }
+        }
+        
+        meta
+    }
+    
+    /// Checks the bag's payload files against its manifest.
+    /// 
+    /// Files in `data/` which aren't listed in the manifest are reported as [unexpected][`Verification::unexpected`].
+    /// 
+    /// # Errors
+    /// Returns an error if a payload file exists, but cannot be read, or if a path in the manifest could
+    /// refer to a file outside of `data/` (e.g. it contains a `..` component).
+    pub fn verify(&self) -> io::Result<Verification> {
+        let payload = self.root.join("data");
+        let mut verification = Verification::default();
+        
+        for (path, md5) in &self.manifest {
+            let file = local_path(&payload, path).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+            let size = match fs::metadata(&file) {
+                Ok(meta) => meta.len(),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                    verification.missing.push(path.clone());
+                    continue;
+                },
+                Err(err) => return Err(err),
+            };
+            
+            let actual = md5_file(&file)?;
+            match actual == *md5 {
+                true => verification.verified.push(path.clone()),
+                false => verification.mismatched.push(Mismatch {
+                    name: path.clone(),
+                    expected_size: None,
+                    actual_size: size,
+                    expected_md5: Some(md5.clone()),
+                    actual_md5: actual,
+                }),
+            }
+        }
+        
+        let listed: HashSet<&str> = self.manifest.iter().map(|(path, _)| path.as_str()).collect();
+        verification.unexpected = payload_files(&payload)?.into_iter()
+            .filter(|path| !listed.contains(path.as_str()))
+            .collect();
+        
+        Ok(verification)
+    }
+    
+    /// Checks the files of an item against the bag's manifest, using the checksums recorded by the
+    /// Internet Archive.
+    /// 
+    /// The "actual" values of each [`Mismatch`] are those of the item. Original files of the item
+    /// which aren't listed in the manifest are reported as [unexpected][`Verification::unexpected`].
+    pub fn verify_item(&self, files: &[FileMetadata]) -> Verification {
+        let mut verification = Verification::default();
+        
+        for (path, md5) in &self.manifest {
+            match files.iter().find(|file| file.name == *path) {
+                None => verification.missing.push(path.clone()),
+                Some(file) if file.md5.as_ref().is_some_and(|actual| actual.eq_ignore_ascii_case(md5)) => verification.verified.push(path.clone()),
+                Some(file) => verification.mismatched.push(Mismatch {
+                    name: path.clone(),
+                    expected_size: None,
+                    actual_size: file.size.unwrap_or_default(),
+                    expected_md5: Some(md5.clone()),
+                    actual_md5: file.md5.clone().unwrap_or_default(),
+                }),
+            }
+        }
+        
+        let listed: HashSet<&str> = self.manifest.iter().map(|(path, _)| path.as_str()).collect();
+        verification.unexpected = files.iter()
+            .filter(|file| is_payload(file) && !listed.contains(file.name.as_str()))
+            .map(|file| file.name.clone())
+            .collect();
+        
+        verification
+    }
+}
+
+/// Parses the `Label: Value` lines of `bag-info.txt`. Lines starting with whitespace continue the previous value.
+fn parse_info(text: &str) -> Vec<(String, String)> {
+    let mut info: Vec<(String, String)> = vec![];
+    for line in text.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = info.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((label, value)) = line.split_once(':') {
+            info.push((label.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    
+    info
+}
+
+/// Percent-encodes the characters which may not appear literally in a manifest path.
+fn encode_path(path: &str) -> String {
+    path.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// Reverses [`encode_path`].
+fn decode_path(path: &str) -> String {
+    path.replace("%0D", "\r").replace("%0d", "\r").replace("%0A", "\n").replace("%0a", "\n").replace("%25", "%")
+}
+
+/// Returns true if a file of an item belongs in a bag's payload: an original file which isn't one of
+/// the metadata files maintained by the Internet Archive.
+fn is_payload(file: &FileMetadata) -> bool {
+    file.is_original() && file.format.as_deref() != Some("Metadata")
+}
+
+/// Lists every file within a payload directory, as paths relative to it using `/` separators.
+fn payload_files(root: &Path) -> io::Result<Vec<String>> {
+    fn walk(dir: &Path, prefix: &str, out: &mut Vec<String>) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = format!("{prefix}{}", entry.file_name().to_string_lossy());
+            match entry.file_type()?.is_dir() {
+                true => walk(&entry.path(), &format!("{name}/"), out)?,
+                false => out.push(name),
+            }
+        }
+        
+        Ok(())
+    }
+    
+    let mut files = vec![];
+    if root.is_dir() {
+        walk(root, "", &mut files)?;
+    }
+    
+    Ok(files)
+}
+
+impl Item {
+    /// Uploads the payload of a [`Bag`] to this item.
+    /// 
+    /// The payload is [verified][`Bag::verify`] before anything is uploaded. Each file is uploaded to
+    /// its path within `data/`, and a derive is queued after the last file. If the item is created by
+    /// the upload, the bag's [metadata][`Bag::metadata`] is used as its initial metadata.
+    /// 
+    /// On success, returns the paths of the uploaded files.
+    /// 
+    /// # Errors
+    /// If the payload doesn't match the bag's manifest, an [`ItemError::Io`] is returned before anything
+    /// is uploaded. Manifest paths are [normalized][`normalize_path`], and if any can't be represented,
+    /// an [`ItemError::InvalidPath`] is returned before anything is uploaded. See [`Item::upload_file`] for other errors.
+    pub fn upload_bag(&self, bag: &Bag) -> Result<Vec<String>, ItemError> {
+        let verification = bag.verify()?;
+        if !verification.is_ok() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!(
+                "bag payload does not match its manifest ({} mismatched, {} missing)",
+                verification.mismatched.len(),
+                verification.missing.len(),
+            )).into());
+        }
+        
+        let meta = bag.metadata();
+        let meta: Vec<(&str, &str)> = meta.iter().map(|(key, value)| (key.as_str(), value.as_str())).collect();
+        
+        let payload = bag.root.join("data");
+        let mut uploaded = vec![];
+        let names = bag.manifest.iter()
+            .map(|(path, _)| normalize_path(Path::new(path)))
+            .collect::<Result<Vec<_>, _>>()?;
+        for (i, ((path, _), name)) in bag.manifest.iter().zip(names).enumerate() {
+            let file = fs::File::open(local_path(&payload, path)?)?;
+            let size = file.metadata()?.len() as usize;
+            self.upload_file(i + 1 == bag.manifest.len(), &meta, &name, file, size)?;
+            uploaded.push(name);
+        }
+        
+        Ok(uploaded)
+    }
+    
+    /// Exports every original file of this item into a new bag at `dest`.
+    /// 
+    /// Files are downloaded into `dest/data/`, and each is checked against the checksum recorded by the
+    /// Internet Archive. The bag's `bag-info.txt` records the item's identifier, title, and description.
+    /// Metadata files maintained by the Internet Archive (e.g. `_meta.xml`) are not included.
+    /// 
+    /// # Errors
+    /// If a downloaded file doesn't match the item's checksum, an [`ItemError::Io`] is returned. If a file
+    /// name can't be safely placed within `dest/data/`, an [`ItemError::InvalidPath`] is returned. See
+    /// [`Item::metadata`] and [`Item::download_file_to_path`] for other errors.
+    pub fn export_bag(&self, dest: &Path) -> Result<Bag, ItemError> {
+        let record = self.metadata()?;
+        let payload = dest.join("data");
+        fs::create_dir_all(&payload)?;
+        
+        let mut manifest = vec![];
+        let mut oxum = (0, 0);
+        for file in record.files().iter().filter(|file| is_payload(file)) {
+            let path = local_path(&payload, &file.name)?;
+            let size = self.download_file_to_path(&file.name, &path)?;
+            
+            let md5 = md5_file(&path)?;
+            if file.md5.as_ref().is_some_and(|expected| !expected.eq_ignore_ascii_case(&md5)) {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, format!("checksum mismatch after downloading {}", file.name)).into());
+            }
+            
+            manifest.push((file.name.clone(), md5));
+            oxum = (oxum.0 + size, oxum.1 + 1);
+        }
+        
+        let days = SystemTime::now().duration_since(UNIX_EPOCH).map(|now| now.as_secs() as i64 / 86400).unwrap_or_default();
+        let (year, month, day) = civil_from_days(days);
+        
+        let mut info = vec![
+            ("External-Identifier".to_string(), self.inner.identifier.clone()),
+            ("Bagging-Date".to_string(), format!("{year:04}-{month:02}-{day:02}")),
+            ("Payload-Oxum".to_string(), format!("{}.{}", oxum.0, oxum.1)),
+        ];
+        for (key, label) in [("title", "Title"), ("description", "External-Description")] {
+            if let Some(value) = record.metadata().get(key).and_then(|value| value.first()) {
+                info.push((label.to_string(), value.replace(['\r', '\n'], " ")));
+            }
+        }
+        
+        let tags = [
+            ("bagit.txt", "BagIt-Version: 1.0\nTag-File-Character-Encoding: UTF-8\n".to_string()),
+            ("bag-info.txt", info.iter().map(|(label, value)| format!("{label}: {value}\n")).collect()),
+            ("manifest-md5.txt", manifest.iter().map(|(path, md5)| format!("{md5}  data/{}\n", encode_path(path))).collect()),
+        ];
+        
+        let mut tagmanifest = String::new();
+        for (name, contents) in &tags {
+            fs::write(dest.join(name), contents)?;
+            tagmanifest.push_str(&format!("{}  {name}\n", md5_file(&dest.join(name))?));
+        }
+        fs::write(dest.join("tagmanifest-md5.txt"), tagmanifest)?;
+        
+        Ok(Bag {
+            root: dest.to_path_buf(),
+            info,
+            manifest,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn round_trips_paths() {
+        for path in ["scans/page 1.png", "100%.txt", "line\nbreak\r\n.txt", "%0A.txt", "%25%0d.txt", "%\n"] {
+            assert_eq!(decode_path(&encode_path(path)), path);
+        }
+        assert_eq!(encode_path("100%\r\n.txt"), "100%25%0D%0A.txt");
+        assert_eq!(decode_path("a%0d%0ab%25"), "a\r\nb%");
+    }
+    
+    #[test]
+    fn parses_info() {
+        let text = "Source-Organization: Internet Archive\r\nExternal-Description: A long\n  description\n\tof the bag\nBagging-Date: 2024-01-02\nnot a tag\nExternal-Identifier: https://archive.org/details/test_item\nPayload-Oxum: 10.2\n";
+        
+        assert_eq!(parse_info(text), [
+            ("Source-Organization", "Internet Archive"),
+            ("External-Description", "A long description of the bag"),
+            ("Bagging-Date", "2024-01-02"),
+            ("External-Identifier", "https://archive.org/details/test_item"),
+            ("Payload-Oxum", "10.2"),
+        ].map(|(label, value)| (label.to_string(), value.to_string())));
+        assert!(parse_info("  orphaned continuation\n").is_empty());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,185 @@
+//! Fallible construction of an [`Item`], validating its whole configuration at once.
+//! 
+//! [`Item::new`] only validates the identifier, and the `with_*` methods of [`Item`] accept any value.
+//! A [`Builder`] accepts the same options, but [`Builder::build`] checks them together before an item
+//! is created, so configuration mistakes are reported up front, rather than as a confusing error (or
+//! silently ignored option) during a later request.
+//! 
+//! # Example
+//! ```rust
+//! use iars::{Credentials, Item, ItemError};
+//! 
+//! let item = Item::builder("test_item")
+//!     .credentials(Credentials::new("abcdefghijklmnop", "1234567890123456"))
+//!     .overwrite_protection(true)
+//!     .build()?;
+//! 
+//! let err = Item::builder("test_item")
+//!     .auto_make(false)
+//!     .test_collection(true)
+//!     .build();
+//! assert!(matches!(err, Err(ItemError::InvalidConfig { option: "test_collection", .. })));
+//! # Ok::<(), iars::ItemError>(())
+//! ```
+
+use std::sync::Arc;
+use crate::{Client, Credentials, default_user_agent, SessionCookies, validate_identifier};
+use crate::item::{Item, ItemError, ItemInner};
+
+/// Builder for an [`Item`], created using [`Item::builder`]. See the [module docs][`crate::item::builder`].
+/// 
+/// Each option behaves the same as the matching `with_*` method of [`Item`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Builder {
+    inner: ItemInner,
+}
+impl Builder {
+    /// See [`Item::with_credentials`].
+    pub fn credentials(mut self, credentials: Credentials) -> Self {
+        self.inner.credentials = Some(credentials);
+        
+        self
+    }
+    
+    /// See [`Item::with_cookies`].
+    pub fn cookies(mut self, cookies: SessionCookies) -> Self {
+        self.inner.cookies = Some(cookies);
+        
+        self
+    }
+    
+    /// See [`Item::with_useragent`].
+    pub fn useragent(mut self, useragent: Option<String>) -> Self {
+        self.inner.useragent = match useragent {
+            Some(useragent) if !useragent.is_empty() => useragent,
+            _ => default_user_agent(),
+        };
+        
+        self
+    }
+    
+    /// See [`Item::with_client`].
+    pub fn client(mut self, client: Client) -> Self {
+        self.inner.client = client;
+        
+        self
+    }
+    
+    /// See [`Item::with_keep_old_versions`].
+    pub fn keep_old_versions(mut self, keep_old_versions: bool) -> Self {
+        self.inner.keep_old_versions = keep_old_versions;
+        
+        self
+    }
+    
+    /// See [`Item::with_auto_make`].
+    pub fn auto_make(mut self, auto_make_bucket: bool) -> Self {
+        self.inner.auto_make_bucket = auto_make_bucket;
+        
+        self
+    }
+    
+    /// See [`Item::with_test_collection`].
+    pub fn test_collection(mut self, use_test_collection: bool) -> Self {
+        self.inner.use_test_collection = use_test_collection;
+        
+        self
+    }
+    
+    /// See [`Item::with_overwrite_protection`].
+    pub fn overwrite_protection(mut self, overwrite_protection: bool) -> Self {
+        self.inner.overwrite_protection = overwrite_protection;
+        
+        self
+    }
+    
+    /// See [`Item::with_task_guard`].
+    #[cfg(feature = "json")]
+    pub fn task_guard(mut self, max_pending: usize) -> Self {
+        self.inner.task_guard = Some(max_pending);
+        
+        self
+    }
+    
+    /// See [`Item::with_gzip_decompression`].
+    pub fn gzip_decompression(mut self, decompress_gzip: bool) -> Self {
+        self.inner.decompress_gzip = decompress_gzip;
+        
+        self
+    }
+    
+    /// See [`Item::with_preserve_mtime`].
+    pub fn preserve_mtime(mut self, preserve_mtime: bool) -> Self {
+        self.inner.preserve_mtime = preserve_mtime;
+        
+        self
+    }
+    
+    /// See [`Item::with_metadata_param`].
+    pub fn metadata_param(mut self, key: &str, value: &str) -> Self {
+        self.inner.metadata_params.push((key.to_string(), value.to_string()));
+        
+        self
+    }
+    
+    /// See [`Item::with_spool_threshold`].
+    pub fn spool_threshold(mut self, threshold: u64) -> Self {
+        self.inner.spool_threshold = Some(threshold);
+        
+        self
+    }
+    
+    /// Validates the configuration, and creates the [`Item`].
+    /// 
+    /// # Errors
+    /// If [`validate_identifier`] determines the identifier is invalid, an [`ItemError::InvalidIdentifier`]
+    /// is returned. Otherwise, an [`ItemError::InvalidConfig`] is returned for the first of these problems:
+    /// * `credentials`: the access or secret key is empty, or contains whitespace or control characters.
+    /// * `cookies`: a cookie value is empty, or contains whitespace, control characters, or `;`.
+    /// * `test_collection`: [automatic creation][`Builder::auto_make`] is disabled, so the item can never
+    ///   be placed in `test_collection`.
+    /// * `metadata_param`: a parameter key is empty.
+    /// * `spool_threshold`: the threshold is 0, which would spool every response to disk.
+    pub fn build(self) -> Result<Item, ItemError> {
+        let inner = self.inner;
+        if !validate_identifier(&inner.identifier) {
+            return Err(ItemError::InvalidIdentifier(inner.identifier));
+        }
+        
+        let invalid = |option, reason| Err(ItemError::InvalidConfig { option, reason });
+        let is_token = |value: &str| !value.is_empty() && !value.chars().any(|c| c.is_whitespace() || c.is_control());
+        
+        if let Some(creds) = &inner.credentials {
+            if !is_token(&creds.access) || !is_token(&creds.secret) {
+                return invalid("credentials", "access and secret keys must be non-empty, without whitespace or control characters");
+            }
+        }
+        if let Some(cookies) = &inner.cookies {
+            if !is_token(&cookies.logged_in_user) || !is_token(&cookies.logged_in_sig) || cookies.logged_in_user.contains(';') || cookies.logged_in_sig.contains(';') {
+                return invalid("cookies", "cookie values must be non-empty, without whitespace, control characters, or `;`");
+            }
+        }
+        if inner.use_test_collection && !inner.auto_make_bucket {
+            return invalid("test_collection", "items are only placed in test_collection when created automatically, but auto_make is disabled");
+        }
+        if inner.metadata_params.iter().any(|(key, _)| key.is_empty()) {
+            return invalid("metadata_param", "parameter keys must be non-empty");
+        }
+        if inner.spool_threshold == Some(0) {
+            return invalid("spool_threshold", "threshold must be greater than 0");
+        }
+        
+        Ok(Item { inner: Arc::new(inner) })
+    }
+}
+
+impl Item {
+    /// Creates a [`Builder`] for an item with the given identifier.
+    /// 
+    /// Unlike [`Item::new`], nothing is validated until [`Builder::build`] is called.
+    pub fn builder(identifier: &str) -> Builder {
+        Builder {
+            inner: ItemInner::new(identifier.to_string()),
+        }
+    }
+}
\ No newline at end of file
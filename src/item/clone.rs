@@ -0,0 +1,177 @@
+//! Cloning of an item into a new identifier.
+//! 
+//! Identifiers cannot be changed once an item has been created. When an identifier was chosen badly,
+//! the usual fix is to clone the item into a new identifier, and then delete or dark the original.
+//! 
+//! A clone [`Request`] copies every [original][`crate::item::FileMetadata::is_original`] file of the
+//! source item into the destination. The source's metadata is sent as the initial metadata of the
+//! first file, so the new item is created with it (e.g. in the right collection) rather than having
+//! it applied afterwards. Files are copied server-side using [`Item::copy_file_from`] where possible,
+//! and are otherwise downloaded and re-uploaded through a temporary file. Derivatives are not copied;
+//! they are regenerated by a single derive, queued after the last file has been copied.
+//! 
+//! As with [`Item::upload_file`], initial metadata is discarded if the destination item already exists.
+//! 
+//! # Example
+//! ```rust,no_run
+//! use iars::{Credentials, Item};
+//! 
+//! let item = Item::new("badly_named_item")?
+//!     .with_credentials(Some(Credentials::new("abcdefghijklmnop", "1234567890123456")));
+//! 
+//! let report = item.clone_to("well_named_item")
+//!     .call_with_progress(|progress| println!("[{}/{}] {}", progress.index + 1, progress.total, progress.file))?;
+//! 
+//! println!("copied {} files", report.files.len());
+//! # Ok::<(), iars::ItemError>(())
+//! ```
+
+use std::fs;
+use std::io::{Seek, SeekFrom};
+use crate::client;
+use crate::item::{Item, ItemError};
+use crate::item::metadata::Change;
+
+/// Metadata fields which are managed by the Internet Archive, and are never copied.
+const MANAGED_FIELDS: &[&str] = &["identifier", "addeddate", "publicdate", "uploader", "curation", "backup_location", "updatedate", "updater"];
+
+/// Request builder for cloning an item into a new identifier.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    source: Item,
+    dest: String,
+    dry_run: bool,
+}
+impl Request {
+    /// Configures whether or not the clone should only be planned and reported, without copying anything.
+    /// 
+    /// This is false (disabled) by default.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        
+        self
+    }
+    
+    /// Clones the item.
+    /// 
+    /// # Errors
+    /// If the destination identifier is invalid, an [`ItemError::InvalidIdentifier`] is returned before
+    /// anything is copied. Otherwise, the first error encountered is returned. Files which were copied
+    /// before the error are kept.
+    pub fn call(&self) -> Result<Report, ItemError> {
+        self.call_with_progress(|_| {})
+    }
+    
+    /// Clones the item, calling `progress` before each file is copied.
+    /// 
+    /// # Errors
+    /// See [`Request::call`].
+    pub fn call_with_progress(&self, mut progress: impl FnMut(Progress)) -> Result<Report, ItemError> {
+        let dest = Item::new(&self.dest)?
+            .with_credentials(self.source.inner.credentials.clone())
+            .with_useragent(Some(self.source.inner.useragent.clone()))
+            .with_client(self.source.inner.client.clone())
+            .with_auto_make(true);
+        
+        let meta = self.source.metadata()?;
+        let files: Vec<String> = meta.originals().into_iter()
+            // the Internet Archive generates these for the new item itself
+            .filter(|file| !matches!(file.format.as_deref(), Some("Metadata" | "Item Tile" | "Archive BitTorrent")))
+            .map(|file| file.name.clone())
+            .collect();
+        
+        let mut keys: Vec<&String> = meta.metadata().keys().filter(|key| !MANAGED_FIELDS.contains(&key.as_str())).collect();
+        keys.sort();
+        let changes: Vec<Change> = keys.into_iter()
+            .map(|key| Change::Set(key.clone(), meta.metadata()[key].clone()))
+            .collect();
+        
+        let mut report = Report {
+            files: files.clone(),
+            metadata: changes,
+            downloaded: vec![],
+        };
+        if self.dry_run {
+            return Ok(report);
+        }
+        
+        let initial_meta: Vec<(&str, &str)> = report.metadata.iter()
+            .filter_map(|change| match change {
+                Change::Set(key, value) => Some(value.iter().map(move |value| (key.as_str(), value))),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+        
+        for (i, name) in files.iter().enumerate() {
+            progress(Progress { file: name, index: i, total: files.len() });
+            
+            let derive = i + 1 == files.len();
+            let meta = if i == 0 { initial_meta.as_slice() } else { &[] };
+            match dest.copy_file(derive, meta, &self.source.inner.identifier, name, name) {
+                Ok(_) => (),
+                Err(ItemError::NotFound { .. }) | Err(ItemError::Forbidden { .. }) | Err(ItemError::Rejected { .. }) => {
+                    self.transfer(&dest, derive, meta, name)?;
+                    report.downloaded.push(name.clone());
+                },
+                Err(err) => return Err(err),
+            }
+        }
+        
+        Ok(report)
+    }
+    
+    /// Copies a file by downloading it to a temporary file, and uploading it again.
+    fn transfer(&self, dest: &Item, derive: bool, initial_meta: &[(&str, &str)], name: &str) -> Result<(), ItemError> {
+        let (tmp, mut file) = client::create_temp_file("iars-clone")?;
+        
+        let result = (|| {
+            let size = self.source.download_file(name, &mut file)?;
+            file.seek(SeekFrom::Start(0))?;
+            dest.upload_file(derive, initial_meta, name, file, size as usize)?;
+            
+            Ok(())
+        })();
+        
+        let _ = fs::remove_file(&tmp);
+        
+        result
+    }
+}
+
+/// Progress of a clone, provided before each file is copied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress<'a> {
+    /// Path of the file about to be copied.
+    pub file: &'a str,
+    
+    /// Position of the file, starting from 0.
+    pub index: usize,
+    
+    /// Total number of files to copy.
+    pub total: usize,
+}
+
+/// Summary of a clone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    /// Paths of files which were (or, for a dry run, would be) copied.
+    pub files: Vec<String>,
+    
+    /// Metadata which was (or would be) sent as the initial metadata of the new item.
+    pub metadata: Vec<Change>,
+    
+    /// Paths of files which couldn't be copied server-side, and were downloaded and re-uploaded instead.
+    pub downloaded: Vec<String>,
+}
+
+impl Item {
+    /// Creates a [clone request][`Request`], which copies this item's files and metadata into a new item.
+    pub fn clone_to(&self, identifier: &str) -> Request {
+        Request {
+            source: self.clone(),
+            dest: identifier.to_string(),
+            dry_run: false,
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,145 @@
+//! Uploading a file and retrieving one of its derivatives once the derive has finished.
+//! 
+//! Derivatives (e.g. an MP3 of an uploaded WAV, or a text file of an uploaded PDF) are produced by a
+//! derive task, which runs some time after the upload. A derive [`Request`] uploads the file, then
+//! polls the Metadata API and the Tasks API until the requested derivative exists, and downloads it.
+//! 
+//! # Example
+//! ```rust,no_run
+//! use std::fs::File;
+//! use iars::{Credentials, Item};
+//! 
+//! let item = Item::new("test_item")?
+//!     .with_credentials(Some(Credentials::new("abcdefghijklmnop", "1234567890123456")));
+//! 
+//! let report = item.upload_and_derive("recording.wav", "local/recording.wav", "VBR MP3")
+//!     .call(File::create("recording.mp3")?)?;
+//! 
+//! println!("downloaded {} ({} bytes)", report.name, report.size);
+//! # Ok::<(), iars::ItemError>(())
+//! ```
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::item::{Item, ItemError};
+use crate::tasks::Status;
+
+/// Request builder for uploading a file, and downloading one of its derivatives.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    item: Item,
+    filepath: String,
+    local: PathBuf,
+    format: String,
+    interval: Duration,
+    timeout: Option<Duration>,
+}
+impl Request {
+    /// Sets how often the item is checked while waiting for the derivative.
+    /// 
+    /// This is 30 seconds by default.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        
+        self
+    }
+    
+    /// Sets how long to wait for the derivative, or `None` to wait indefinitely.
+    /// 
+    /// This is 2 hours by default. Derives of large files (e.g. long videos or books) can take longer.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        
+        self
+    }
+    
+    /// Uploads the file, waits for the derive to finish, and writes the derivative to `writer`.
+    /// 
+    /// # Errors
+    /// Returns the first error encountered while uploading, checking the item, or downloading. Errors
+    /// from the Tasks API are returned as an [`ItemError::Io`] wrapping the [`TaskError`][`crate::tasks::TaskError`].
+    /// 
+    /// If a derive task of the item fails, or every task has finished without producing the requested
+    /// format, an [`ItemError::Io`] of kind [`io::ErrorKind::Other`] or [`io::ErrorKind::NotFound`]
+    /// (respectively) is returned. If the derivative doesn't exist before the [timeout][`Request::with_timeout`],
+    /// an [`ItemError::Io`] of kind [`io::ErrorKind::TimedOut`] is returned.
+    pub fn call(&self, writer: impl Write) -> Result<Report, ItemError> {
+        let file = File::open(&self.local)?;
+        let size = file.metadata()?.len() as usize;
+        self.item.upload_file(true, &[], &self.filepath, file, size)?;
+        
+        let name = self.wait()?;
+        let size = self.item.download_file(&name, writer)?;
+        
+        Ok(Report { name, size })
+    }
+    
+    /// Waits until the derivative exists, and returns its path.
+    fn wait(&self) -> Result<String, ItemError> {
+        let start = Instant::now();
+        loop {
+            let meta = match self.item.metadata() {
+                Ok(meta) => Some(meta),
+                // the item may not be created until the upload has been processed
+                Err(ItemError::NotFound { .. }) => None,
+                Err(err) => return Err(err),
+            };
+            
+            if let Some(meta) = &meta {
+                let derivatives = meta.derivatives();
+                let found = derivatives.get(self.filepath.as_str())
+                    .and_then(|files| files.iter().find(|file| file.format.as_deref().is_some_and(|format| format.eq_ignore_ascii_case(&self.format))));
+                if let Some(file) = found {
+                    return Ok(file.name.clone());
+                }
+            }
+            
+            let tasks = self.item.tasks()
+                .with_categories(false, true, false)
+                .call(None)
+                .map_err(io::Error::other)?;
+            if let Some(task) = tasks.catalog.iter().find(|task| task.status == Status::Error) {
+                return Err(io::Error::other(format!("{} task {} failed", task.cmd, task.task_id)).into());
+            }
+            if tasks.catalog.is_empty() && meta.is_some_and(|meta| meta.file(&self.filepath).is_some()) {
+                return Err(io::Error::new(io::ErrorKind::NotFound, format!("derive finished without producing {}", self.format)).into());
+            }
+            
+            if self.timeout.is_some_and(|timeout| start.elapsed() + self.interval > timeout) {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, format!("{} was not derived before the timeout", self.format)).into());
+            }
+            
+            thread::sleep(self.interval);
+        }
+    }
+}
+
+/// Summary of a derivative download.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    /// Path of the derivative within the item.
+    pub name: String,
+    
+    /// Number of bytes downloaded.
+    pub size: u64,
+}
+
+impl Item {
+    /// Creates a [request][`Request`] which uploads the local file to `filepath`, then downloads its
+    /// derivative of the given `format` (e.g. "VBR MP3", "h.264", "DjVuTXT").
+    /// 
+    /// Formats are compared case-insensitively against [`FileMetadata::format`][`crate::item::FileMetadata::format`].
+    pub fn upload_and_derive(&self, filepath: &str, local: impl AsRef<Path>, format: &str) -> Request {
+        Request {
+            item: self.clone(),
+            filepath: filepath.to_string(),
+            local: local.as_ref().to_path_buf(),
+            format: format.to_string(),
+            interval: Duration::from_secs(30),
+            timeout: Some(Duration::from_secs(7200)),
+        }
+    }
+}
\ No newline at end of file
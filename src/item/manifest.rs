@@ -0,0 +1,164 @@
+//! Fixity manifests, recording the expected state of an item's uploaded files.
+//! 
+//! A [`Manifest`] lists the path, size, MD5 checksum, and modification time of each file in a local
+//! directory, as a CSV file. When [uploading with a sync][`crate::item::sync::Request::with_manifest`],
+//! a manifest can be generated and uploaded alongside the content. The item can later be [checked][`Item::verify_manifest`]
+//! against its manifest, to detect files which went missing or changed since they were uploaded.
+//! 
+//! # Example
+//! ```rust,no_run
+//! use std::path::Path;
+//! use iars::{Credentials, Item};
+//! 
+//! let item = Item::new("test_item")?
+//!     .with_credentials(Some(Credentials::new("abcdefghijklmnop", "1234567890123456")));
+//! 
+//! item.sync(Path::new("local/test_item"))
+//!     .with_manifest(Some("manifest.csv"))
+//!     .upload()?;
+//! 
+//! // some time later
+//! let verification = item.verify_manifest("manifest.csv")?;
+//! assert!(verification.is_ok());
+//! # Ok::<(), iars::ItemError>(())
+//! ```
+
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+use serde::{Deserialize, Serialize};
+use crate::item::{local_path, FileMetadata, Item, ItemError};
+use crate::item::sync::{md5_file, Mismatch, Verification};
+
+/// List of files and their expected sizes and checksums.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manifest {
+    /// Entry of each file, in the order they were added.
+    pub entries: Vec<Entry>,
+}
+impl Manifest {
+    /// Builds a manifest of files within a local directory, given their paths relative to `root`
+    /// (using `/` separators).
+    /// 
+    /// # Errors
+    /// Returns an error if a file cannot be read, or if a name could refer to a file outside of `root`.
+    pub fn from_files<S: AsRef<str>>(root: &Path, names: &[S]) -> io::Result<Self> {
+        let entries = names.iter().map(|name| {
+            let name = name.as_ref();
+            let path = local_path(root, name).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+            let meta = fs::metadata(&path)?;
+            
+            Ok(Entry {
+                path: name.to_string(),
+                size: meta.len(),
+                md5: md5_file(&path)?,
+                mtime: meta.modified().ok()
+                    .and_then(|mtime| mtime.duration_since(UNIX_EPOCH).ok())
+                    .map(|mtime| mtime.as_secs() as i64),
+            })
+        }).collect::<io::Result<_>>()?;
+        
+        Ok(Self { entries })
+    }
+    
+    /// Parses a manifest previously written by [`Manifest::write`].
+    /// 
+    /// # Errors
+    /// Returns an error if the manifest cannot be read, or is not a valid manifest.
+    pub fn read(reader: impl Read) -> io::Result<Self> {
+        let entries = csv::Reader::from_reader(reader)
+            .deserialize()
+            .collect::<Result<_, _>>()?;
+        
+        Ok(Self { entries })
+    }
+    
+    /// Writes the manifest as CSV, with a `path,size,md5,mtime` header.
+    /// 
+    /// # Errors
+    /// Returns an error if writing fails.
+    pub fn write(&self, writer: impl Write) -> io::Result<()> {
+        let mut csv = csv::Writer::from_writer(writer);
+        for entry in &self.entries {
+            csv.serialize(entry)?;
+        }
+        
+        csv.flush()
+    }
+    
+    /// Checks the files of an item against this manifest.
+    /// 
+    /// The "actual" values of each [`Mismatch`] are those of the item. Files of the item which aren't
+    /// listed in the manifest are reported as [unexpected][`Verification::unexpected`], except for files
+    /// produced by the Internet Archive (derivatives and metadata files).
+    pub fn verify(&self, files: &[FileMetadata]) -> Verification {
+        let mut verification = Verification::default();
+        
+        for entry in &self.entries {
+            let Some(file) = files.iter().find(|file| file.name == entry.path) else {
+                verification.missing.push(entry.path.clone());
+                continue;
+            };
+            
+            let md5 = file.md5.clone().unwrap_or_default();
+            if file.size != Some(entry.size) || !md5.eq_ignore_ascii_case(&entry.md5) {
+                verification.mismatched.push(Mismatch {
+                    name: entry.path.clone(),
+                    expected_size: Some(entry.size),
+                    actual_size: file.size.unwrap_or_default(),
+                    expected_md5: Some(entry.md5.clone()),
+                    actual_md5: md5,
+                });
+            } else {
+                verification.verified.push(entry.path.clone());
+            }
+        }
+        
+        let listed: HashSet<&str> = self.entries.iter().map(|entry| entry.path.as_str()).collect();
+        verification.unexpected = files.iter()
+            .filter(|file| file.is_original() && !listed.contains(file.name.as_str()))
+            .map(|file| file.name.clone())
+            .collect();
+        
+        verification
+    }
+}
+
+/// A single file listed in a [`Manifest`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    /// Path of the file, relative to the root of the item.
+    pub path: String,
+    
+    /// Size of the file in bytes.
+    pub size: u64,
+    
+    /// MD5 checksum of the file, as a hex string.
+    pub md5: String,
+    
+    /// UNIX epoch timestamp of when the local file was last modified, if known.
+    pub mtime: Option<i64>,
+}
+
+impl Item {
+    /// Downloads the manifest stored in this item at `manifest_path`, and checks the item's files
+    /// against it.
+    /// 
+    /// The manifest itself is not reported as [unexpected][`Verification::unexpected`].
+    /// 
+    /// # Errors
+    /// If the manifest does not exist, an [`ItemError::NotFound`] is returned. If it cannot be parsed,
+    /// an [`ItemError::Io`] is returned. See also [`Item::metadata`].
+    pub fn verify_manifest(&self, manifest_path: &str) -> Result<Verification, ItemError> {
+        let mut buf = vec![];
+        self.download_file(manifest_path, &mut buf)?;
+        let manifest = Manifest::read(buf.as_slice())?;
+        
+        let mut verification = manifest.verify(self.metadata()?.files());
+        verification.unexpected.retain(|name| name != manifest_path);
+        
+        Ok(verification)
+    }
+}
\ No newline at end of file
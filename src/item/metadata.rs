@@ -0,0 +1,402 @@
+//! Modification of item metadata.
+//! 
+//! The [Metadata API](https://archive.org/developers/metadata.html) allows individual metadata fields
+//! of an existing item to be added, changed, or removed, without affecting any other fields. Changes
+//! are submitted as a [JSON Patch](https://datatracker.ietf.org/doc/html/rfc6902), and applied by a
+//! task queued on the item.
+//! 
+//! Fields can either be [set][`Request::with_set`], replacing any existing value, or [appended to][`Request::with_append`],
+//! which adds values to a multi-value field (such as `subject`) while keeping the existing ones. Appends
+//! are resolved against the item's current metadata when the request is submitted, and the generated
+//! patch fails rather than overwriting values which changed in the meantime.
+//! 
+//! # Example
+//! ```rust,no_run
+//! use iars::{Credentials, Item};
+//! 
+//! let item = Item::new("test_item")?
+//!     .with_credentials(Some(Credentials::new("abcdefghijklmnop", "1234567890123456")));
+//! 
+//! let resp = item.modify_metadata()
+//!     .with_set("title", "A Better Title")
+//!     .with_append("subject", "maps")
+//!     .with_remove("notes")
+//!     .call()?;
+//! 
+//! println!("queued task {:?}", resp.task_id);
+//! # Ok::<(), iars::ItemError>(())
+//! ```
+
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use crate::headers::RequestHeaderExt;
+use crate::item::{Item, ItemError};
+use crate::tasks::Priority;
+
+/// Value of an item's metadata field, which may hold either one or several strings.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MetaValue {
+    Single(String),
+    Multiple(Vec<String>),
+}
+impl MetaValue {
+    /// Returns the first value, or `None` if this is an empty list.
+    pub fn first(&self) -> Option<&str> {
+        self.iter().next()
+    }
+    
+    /// Iterates over every value. A [`MetaValue::Single`] yields exactly one value.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        let values = match self {
+            Self::Single(value) => std::slice::from_ref(value),
+            Self::Multiple(values) => values.as_slice(),
+        };
+        
+        values.iter().map(String::as_str)
+    }
+    
+    /// Returns the number of values.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Single(_) => 1,
+            Self::Multiple(values) => values.len(),
+        }
+    }
+    
+    /// Returns true if this is an empty list.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    
+    /// Returns true if the value (or any of the values) equals `value`.
+    pub fn contains(&self, value: &str) -> bool {
+        self.iter().any(|v| v == value)
+    }
+}
+impl From<&str> for MetaValue {
+    fn from(value: &str) -> Self {
+        Self::Single(value.to_string())
+    }
+}
+impl From<String> for MetaValue {
+    fn from(value: String) -> Self {
+        Self::Single(value)
+    }
+}
+impl From<Vec<String>> for MetaValue {
+    fn from(values: Vec<String>) -> Self {
+        Self::Multiple(values)
+    }
+}
+impl From<Vec<&str>> for MetaValue {
+    fn from(values: Vec<&str>) -> Self {
+        Self::Multiple(values.into_iter().map(str::to_string).collect())
+    }
+}
+impl From<&[&str]> for MetaValue {
+    fn from(values: &[&str]) -> Self {
+        Self::Multiple(values.iter().map(|value| value.to_string()).collect())
+    }
+}
+
+/// A single change to a metadata field.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// Sets the field to a value, replacing any existing value.
+    Set(String, MetaValue),
+    
+    /// Adds values to a multi-value field, keeping any existing values. Values the field already
+    /// contains are not added again.
+    /// 
+    /// If the field holds a single value, it becomes a list. If the field doesn't exist, it's created.
+    Append(String, Vec<String>),
+    
+    /// Removes the field. The field must exist, or the whole request will be rejected.
+    Remove(String),
+}
+
+/// Request builder for modifying the metadata of an item.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    item: Item,
+    changes: Vec<Change>,
+    priority: Option<Priority>,
+    derive: Option<bool>,
+}
+impl Request {
+    /// Sets a field to a single string value, replacing any existing value(s).
+    pub fn with_set(self, key: &str, value: &str) -> Self {
+        self.with_change(Change::Set(key.to_string(), value.into()))
+    }
+    
+    /// Sets a field to a list of string values, replacing any existing value(s).
+    pub fn with_set_list(self, key: &str, values: &[&str]) -> Self {
+        self.with_change(Change::Set(key.to_string(), values.into()))
+    }
+    
+    /// Appends a value to a multi-value field (e.g. adding a subject), keeping any existing value(s).
+    /// 
+    /// See [`Change::Append`].
+    pub fn with_append(self, key: &str, value: &str) -> Self {
+        self.with_append_list(key, &[value])
+    }
+    
+    /// Appends several values to a multi-value field, keeping any existing value(s).
+    /// 
+    /// See [`Change::Append`].
+    pub fn with_append_list(self, key: &str, values: &[&str]) -> Self {
+        self.with_change(Change::Append(key.to_string(), values.iter().map(|value| value.to_string()).collect()))
+    }
+    
+    /// Removes a field.
+    pub fn with_remove(self, key: &str) -> Self {
+        self.with_change(Change::Remove(key.to_string()))
+    }
+    
+    /// Adds a [`Change`] to the request.
+    pub fn with_change(mut self, change: Change) -> Self {
+        self.changes.push(change);
+        
+        self
+    }
+    
+    /// [Priority][`Priority`] of the task queued to apply the changes.
+    /// 
+    /// Bulk changes to many items can use a negative priority, so they don't delay interactive tasks.
+    /// If not provided, the Internet Archive's default priority is used.
+    pub fn with_priority(mut self, priority: Option<Priority>) -> Self {
+        self.priority = priority;
+        
+        self
+    }
+    
+    /// Configures whether a derive may be queued on the item after the changes are applied.
+    /// 
+    /// Some metadata changes (e.g. to `title`) cause derivatives to be regenerated. Setting this to
+    /// `false` prevents that. If not called, the Internet Archive decides.
+    pub fn with_derive(mut self, derive: bool) -> Self {
+        self.derive = Some(derive);
+        
+        self
+    }
+    
+    /// Returns the changes included in this request.
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+    
+    /// Builds the JSON Patch for this request, assuming every [appended][`Change::Append`] field already
+    /// holds a list.
+    /// 
+    /// [`Request::call`] instead resolves appends against the item's current metadata, as done by
+    /// [`Request::patch_for`].
+    pub fn patch(&self) -> Value {
+        Value::Array(self.changes.iter().flat_map(|change| match change {
+            Change::Set(key, value) => vec![json!({ "op": "add", "path": format!("/{key}"), "value": value })],
+            Change::Append(key, values) => values.iter().map(|value| json!({ "op": "add", "path": format!("/{key}/-"), "value": value })).collect(),
+            Change::Remove(key) => vec![json!({ "op": "remove", "path": format!("/{key}") })],
+        }).collect())
+    }
+    
+    /// Builds the JSON Patch for this request, resolving [appends][`Change::Append`] against the item's
+    /// `current` metadata.
+    /// 
+    /// Appending to a list adds each new value to the end of it. Appending to a single value replaces
+    /// it with a list, preceded by a `test` operation so the patch is rejected if the value changed.
+    pub fn patch_for(&self, current: &HashMap<String, MetaValue>) -> Value {
+        let mut state = current.clone();
+        let mut ops = vec![];
+        for change in &self.changes {
+            match change {
+                Change::Set(key, value) => {
+                    ops.push(json!({ "op": "add", "path": format!("/{key}"), "value": value }));
+                    state.insert(key.clone(), value.clone());
+                },
+                Change::Remove(key) => {
+                    ops.push(json!({ "op": "remove", "path": format!("/{key}") }));
+                    state.remove(key);
+                },
+                Change::Append(key, values) => {
+                    let existing = state.get(key).cloned();
+                    let mut merged: Vec<String> = existing.iter().flat_map(|value| value.iter().map(str::to_string)).collect();
+                    let new: Vec<String> = values.iter().filter(|value| !merged.contains(value)).cloned().collect();
+                    if new.is_empty() {
+                        continue;
+                    }
+                    merged.extend(new.iter().cloned());
+                    
+                    match existing {
+                        Some(MetaValue::Multiple(_)) => ops.extend(new.iter().map(|value| json!({ "op": "add", "path": format!("/{key}/-"), "value": value }))),
+                        Some(MetaValue::Single(value)) => {
+                            ops.push(json!({ "op": "test", "path": format!("/{key}"), "value": value }));
+                            ops.push(json!({ "op": "add", "path": format!("/{key}"), "value": merged }));
+                        },
+                        None if merged.len() == 1 => ops.push(json!({ "op": "add", "path": format!("/{key}"), "value": merged[0] })),
+                        None => ops.push(json!({ "op": "add", "path": format!("/{key}"), "value": merged })),
+                    }
+                    
+                    state.insert(key.clone(), merged.into());
+                },
+            }
+        }
+        
+        Value::Array(ops)
+    }
+    
+    /// Submits the changes to the Internet Archive.
+    /// 
+    /// If the request includes any [appends][`Change::Append`], the item's current metadata is retrieved
+    /// first, to [resolve][`Request::patch_for`] them.
+    /// 
+    /// # Errors
+    /// Possibly returns [`ItemError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    /// 
+    /// If the Metadata API refuses the changes (e.g. when removing a field which does not exist, or
+    /// when the changes would not modify the item), an [`ItemError::Rejected`] is returned.
+    pub fn call(&self) -> Result<Response, ItemError> {
+        let url = format!("https://archive.org/metadata/{}", self.item.inner.identifier);
+        let context = self.item.context("modify_metadata", &url);
+        
+        self.item.check_tasks(&context)?;
+        
+        let mut req = self.item.inner.client.request("POST", &url)
+            .set("user-agent", &self.item.inner.useragent);
+        
+        if let Some(creds) = self.item.inner.credentials.as_ref() {
+            req = req.set_header(creds.into());
+        }
+        
+        let current = match self.changes.iter().any(|change| matches!(change, Change::Append(..))) {
+            true => self.item.metadata()?.metadata().clone(),
+            false => HashMap::new(),
+        };
+        
+        let patch = self.patch_for(&current).to_string();
+        let priority = self.priority.map(|priority| priority.to_string());
+        let mut form = vec![("-target", "metadata"), ("-patch", patch.as_str())];
+        if let Some(priority) = priority.as_deref() {
+            form.push(("priority", priority));
+        }
+        if let Some(derive) = self.derive {
+            form.push(("derive", if derive { "1" } else { "0" }));
+        }
+        
+        let resp = match self.item.inner.client.send_form(req, &form) {
+            Ok(resp) | Err(ureq::Error::Status(400, resp)) => resp,
+            Err(err) => return Err(ItemError::ureq(&context, err)),
+        };
+        let resp: InterimResponse = resp.into_json().map_err(|err| ItemError::io(&context, err))?;
+        
+        if !resp.success {
+            return Err(ItemError::Rejected { context, message: resp.error.unwrap_or_default() });
+        }
+        
+        Ok(Response {
+            task_id: resp.task_id,
+            log: resp.log,
+        })
+    }
+}
+
+/// Response data returned from a successful metadata [modification][`Request::call`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    /// ID of the task queued to apply the changes.
+    pub task_id: Option<usize>,
+    
+    /// URL of the queued task's [log][`crate::tasks::log`].
+    pub log: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InterimResponse {
+    #[serde(default)]
+    success: bool,
+    error: Option<String>,
+    task_id: Option<usize>,
+    log: Option<String>,
+}
+
+impl Item {
+    /// Creates a [request][`Request`] to modify this item's metadata.
+    pub fn modify_metadata(&self) -> Request {
+        Request {
+            item: self.clone(),
+            changes: vec![],
+            priority: None,
+            derive: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn request() -> Request {
+        Item::new("test_item").unwrap().modify_metadata()
+    }
+    
+    #[test]
+    fn appends_to_lists() {
+        let current = HashMap::from([("subject".to_string(), MetaValue::from(vec!["maps", "atlases"]))]);
+        let patch = request()
+            .with_append_list("subject", &["atlases", "charts", "globes"])
+            .patch_for(&current);
+        
+        assert_eq!(patch, json!([
+            { "op": "add", "path": "/subject/-", "value": "charts" },
+            { "op": "add", "path": "/subject/-", "value": "globes" },
+        ]));
+    }
+    
+    #[test]
+    fn appends_to_single_values() {
+        let current = HashMap::from([("subject".to_string(), MetaValue::from("maps"))]);
+        let patch = request()
+            .with_append("subject", "charts")
+            .with_append("subject", "globes")
+            .patch_for(&current);
+        
+        assert_eq!(patch, json!([
+            { "op": "test", "path": "/subject", "value": "maps" },
+            { "op": "add", "path": "/subject", "value": ["maps", "charts"] },
+            { "op": "add", "path": "/subject/-", "value": "globes" },
+        ]));
+    }
+    
+    #[test]
+    fn appends_to_missing_fields() {
+        let patch = request()
+            .with_append("subject", "maps")
+            .with_append_list("language", &["eng", "fre"])
+            .with_append("subject", "maps")
+            .patch_for(&HashMap::new());
+        
+        assert_eq!(patch, json!([
+            { "op": "add", "path": "/subject", "value": "maps" },
+            { "op": "add", "path": "/language", "value": ["eng", "fre"] },
+        ]));
+    }
+    
+    #[test]
+    fn appends_after_other_changes() {
+        let current = HashMap::from([("subject".to_string(), MetaValue::from(vec!["maps"]))]);
+        let patch = request()
+            .with_remove("subject")
+            .with_append("subject", "charts")
+            .with_set("title", "Atlas")
+            .with_append("title", "Maps")
+            .patch_for(&current);
+        
+        assert_eq!(patch, json!([
+            { "op": "remove", "path": "/subject" },
+            { "op": "add", "path": "/subject", "value": "charts" },
+            { "op": "add", "path": "/title", "value": "Atlas" },
+            { "op": "test", "path": "/title", "value": "Atlas" },
+            { "op": "add", "path": "/title", "value": ["Atlas", "Maps"] },
+        ]));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,495 @@
+//! Multipart uploads of large files.
+//! 
+//! The Internet Archive's S3-like API supports uploading a single file in several parts, which can
+//! be sent (and retried) independently of one another. This is recommended for very large files,
+//! where restarting a failed single-request upload from the beginning would be costly.
+//! 
+//! A multipart upload is [started][`Item::start_multipart_upload`], then each part is
+//! [uploaded][`Upload::upload_part`], and finally the upload is [completed][`Upload::complete`] to
+//! assemble the parts into the final file.
+//! 
+//! Uploads which are never completed or [aborted][`Upload::abort`] will linger on the item, and may
+//! block future uploads of the same file. These can be found using [`Item::multipart_uploads`] and
+//! removed using [`Item::abort_multipart_upload`].
+//! 
+//! # Parallel Uploads
+//! Large local files can be split into parts and uploaded using several threads at once with
+//! [`Upload::upload_parts_from_file`], or [`Item::upload_file_multipart`] which also starts and
+//! completes the upload. This can help to saturate fast connections when uploading very large items.
+//! 
+//! # Example
+//! ```rust,no_run
+//! use iars::{Credentials, Item};
+//! 
+//! let item = Item::new("test_item")?
+//!     .with_credentials(Some(Credentials::new("abcdefghijklmnop", "1234567890123456")));
+//! 
+//! let upload = item.start_multipart_upload(true, &[], "large_file.bin")?;
+//! 
+//! let part = vec![0u8; 16 * 1024 * 1024];
+//! upload.upload_part(1, part.as_slice(), part.len())?;
+//! upload.upload_part(2, part.as_slice(), part.len())?;
+//! 
+//! upload.complete()?;
+//! # Ok::<(), iars::ItemError>(())
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use md5::{Digest, Md5};
+use serde::Deserialize;
+use crate::headers::Header::ContentLength;
+use crate::headers::RequestHeaderExt;
+use crate::item::{Item, ItemError};
+
+/// An in-progress multipart upload of a single file.
+/// 
+/// Parts may be uploaded from multiple threads at once, as [`Upload::upload_part`] only requires a
+/// shared reference.
+#[derive(Debug)]
+pub struct Upload {
+    item: Item,
+    path: String,
+    upload_id: String,
+    parts: Mutex<BTreeMap<usize, Part>>,
+}
+impl Upload {
+    /// Path of the file being uploaded, relative to the root of the item.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+    
+    /// ID assigned to this upload by the Internet Archive.
+    pub fn upload_id(&self) -> &str {
+        &self.upload_id
+    }
+    
+    /// Returns the numbers and sizes of the parts which have been uploaded so far, in order.
+    /// 
+    /// For a [resumed][`Item::resume_multipart_upload`] upload, this includes the parts uploaded
+    /// before it was resumed.
+    pub fn uploaded_parts(&self) -> Vec<(usize, u64)> {
+        self.parts.lock().unwrap().iter().map(|(number, part)| (*number, part.size)).collect()
+    }
+    
+    /// Uploads a single part of the file.
+    /// 
+    /// Parts are numbered starting from 1, and will be assembled in order of their number when the
+    /// upload is [completed][`Upload::complete`]. Every part except the last must be at least 5 MiB.
+    /// Uploading a part with the same number again replaces the previous upload of that part.
+    /// 
+    /// As with [`Item::upload_file`], the `size` of the part **must** be accurate.
+    /// 
+    /// # Errors
+    /// Possibly returns [`ItemError::Ureq`] if a [`ureq::Error`] is encountered while uploading.
+    pub fn upload_part(&self, part_number: usize, reader: impl Read, size: usize) -> Result<(), ItemError> {
+        let url = self.url();
+        let context = self.item.context("upload_part", &url);
+        let mut req = self.item.inner.client.request("PUT", &url)
+            .query("partNumber", &part_number.to_string())
+            .query("uploadId", &self.upload_id)
+            .set("user-agent", &self.item.inner.useragent)
+            .set_header(ContentLength(size));
+        
+        if let Some(creds) = self.item.inner.credentials.as_ref() {
+            req = req.set_header(creds.into());
+        }
+        
+        let resp = self.item.inner.client.send(req, reader).map_err(|err| ItemError::ureq(&context, err))?;
+        let etag = resp.header("etag")
+            .ok_or_else(|| ItemError::io(&context, std::io::Error::new(std::io::ErrorKind::InvalidData, "response is missing an ETag")))?;
+        
+        self.parts.lock().unwrap().insert(part_number, Part { etag: etag.to_string(), size: size as u64 });
+        
+        Ok(())
+    }
+    
+    /// Uploads every part of a local file, using up to `concurrency` threads at once.
+    /// 
+    /// The file is split into parts of `part_size` bytes (except the last part, which may be smaller),
+    /// numbered from 1. `part_size` must be at least 5 MiB, unless the file fits within a single part.
+    /// 
+    /// Parts which were already uploaded with the same contents (e.g. before the upload was [resumed][`Item::resume_multipart_upload`])
+    /// are skipped, so only the missing parts are sent. A part is only considered the same if both its
+    /// size and the MD5 checksum of its contents match the uploaded part's ETag; otherwise it is uploaded
+    /// again, replacing the previous upload of that part.
+    /// 
+    /// If any part fails to upload, no further parts are started and the first error is returned.
+    /// Parts which were uploaded successfully are kept, so the upload may be retried or [aborted][`Upload::abort`].
+    /// 
+    /// # Errors
+    /// Possibly returns [`ItemError::Ureq`] if a [`ureq::Error`] is encountered while uploading.
+    /// 
+    /// If the file cannot be read, or `part_size` or `concurrency` is 0, an [`ItemError::Io`] is returned.
+    pub fn upload_parts_from_file(&self, source: &Path, part_size: usize, concurrency: usize) -> Result<(), ItemError> {
+        if part_size == 0 || concurrency == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "part_size and concurrency must be greater than 0").into());
+        }
+        
+        let len = std::fs::metadata(source)?.len() as usize;
+        let parts = len.div_ceil(part_size).max(1);
+        
+        let next = AtomicUsize::new(0);
+        let failed = AtomicBool::new(false);
+        let error = Mutex::new(None);
+        
+        thread::scope(|scope| {
+            for _ in 0..concurrency.min(parts) {
+                scope.spawn(|| {
+                    while !failed.load(Ordering::Relaxed) {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        if index >= parts {
+                            break;
+                        }
+                        
+                        let offset = index * part_size;
+                        let size = part_size.min(len - offset);
+                        let result = self.is_uploaded(index + 1, source, offset, size).and_then(|uploaded| match uploaded {
+                            true => Ok(()),
+                            false => open_part(source, offset, size)
+                                .map_err(ItemError::from)
+                                .and_then(|file| self.upload_part(index + 1, file, size)),
+                        });
+                        
+                        if let Err(err) = result {
+                            failed.store(true, Ordering::Relaxed);
+                            error.lock().unwrap().get_or_insert(err);
+                        }
+                    }
+                });
+            }
+        });
+        
+        match error.into_inner().unwrap() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+    
+    /// Completes the upload, assembling all uploaded parts into the final file.
+    /// 
+    /// # Errors
+    /// Possibly returns [`ItemError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    pub fn complete(self) -> Result<ureq::Response, ItemError> {
+        let url = self.url();
+        let context = self.item.context("complete_multipart_upload", &url);
+        
+        let mut body = String::from("<CompleteMultipartUpload>");
+        for (part_number, part) in self.parts.lock().unwrap().iter() {
+            body.push_str(&format!("<Part><PartNumber>{part_number}</PartNumber><ETag>{}</ETag></Part>", part.etag));
+        }
+        body.push_str("</CompleteMultipartUpload>");
+        
+        let mut req = self.item.inner.client.request("POST", &url)
+            .query("uploadId", &self.upload_id)
+            .set("user-agent", &self.item.inner.useragent)
+            .set_header(ContentLength(body.len()));
+        
+        if let Some(creds) = self.item.inner.credentials.as_ref() {
+            req = req.set_header(creds.into());
+        }
+        
+        self.item.inner.client.send(req, body.as_bytes()).map_err(|err| ItemError::ureq(&context, err))
+    }
+    
+    /// Aborts the upload, discarding any parts which have already been uploaded.
+    /// 
+    /// # Errors
+    /// Possibly returns [`ItemError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    pub fn abort(self) -> Result<(), ItemError> {
+        self.item.abort_multipart_upload(&self.path, &self.upload_id)
+    }
+    
+    fn url(&self) -> String {
+        format!("https://s3.us.archive.org/{}/{}", self.item.inner.identifier, self.path)
+    }
+    
+    /// Checks whether a part has already been uploaded with the contents of the given range of `source`,
+    /// by comparing the range's MD5 checksum with the part's ETag.
+    fn is_uploaded(&self, part_number: usize, source: &Path, offset: usize, size: usize) -> Result<bool, ItemError> {
+        let etag = match self.parts.lock().unwrap().get(&part_number) {
+            Some(part) if part.size == size as u64 => part.etag.trim_matches('"').to_ascii_lowercase(),
+            _ => return Ok(false),
+        };
+        
+        let mut hasher = Md5::new();
+        io::copy(&mut open_part(source, offset, size)?, &mut hasher)?;
+        
+        Ok(format!("{:x}", hasher.finalize()) == etag)
+    }
+}
+
+/// Opens a reader over `size` bytes of a local file, starting at `offset`.
+fn open_part(source: &Path, offset: usize, size: usize) -> io::Result<io::Take<File>> {
+    let mut file = File::open(source)?;
+    file.seek(SeekFrom::Start(offset as u64))?;
+    
+    Ok(file.take(size as u64))
+}
+
+/// A part which has been uploaded.
+#[derive(Debug)]
+struct Part {
+    etag: String,
+    size: u64,
+}
+
+/// A multipart upload which has been started, but not yet completed or aborted.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct InProgress {
+    /// Path of the file being uploaded, relative to the root of the item.
+    #[serde(rename = "Key")]
+    pub path: String,
+    
+    /// ID assigned to the upload by the Internet Archive.
+    #[serde(rename = "UploadId")]
+    pub upload_id: String,
+    
+    /// Date and time the upload was started (e.g. `2024-01-02T03:04:05.000Z`).
+    #[serde(rename = "Initiated")]
+    pub initiated: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct InitiateMultipartUploadResult {
+    upload_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListMultipartUploadsResult {
+    #[serde(rename = "Upload", default)]
+    uploads: Vec<InProgress>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListPartsResult {
+    #[serde(rename = "Part", default)]
+    parts: Vec<ListedPart>,
+    #[serde(default)]
+    is_truncated: bool,
+    next_part_number_marker: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListedPart {
+    part_number: usize,
+    #[serde(rename = "ETag")]
+    etag: String,
+    size: u64,
+}
+
+impl Item {
+    /// Starts a multipart upload of a file to this item.
+    /// 
+    /// The `derive` and `initial_meta` arguments behave the same as in [`Item::upload_file`]. See the
+    /// [module docs][`crate::item::multipart`] for details.
+    /// 
+    /// # Errors
+    /// Possibly returns [`ItemError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    /// 
+    /// If the query succeeds but the response cannot be parsed, an [`ItemError::XmlParseFailed`]
+    /// is returned.
+    pub fn start_multipart_upload(&self, derive: bool, initial_meta: &[(&str, &str)], filepath: &str) -> Result<Upload, ItemError> {
+        let url = format!("https://s3.us.archive.org/{}/{filepath}", self.inner.identifier);
+        let context = self.context("start_multipart_upload", &url);
+        
+        self.check_tasks(&context)?;
+        self.check_overwrite(&url, &context)?;
+        
+        let mut req = self.upload_request("POST", &url, derive, initial_meta)
+            .query("uploads", "")
+            .set_header(ContentLength(0));
+        
+        if let Some(creds) = self.inner.credentials.as_ref() {
+            req = req.set_header(creds.into());
+        }
+        
+        let resp = self.inner.client.send(req, std::io::empty()).map_err(|err| ItemError::ureq(&context, err))?;
+        let result: InitiateMultipartUploadResult = quick_xml::de::from_reader(BufReader::new(resp.into_reader()))
+            .map_err(|err| ItemError::xml(&context, err))?;
+        
+        Ok(Upload {
+            item: self.clone(),
+            path: filepath.to_string(),
+            upload_id: result.upload_id,
+            parts: Default::default(),
+        })
+    }
+    
+    /// Resumes a multipart upload which was started earlier (e.g. by a process which was interrupted),
+    /// using its `upload_id` as listed by [`Item::multipart_uploads`].
+    /// 
+    /// The parts which were already uploaded are retrieved, so that [`Upload::upload_parts_from_file`]
+    /// only sends the missing parts. The `derive` and `initial_meta` options of the original upload still apply.
+    /// 
+    /// # Errors
+    /// Possibly returns [`ItemError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    /// If the upload no longer exists, an [`ItemError::NotFound`] is returned.
+    /// 
+    /// If the query succeeds but the response cannot be parsed, an [`ItemError::XmlParseFailed`]
+    /// is returned.
+    pub fn resume_multipart_upload(&self, filepath: &str, upload_id: &str) -> Result<Upload, ItemError> {
+        let upload = Upload {
+            item: self.clone(),
+            path: filepath.to_string(),
+            upload_id: upload_id.to_string(),
+            parts: Default::default(),
+        };
+        let url = upload.url();
+        let context = self.context("resume_multipart_upload", &url);
+        
+        let mut marker = None;
+        loop {
+            let mut req = self.inner.client.request("GET", &url)
+                .query("uploadId", upload_id)
+                .set("user-agent", &self.inner.useragent);
+            
+            if let Some(marker) = marker {
+                req = req.query("part-number-marker", &format!("{marker}"));
+            }
+            if let Some(creds) = self.inner.credentials.as_ref() {
+                req = req.set_header(creds.into());
+            }
+            
+            let resp = self.inner.client.call(req).map_err(|err| ItemError::ureq(&context, err))?;
+            let result: ListPartsResult = quick_xml::de::from_reader(BufReader::new(resp.into_reader()))
+                .map_err(|err| ItemError::xml(&context, err))?;
+            
+            upload.parts.lock().unwrap().extend(result.parts.into_iter().map(|part| (part.part_number, Part { etag: part.etag, size: part.size })));
+            
+            match result.next_part_number_marker {
+                Some(next) if result.is_truncated => marker = Some(next),
+                _ => break,
+            }
+        }
+        
+        Ok(upload)
+    }
+    
+    /// Uploads a local file to this item as a parallel multipart upload, resuming an earlier upload of
+    /// the same file if one is still in progress.
+    /// 
+    /// This behaves like [`Item::upload_file_multipart`], except that if the item has an [in-progress upload][`Item::multipart_uploads`]
+    /// of `filepath`, the most recent one is [resumed][`Item::resume_multipart_upload`] and only its
+    /// missing parts are uploaded. `part_size` must match the one used by the earlier upload, otherwise
+    /// every part is uploaded again. The `derive` and `initial_meta` arguments only apply when a new
+    /// upload is started.
+    /// 
+    /// The upload to resume is chosen by `filepath` alone (the most recently initiated one, if several
+    /// are in progress); it isn't checked against `source`. An upload started from a different local
+    /// file is still resumed, but as each part's checksum is [compared][`Upload::upload_parts_from_file`]
+    /// before it is skipped, any part with different contents is uploaded again.
+    /// 
+    /// # Errors
+    /// See [`Item::upload_file_multipart`] and [`Item::resume_multipart_upload`].
+    pub fn resume_file_multipart(&self, derive: bool, initial_meta: &[(&str, &str)], filepath: &str, source: &Path, part_size: usize, concurrency: usize) -> Result<ureq::Response, ItemError> {
+        let previous = self.multipart_uploads()?.into_iter()
+            .filter(|upload| upload.path == filepath)
+            .max_by(|a, b| a.initiated.cmp(&b.initiated));
+        
+        let upload = match previous {
+            Some(previous) => self.resume_multipart_upload(filepath, &previous.upload_id)?,
+            None => self.start_multipart_upload(derive, initial_meta, filepath)?,
+        };
+        upload.upload_parts_from_file(source, part_size, concurrency)?;
+        
+        upload.complete()
+    }
+    
+    /// Uploads a local file to this item as a parallel multipart upload.
+    /// 
+    /// This [starts][`Item::start_multipart_upload`] an upload, [uploads every part][`Upload::upload_parts_from_file`]
+    /// using up to `concurrency` threads, and then [completes][`Upload::complete`] the upload.
+    /// 
+    /// If a part fails to upload, the upload is left in progress so that it may be inspected with
+    /// [`Item::multipart_uploads`], and should be [aborted][`Item::abort_multipart_upload`] if it will
+    /// not be [resumed][`Item::resume_file_multipart`].
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use std::path::Path;
+    /// use iars::{Credentials, Item};
+    /// 
+    /// let item = Item::new("test_item")?
+    ///     .with_credentials(Some(Credentials::new("abcdefghijklmnop", "1234567890123456")));
+    /// 
+    /// // upload in 100 MiB parts, 8 at a time
+    /// item.upload_file_multipart(true, &[], "disk_image.iso", Path::new("disk_image.iso"), 100 * 1024 * 1024, 8)?;
+    /// # Ok::<(), iars::ItemError>(())
+    /// ```
+    /// 
+    /// # Errors
+    /// See [`Item::start_multipart_upload`] and [`Upload::upload_parts_from_file`].
+    pub fn upload_file_multipart(&self, derive: bool, initial_meta: &[(&str, &str)], filepath: &str, source: &Path, part_size: usize, concurrency: usize) -> Result<ureq::Response, ItemError> {
+        let upload = self.start_multipart_upload(derive, initial_meta, filepath)?;
+        upload.upload_parts_from_file(source, part_size, concurrency)?;
+        
+        upload.complete()
+    }
+    
+    /// Lists the multipart uploads on this item which have been started, but not yet completed or aborted.
+    /// 
+    /// # Errors
+    /// Possibly returns [`ItemError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    /// 
+    /// If the query succeeds but the response cannot be parsed, an [`ItemError::XmlParseFailed`]
+    /// is returned.
+    pub fn multipart_uploads(&self) -> Result<Vec<InProgress>, ItemError> {
+        let url = format!("https://s3.us.archive.org/{}", self.inner.identifier);
+        let context = self.context("multipart_uploads", &url);
+        let mut req = self.inner.client.request("GET", &url)
+            .query("uploads", "")
+            .set("user-agent", &self.inner.useragent);
+        
+        if let Some(creds) = self.inner.credentials.as_ref() {
+            req = req.set_header(creds.into());
+        }
+        
+        let resp = self.inner.client.call(req).map_err(|err| ItemError::ureq(&context, err))?;
+        let result: ListMultipartUploadsResult = quick_xml::de::from_reader(BufReader::new(resp.into_reader()))
+            .map_err(|err| ItemError::xml(&context, err))?;
+        
+        Ok(result.uploads)
+    }
+    
+    /// Aborts a multipart upload on this item, discarding any parts which have already been uploaded.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use iars::{Credentials, Item};
+    /// 
+    /// let item = Item::new("test_item")?
+    ///     .with_credentials(Some(Credentials::new("abcdefghijklmnop", "1234567890123456")));
+    /// 
+    /// for upload in item.multipart_uploads()? {
+    ///     item.abort_multipart_upload(&upload.path, &upload.upload_id)?;
+    /// }
+    /// # Ok::<(), iars::ItemError>(())
+    /// ```
+    /// 
+    /// # Errors
+    /// Possibly returns [`ItemError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    pub fn abort_multipart_upload(&self, filepath: &str, upload_id: &str) -> Result<(), ItemError> {
+        let url = format!("https://s3.us.archive.org/{}/{filepath}", self.inner.identifier);
+        let context = self.context("abort_multipart_upload", &url);
+        let mut req = self.inner.client.request("DELETE", &url)
+            .query("uploadId", upload_id)
+            .set("user-agent", &self.inner.useragent);
+        
+        if let Some(creds) = self.inner.credentials.as_ref() {
+            req = req.set_header(creds.into());
+        }
+        
+        self.inner.client.call(req).map_err(|err| ItemError::ureq(&context, err))?;
+        
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,196 @@
+//! Point-in-time snapshots of an item's files and metadata, for auditing changes between runs.
+//! 
+//! A [`Snapshot`] records the metadata of an item along with the size, modification time, and checksums
+//! of each of its files. Snapshots can be [saved][`Snapshot::save`] to disk as JSON, and later [compared][`Snapshot::diff`]
+//! against a newer snapshot to find which files and metadata fields were added, removed, or changed.
+//! 
+//! # Example
+//! ```rust,no_run
+//! use std::path::Path;
+//! use iars::Item;
+//! use iars::item::snapshot::Snapshot;
+//! 
+//! let item = Item::new("test_item")?;
+//! let path = Path::new("audit/test_item.json");
+//! 
+//! let current = item.snapshot()?;
+//! if let Ok(previous) = Snapshot::load(path) {
+//!     let diff = previous.diff(&current);
+//!     if !diff.is_empty() {
+//!         println!("{diff}");
+//!     }
+//! }
+//! 
+//! current.save(path)?;
+//! # Ok::<(), iars::ItemError>(())
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::Formatter;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use crate::item::{Item, ItemError, MetadataResponse};
+use crate::item::metadata::MetaValue;
+
+/// State of an item's files and metadata at a point in time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Identifier of the item.
+    pub identifier: String,
+    
+    /// UNIX epoch timestamp of when the snapshot was taken.
+    pub taken_at: i64,
+    
+    /// The item's metadata fields.
+    pub metadata: BTreeMap<String, MetaValue>,
+    
+    /// State of each file in the item, by path.
+    pub files: BTreeMap<String, FileState>,
+}
+impl Snapshot {
+    /// Creates a snapshot from an item's [metadata record][`Item::metadata`], timestamped with the current time.
+    pub fn from_metadata(identifier: &str, record: &MetadataResponse) -> Self {
+        Self {
+            identifier: identifier.to_string(),
+            taken_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|now| now.as_secs() as i64).unwrap_or_default(),
+            metadata: record.metadata().iter().map(|(key, value)| (key.clone(), value.clone())).collect(),
+            files: record.files().iter().map(|file| (file.name.clone(), FileState {
+                size: file.size,
+                mtime: file.mtime,
+                md5: file.md5.clone(),
+                sha1: file.sha1.clone(),
+            })).collect(),
+        }
+    }
+    
+    /// Reads a snapshot previously written by [`Snapshot::save`].
+    /// 
+    /// # Errors
+    /// Returns an error if the file cannot be read, or does not contain a valid snapshot.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+    
+    /// Writes the snapshot to `path` as JSON, creating any missing parent directories.
+    /// 
+    /// # Errors
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        
+        fs::write(path, serde_json::to_vec_pretty(self)?)
+    }
+    
+    /// Compares this snapshot against a newer snapshot of the item, returning what changed between them.
+    pub fn diff(&self, newer: &Snapshot) -> Diff {
+        let (added_files, removed_files, changed_files) = compare(&self.files, &newer.files);
+        let (added_keys, removed_keys, changed_keys) = compare(&self.metadata, &newer.metadata);
+        
+        Diff {
+            added_files,
+            removed_files,
+            changed_files,
+            added_keys,
+            removed_keys,
+            changed_keys,
+        }
+    }
+}
+
+/// Recorded state of a single file within a [`Snapshot`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileState {
+    /// Size of the file in bytes.
+    pub size: Option<u64>,
+    
+    /// UNIX epoch timestamp of when the file was last modified.
+    pub mtime: Option<i64>,
+    
+    /// MD5 checksum of the file.
+    pub md5: Option<String>,
+    
+    /// SHA-1 checksum of the file.
+    pub sha1: Option<String>,
+}
+
+/// Differences between two [snapshots][`Snapshot`] of an item.
+/// 
+/// Each list is sorted. A file is considered changed if its size, modification time, or checksums
+/// differ, and a metadata field if any of its values differ.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diff {
+    /// Paths of files present only in the newer snapshot.
+    pub added_files: Vec<String>,
+    
+    /// Paths of files present only in the older snapshot.
+    pub removed_files: Vec<String>,
+    
+    /// Paths of files present in both snapshots, whose state differs.
+    pub changed_files: Vec<String>,
+    
+    /// Metadata fields present only in the newer snapshot.
+    pub added_keys: Vec<String>,
+    
+    /// Metadata fields present only in the older snapshot.
+    pub removed_keys: Vec<String>,
+    
+    /// Metadata fields present in both snapshots, whose values differ.
+    pub changed_keys: Vec<String>,
+}
+impl Diff {
+    /// Returns true if nothing changed between the snapshots.
+    pub fn is_empty(&self) -> bool {
+        self.added_files.is_empty() && self.removed_files.is_empty() && self.changed_files.is_empty()
+            && self.added_keys.is_empty() && self.removed_keys.is_empty() && self.changed_keys.is_empty()
+    }
+}
+impl fmt::Display for Diff {
+    /// Lists each difference on its own line, prefixed by `+` (added), `-` (removed), or `~` (changed).
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let lines = [
+            ("+ file", &self.added_files), ("- file", &self.removed_files), ("~ file", &self.changed_files),
+            ("+ metadata", &self.added_keys), ("- metadata", &self.removed_keys), ("~ metadata", &self.changed_keys),
+        ];
+        
+        let mut first = true;
+        for (prefix, names) in lines {
+            for name in names {
+                if !first {
+                    writeln!(f)?;
+                }
+                write!(f, "{prefix} {name}")?;
+                first = false;
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+/// Returns the keys which were added, removed, and changed between two maps.
+fn compare<V: PartialEq>(older: &BTreeMap<String, V>, newer: &BTreeMap<String, V>) -> (Vec<String>, Vec<String>, Vec<String>) {
+    let added = newer.keys().filter(|key| !older.contains_key(*key)).cloned().collect();
+    let removed = older.keys().filter(|key| !newer.contains_key(*key)).cloned().collect();
+    let changed = older.iter()
+        .filter(|(key, value)| newer.get(*key).is_some_and(|newer| newer != *value))
+        .map(|(key, _)| key.clone())
+        .collect();
+    
+    (added, removed, changed)
+}
+
+impl Item {
+    /// Takes a [`Snapshot`] of this item's current files and metadata.
+    /// 
+    /// # Errors
+    /// See [`Item::metadata`].
+    pub fn snapshot(&self) -> Result<Snapshot, ItemError> {
+        Ok(Snapshot::from_metadata(&self.inner.identifier, &self.metadata()?))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,199 @@
+//! Staged uploads, which keep new items out of their final collection until every file has arrived.
+//! 
+//! Items become visible in their collection as soon as they are created by the first upload. For
+//! curated collections, this means visitors can find half-uploaded items, or items whose uploads failed
+//! part way through.
+//! 
+//! A staged upload [`Request`] instead creates the item in a staging collection (`test_collection` by
+//! default), uploads every file, and waits until the Metadata API lists each file with a matching MD5
+//! checksum. Only then is the item's `collection` changed to its final destination.
+//! 
+//! # Example
+//! ```rust,no_run
+//! use iars::{Credentials, Item};
+//! 
+//! let item = Item::new("my_new_item")?
+//!     .with_credentials(Some(Credentials::new("abcdefghijklmnop", "1234567890123456")));
+//! 
+//! let report = item.staged_upload("my_collection")
+//!     .with_meta("mediatype", "audio")
+//!     .with_meta("title", "My New Item")
+//!     .with_file("track01.flac", "album/track01.flac")
+//!     .with_file("track02.flac", "album/track02.flac")
+//!     .call()?;
+//! 
+//! println!("promoted by task {:?}", report.task_id);
+//! # Ok::<(), iars::ItemError>(())
+//! ```
+
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::item::{Item, ItemError};
+use crate::item::sync::md5_file;
+
+/// Request builder for uploading a new item through a staging collection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    item: Item,
+    collection: String,
+    staging: String,
+    meta: Vec<(String, String)>,
+    files: Vec<(String, PathBuf)>,
+    derive: bool,
+    interval: Duration,
+    timeout: Option<Duration>,
+}
+impl Request {
+    /// Sets the collection the item is created in, before it is moved to its final collection.
+    /// 
+    /// This is `test_collection` by default. The [credentials][`Item::with_credentials`] must be able to
+    /// upload into the staging collection, and to move items out of it.
+    pub fn with_staging_collection(mut self, staging: &str) -> Self {
+        self.staging = staging.to_string();
+        
+        self
+    }
+    
+    /// Adds a metadata field to set when the item is created.
+    /// 
+    /// The `collection` field is managed by the staged upload, and is ignored.
+    pub fn with_meta(mut self, key: &str, value: &str) -> Self {
+        self.meta.push((key.to_string(), value.to_string()));
+        
+        self
+    }
+    
+    /// Adds a local file to upload, stored at `filepath` within the item.
+    pub fn with_file(mut self, filepath: &str, local: impl AsRef<Path>) -> Self {
+        self.files.push((filepath.to_string(), local.as_ref().to_path_buf()));
+        
+        self
+    }
+    
+    /// Configures whether or not a derive should be queued after the last file is uploaded.
+    /// 
+    /// This is true (enabled) by default.
+    pub fn with_derive(mut self, derive: bool) -> Self {
+        self.derive = derive;
+        
+        self
+    }
+    
+    /// Sets how often the Metadata API is checked while waiting for the uploaded files to appear.
+    /// 
+    /// This is 30 seconds by default.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        
+        self
+    }
+    
+    /// Sets how long to wait for the uploaded files to appear, or `None` to wait indefinitely.
+    /// 
+    /// This is 1 hour by default.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        
+        self
+    }
+    
+    /// Uploads the files, verifies them, and moves the item into its final collection.
+    /// 
+    /// # Errors
+    /// Returns the first error encountered while uploading or checking the files. The item is left in
+    /// the staging collection whenever an error is returned.
+    /// 
+    /// If the files are not all listed before the [timeout][`Request::with_timeout`], an [`ItemError::Io`]
+    /// of kind [`io::ErrorKind::TimedOut`] is returned. If a listed file's checksum doesn't match the
+    /// local file, an [`ItemError::Io`] of kind [`io::ErrorKind::InvalidData`] is returned.
+    pub fn call(&self) -> Result<Report, ItemError> {
+        let item = self.item.clone().with_auto_make(true);
+        
+        let mut initial_meta: Vec<(&str, &str)> = self.meta.iter()
+            .filter(|(key, _)| key != "collection")
+            .map(|(key, val)| (key.as_str(), val.as_str()))
+            .collect();
+        initial_meta.push(("collection", &self.staging));
+        
+        let mut checksums = vec![];
+        for (i, (name, path)) in self.files.iter().enumerate() {
+            let file = File::open(path)?;
+            let size = file.metadata()?.len() as usize;
+            checksums.push((name, md5_file(path)?));
+            
+            item.upload_file(self.derive && i + 1 == self.files.len(), &initial_meta, name, file, size)?;
+        }
+        
+        self.verify(&checksums)?;
+        
+        let resp = item.modify_metadata()
+            .with_set("collection", &self.collection)
+            .call()?;
+        
+        Ok(Report {
+            files: self.files.iter().map(|(name, _)| name.clone()).collect(),
+            task_id: resp.task_id,
+        })
+    }
+    
+    /// Waits until every uploaded file is listed by the Metadata API with its expected checksum.
+    fn verify(&self, checksums: &[(&String, String)]) -> Result<(), ItemError> {
+        let start = Instant::now();
+        loop {
+            let meta = match self.item.metadata() {
+                Ok(meta) => Some(meta),
+                // the item may not be created until the first upload has been processed
+                Err(ItemError::NotFound { .. }) => None,
+                Err(err) => return Err(err),
+            };
+            
+            let mut pending = 0;
+            for (name, md5) in checksums {
+                match meta.as_ref().and_then(|meta| meta.file(name)).and_then(|file| file.md5.as_ref()) {
+                    Some(remote) if remote.eq_ignore_ascii_case(md5) => (),
+                    Some(remote) => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("checksum of {name} is {remote}, expected {md5}")).into()),
+                    None => pending += 1,
+                }
+            }
+            
+            if pending == 0 {
+                return Ok(());
+            }
+            if self.timeout.is_some_and(|timeout| start.elapsed() + self.interval > timeout) {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, format!("{pending} uploaded files were not listed before the timeout")).into());
+            }
+            
+            thread::sleep(self.interval);
+        }
+    }
+}
+
+/// Summary of a staged upload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    /// Paths of the files which were uploaded and verified.
+    pub files: Vec<String>,
+    
+    /// ID of the task queued to move the item into its final collection.
+    pub task_id: Option<usize>,
+}
+
+impl Item {
+    /// Creates a [staged upload request][`Request`], which creates this item in a staging collection
+    /// and moves it into `collection` once every file has been uploaded and verified.
+    pub fn staged_upload(&self, collection: &str) -> Request {
+        Request {
+            item: self.clone(),
+            collection: collection.to_string(),
+            staging: "test_collection".to_string(),
+            meta: vec![],
+            files: vec![],
+            derive: true,
+            interval: Duration::from_secs(30),
+            timeout: Some(Duration::from_secs(3600)),
+        }
+    }
+}
\ No newline at end of file
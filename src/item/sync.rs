@@ -0,0 +1,652 @@
+//! Synchronization of items with local directories.
+//! 
+//! A sync [`Request`] compares the files of an item with the files in a local directory, and transfers
+//! only the files which are missing or have changed (based on their size and MD5 checksum). Syncing
+//! can be performed in either direction:
+//! * [Downloading][`Request::download`] mirrors the item into the local directory.
+//! * [Uploading][`Request::upload`] pushes new or changed local files to the item.
+//! 
+//! By default, files are never deleted. If [deletion][`Request::with_delete`] is enabled, files which
+//! no longer exist on the source side are deleted from the destination, so both sides end up with the
+//! same set of files.
+//! 
+//! Syncs can be restricted to a subset of files using [include][`Request::with_include`] and
+//! [exclude][`Request::with_exclude`] glob patterns. Files which aren't selected are never transferred
+//! or deleted. Syncs can also be restricted to [original files][`Request::with_originals_only`], which
+//! skips derivatives and the metadata files produced by the Internet Archive. Symbolic links within
+//! the local directory are followed, unless another [policy][`Request::with_symlinks`] is configured.
+//! 
+//! Local files such as `.DS_Store` or build artifacts can be [ignored][`Request::with_ignore`], using
+//! `.gitignore`-style patterns, either added programmatically or listed in an [`IGNORE_FILE`] at the
+//! root of the local directory.
+//! 
+//! After downloading, a local mirror can be [verified][`Request::verify`] by hashing every file and
+//! comparing it against the checksums recorded in the item's `_files.xml`. Verification can also run
+//! [automatically][`Request::with_verify`] after each download.
+//! 
+//! # Example
+//! ```rust,no_run
+//! use std::path::Path;
+//! use iars::{Credentials, Item};
+//! 
+//! let item = Item::new("test_item")?
+//!     .with_credentials(Some(Credentials::new("abcdefghijklmnop", "1234567890123456")));
+//! 
+//! let report = item.sync(Path::new("local/test_item"))
+//!     .with_exclude("*_thumb.jpg")
+//!     .with_delete(true)
+//!     .upload()?;
+//! 
+//! println!("uploaded {} files, deleted {} files", report.transferred.len(), report.deleted.len());
+//! # Ok::<(), iars::ItemError>(())
+//! ```
+
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use md5::{Digest, Md5};
+use crate::glob::{self, Ignore};
+use crate::item::{local_path, FileMetadata, Item, ItemError};
+use crate::item::manifest::Manifest;
+
+/// Name of the file at the root of a local directory which lists `.gitignore`-style patterns of files
+/// to [ignore][`Request::with_ignore`]. The file itself is never uploaded.
+pub const IGNORE_FILE: &str = ".iaignore";
+
+/// Request builder for synchronizing an item with a local directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    item: Item,
+    local: PathBuf,
+    delete: bool,
+    derive: bool,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    originals_only: bool,
+    verify: bool,
+    manifest: Option<String>,
+    symlinks: SymlinkPolicy,
+    ignore: Vec<String>,
+}
+impl Request {
+    /// Configures whether or not files which only exist on the destination side should be deleted.
+    /// 
+    /// When [downloading][`Request::download`], local files which are not part of the item are deleted.
+    /// When [uploading][`Request::upload`], original files of the item which do not exist locally are
+    /// deleted, along with their derivatives. Files produced by the Internet Archive (derivatives and
+    /// metadata files) are never deleted directly.
+    /// 
+    /// This is false (disabled) by default.
+    pub fn with_delete(mut self, delete: bool) -> Self {
+        self.delete = delete;
+        
+        self
+    }
+    
+    /// Configures whether or not a derive should be queued after uploading files.
+    /// 
+    /// A derive is queued at most once per sync, after the last file is uploaded. This is true (enabled)
+    /// by default.
+    pub fn with_derive(mut self, derive: bool) -> Self {
+        self.derive = derive;
+        
+        self
+    }
+    
+    /// Adds a glob pattern of files to include. When any are added, only files matching at least one
+    /// include pattern are synced.
+    /// 
+    /// `*` matches any characters except `/`, `**` matches any characters including `/`, `?` matches
+    /// a single character, and `[abc]` matches one of the listed characters. Patterns without a `/`
+    /// are matched against the file's name, and other patterns against its path within the item.
+    pub fn with_include(mut self, pattern: &str) -> Self {
+        self.include.push(pattern.to_string());
+        
+        self
+    }
+    
+    /// Adds a glob pattern of files to exclude (e.g. `*_thumb.jpg`). Files matching any exclude
+    /// pattern are never synced, even if they match an [include pattern][`Request::with_include`].
+    pub fn with_exclude(mut self, pattern: &str) -> Self {
+        self.exclude.push(pattern.to_string());
+        
+        self
+    }
+    
+    /// Adds a `.gitignore`-style pattern of local files to ignore (e.g. `.DS_Store`, `*.tmp`, or `build/`).
+    /// 
+    /// Ignored files are never uploaded, and are never deleted or reported as [unexpected][`Verification::unexpected`],
+    /// as if they didn't exist locally. Patterns use the same wildcards as [include patterns][`Request::with_include`].
+    /// In addition, a trailing `/` only matches directories, and a leading `!` re-includes paths which
+    /// were ignored by an earlier pattern.
+    /// 
+    /// Patterns are also read from the [`IGNORE_FILE`] at the root of the local directory, if it exists.
+    /// Patterns added using this method take precedence over the ones in the file.
+    pub fn with_ignore(mut self, pattern: &str) -> Self {
+        self.ignore.push(pattern.to_string());
+        
+        self
+    }
+    
+    /// Configures whether or not only [original][`FileMetadata::is_original`] files should be synced.
+    /// 
+    /// When [downloading][`Request::download`], derivatives and metadata files are skipped (and local
+    /// copies of them are never deleted). When [uploading][`Request::upload`], local files which exist
+    /// as derivatives or metadata files of the item are skipped, rather than uploaded as new originals.
+    /// 
+    /// This is false (disabled) by default.
+    pub fn with_originals_only(mut self, originals_only: bool) -> Self {
+        self.originals_only = originals_only;
+        
+        self
+    }
+    
+    /// Configures whether or not the local directory should be [verified][`Request::verify`] after
+    /// [downloading][`Request::download`], storing the result in [`Report::verification`].
+    /// 
+    /// This hashes every selected file again, including files which were already up to date. This is
+    /// false (disabled) by default.
+    pub fn with_verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        
+        self
+    }
+    
+    /// Configures a path within the item where a [`Manifest`] of the uploaded files should be stored.
+    /// 
+    /// When [uploading][`Request::upload`], a manifest listing every selected local file is generated
+    /// and uploaded after the content, whenever any file was transferred or deleted (or the manifest is
+    /// missing from the item). A local file at the same path is never uploaded as content. The item
+    /// can later be checked against the manifest using [`Item::verify_manifest`].
+    /// 
+    /// This is `None` (disabled) by default.
+    pub fn with_manifest(mut self, path: Option<&str>) -> Self {
+        self.manifest = path.map(str::to_string);
+        
+        self
+    }
+    
+    /// Configures how symbolic links within the local directory are handled. See [`SymlinkPolicy`].
+    /// 
+    /// This is [`SymlinkPolicy::Follow`] by default.
+    pub fn with_symlinks(mut self, symlinks: SymlinkPolicy) -> Self {
+        self.symlinks = symlinks;
+        
+        self
+    }
+    
+    /// Downloads every file of the item which is missing from, or has changed in, the local directory.
+    /// 
+    /// Files are downloaded using [`Item::download_file_to_path`], so the item's download options
+    /// (e.g. [preserving mtimes][`Item::with_preserve_mtime`]) apply.
+    /// 
+    /// # Errors
+    /// Returns the first error encountered. Files which were transferred before the error are kept.
+    /// 
+    /// A file name which can't be safely placed within the local directory (e.g. one containing a `..`
+    /// component) fails the download with an [`ItemError::InvalidPath`].
+    pub fn download(&self) -> Result<Report, ItemError> {
+        let meta = self.item.metadata()?;
+        let mut report = Report::default();
+        
+        for file in meta.files().iter().filter(|file| self.selects(&file.name) && (!self.originals_only || file.is_original())) {
+            let path = local_path(&self.local, &file.name)?;
+            if is_unchanged(&path, file)? {
+                report.unchanged.push(file.name.clone());
+                continue;
+            }
+            if is_partial(&path, file) {
+                report.partial.push(file.name.clone());
+            }
+            
+            self.item.download_file_to_path(&file.name, &path)?;
+            report.transferred.push(file.name.clone());
+        }
+        
+        if self.delete {
+            let remote: HashSet<&str> = meta.files().iter().map(|file| file.name.as_str()).collect();
+            for name in self.local_files()? {
+                if self.selects(&name) && !remote.contains(name.as_str()) {
+                    fs::remove_file(local_path(&self.local, &name)?)?;
+                    report.deleted.push(name);
+                }
+            }
+        }
+        
+        if self.verify {
+            report.verification = Some(self.verify_files(meta.files())?);
+        }
+        
+        Ok(report)
+    }
+    
+    /// Verifies the local directory against the item, by hashing every selected local file and comparing
+    /// it against the size and MD5 checksum recorded in the item's `_files.xml` (as reported by the
+    /// Metadata API).
+    /// 
+    /// Nothing is transferred or modified. Files are selected the same way as when [downloading][`Request::download`].
+    /// 
+    /// # Errors
+    /// Returns an error if the item's metadata cannot be retrieved, or a local file cannot be read.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use std::path::Path;
+    /// use iars::Item;
+    /// 
+    /// let verification = Item::new("test_item")?
+    ///     .sync(Path::new("mirror/test_item"))
+    ///     .verify()?;
+    /// 
+    /// for mismatch in &verification.mismatched {
+    ///     println!("{} is corrupt (expected md5 {:?})", mismatch.name, mismatch.expected_md5);
+    /// }
+    /// for name in &verification.missing {
+    ///     println!("{name} is missing");
+    /// }
+    /// # Ok::<(), iars::ItemError>(())
+    /// ```
+    pub fn verify(&self) -> Result<Verification, ItemError> {
+        self.verify_files(self.item.metadata()?.files())
+    }
+    
+    fn verify_files(&self, files: &[FileMetadata]) -> Result<Verification, ItemError> {
+        let mut verification = Verification::default();
+        
+        let selected: Vec<&FileMetadata> = files.iter().filter(|file| self.selects(&file.name) && (!self.originals_only || file.is_original())).collect();
+        for file in &selected {
+            let path = local_path(&self.local, &file.name)?;
+            let size = match fs::metadata(&path) {
+                Ok(meta) => meta.len(),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                    verification.missing.push(file.name.clone());
+                    continue;
+                },
+                Err(err) => return Err(err.into()),
+            };
+            
+            let md5 = md5_file(&path)?;
+            if file.size.is_some_and(|expected| expected != size) || file.md5.as_ref().is_some_and(|expected| !expected.eq_ignore_ascii_case(&md5)) {
+                verification.mismatched.push(Mismatch {
+                    name: file.name.clone(),
+                    expected_size: file.size,
+                    actual_size: size,
+                    expected_md5: file.md5.clone(),
+                    actual_md5: md5,
+                });
+            } else {
+                verification.verified.push(file.name.clone());
+            }
+        }
+        
+        let remote: HashSet<&str> = files.iter().map(|file| file.name.as_str()).collect();
+        verification.unexpected = self.local_files()?.into_iter()
+            .filter(|name| self.selects(name) && !remote.contains(name.as_str()))
+            .collect();
+        
+        Ok(verification)
+    }
+    
+    /// Uploads every local file which is missing from, or has changed in, the item.
+    /// 
+    /// If the item does not exist yet, every local file is uploaded (and the item is created if
+    /// [enabled][`Item::with_auto_make`]). Local paths are [normalized][`normalize_path`] into file paths
+    /// within the item.
+    /// 
+    /// # Errors
+    /// If a local path can't be represented as a file path, an [`ItemError::InvalidPath`] is returned
+    /// before anything is uploaded. Otherwise, returns the first error encountered. Files which were
+    /// transferred before the error are kept.
+    pub fn upload(&self) -> Result<Report, ItemError> {
+        let files = match self.item.metadata() {
+            Ok(meta) => meta.files().to_vec(),
+            Err(ItemError::NotFound { .. }) => vec![],
+            Err(err) => return Err(err),
+        };
+        let mut report = Report::default();
+        
+        let mut changed = vec![];
+        let mut local = self.local_files()?;
+        local.retain(|name| self.selects(name) && self.manifest.as_ref() != Some(name));
+        if self.originals_only {
+            local.retain(|name| files.iter().all(|file| &file.name != name || file.is_original()));
+        }
+        for name in &local {
+            let path = local_path(&self.local, name)?;
+            match files.iter().find(|file| &file.name == name) {
+                Some(file) if is_unchanged(&path, file)? => report.unchanged.push(name.clone()),
+                Some(file) if is_partial(&path, file) => {
+                    report.partial.push(name.clone());
+                    changed.push((name, path));
+                },
+                _ => changed.push((name, path)),
+            }
+        }
+        
+        let count = changed.len();
+        for (i, (name, path)) in changed.into_iter().enumerate() {
+            let file = File::open(&path)?;
+            let size = file.metadata()?.len() as usize;
+            self.item.upload_file(self.derive && i + 1 == count, &[], name, file, size)?;
+            report.transferred.push(name.clone());
+        }
+        
+        if self.delete {
+            let local: HashSet<&str> = local.iter().map(|name| name.as_str()).collect();
+            for file in files.iter().filter(|file| file.is_original() && self.selects(&file.name)) {
+                if !local.contains(file.name.as_str()) {
+                    self.item.delete_file(&file.name, true)?;
+                    report.deleted.push(file.name.clone());
+                }
+            }
+        }
+        
+        if let Some(name) = self.manifest.as_ref() {
+            if !report.transferred.is_empty() || !report.deleted.is_empty() || files.iter().all(|file| &file.name != name) {
+                let mut buf = vec![];
+                Manifest::from_files(&self.local, &local)?.write(&mut buf)?;
+                self.item.upload_file(false, &[], name, buf.as_slice(), buf.len())?;
+                report.transferred.push(name.clone());
+            }
+        }
+        
+        Ok(report)
+    }
+    
+    fn selects(&self, name: &str) -> bool {
+        glob::selects(&self.include, &self.exclude, name)
+    }
+    
+    /// Lists the files within the local directory, skipping those which are [ignored][`Request::with_ignore`].
+    fn local_files(&self) -> Result<Vec<String>, ItemError> {
+        let mut ignore = Ignore::default();
+        ignore.add(&format!("/{IGNORE_FILE}"));
+        match fs::read_to_string(self.local.join(IGNORE_FILE)) {
+            Ok(text) => ignore.add_lines(&text),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => (),
+            Err(err) => return Err(err.into()),
+        }
+        self.ignore.iter().for_each(|pattern| ignore.add(pattern));
+        
+        local_files(&self.local, self.symlinks, &ignore)
+    }
+}
+
+/// Handling of symbolic links found while listing the files of a local directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// Links are treated as the file or directory they point to, so linked files are synced under the
+    /// link's path. A link to a directory containing it would repeat forever, so it fails with an
+    /// [`ItemError::InvalidPath`] instead.
+    Follow,
+    
+    /// Links are ignored, as if they didn't exist.
+    Skip,
+    
+    /// Any link fails the sync with an [`ItemError::InvalidPath`]. When [uploading][`Request::upload`],
+    /// this happens before anything is transferred.
+    Error,
+}
+
+/// Summary of the files affected by a sync.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Report {
+    /// Paths of files which were downloaded or uploaded.
+    pub transferred: Vec<String>,
+    
+    /// Paths of files which were deleted from the destination.
+    pub deleted: Vec<String>,
+    
+    /// Paths of files which were already up to date.
+    pub unchanged: Vec<String>,
+    
+    /// Paths of files which existed on both sides, but with different sizes (e.g. because an earlier
+    /// transfer was interrupted). These were transferred again, and are also included in [`Report::transferred`].
+    pub partial: Vec<String>,
+    
+    /// Result of [verifying][`Request::with_verify`] the local directory after downloading, if enabled.
+    pub verification: Option<Verification>,
+}
+
+/// Result of [verifying][`Request::verify`] a local directory against an item.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Verification {
+    /// Paths of files whose size and checksum match the item.
+    pub verified: Vec<String>,
+    
+    /// Files whose size or checksum differs from the item.
+    pub mismatched: Vec<Mismatch>,
+    
+    /// Paths of files in the item which don't exist locally.
+    pub missing: Vec<String>,
+    
+    /// Paths of local files which are not part of the item.
+    pub unexpected: Vec<String>,
+}
+impl Verification {
+    /// Returns true if every file of the item exists locally and matches, ignoring [unexpected][`Verification::unexpected`]
+    /// local files.
+    pub fn is_ok(&self) -> bool {
+        self.mismatched.is_empty() && self.missing.is_empty()
+    }
+}
+
+/// A local file which doesn't match the item, found during [verification][`Request::verify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    /// Path of the file within the item.
+    pub name: String,
+    
+    /// Size of the file according to the item, if recorded.
+    pub expected_size: Option<u64>,
+    
+    /// Size of the local file.
+    pub actual_size: u64,
+    
+    /// MD5 checksum of the file according to the item, if recorded.
+    pub expected_md5: Option<String>,
+    
+    /// MD5 checksum of the local file.
+    pub actual_md5: String,
+}
+
+impl Item {
+    /// Creates a [sync request][`Request`] between this item and a local directory.
+    pub fn sync(&self, local: &Path) -> Request {
+        Request {
+            item: self.clone(),
+            local: local.to_path_buf(),
+            delete: false,
+            derive: true,
+            include: vec![],
+            exclude: vec![],
+            originals_only: false,
+            verify: false,
+            manifest: None,
+            symlinks: SymlinkPolicy::Follow,
+            ignore: vec![],
+        }
+    }
+}
+
+/// Converts a local path, relative to the directory being uploaded, into a file path within an item.
+/// 
+/// Backslashes are treated as separators, and Windows drive prefixes (e.g. `C:`), UNC prefixes (e.g.
+/// `\\server\share`), verbatim prefixes (e.g. `\\?\`), and leading separators are removed. Empty and
+/// `.` components are dropped.
+/// 
+/// # Errors
+/// Returns [`ItemError::InvalidPath`] if the path isn't valid Unicode, is empty once normalized, contains
+/// a `..` component, or contains a control character, `?`, or `#` (which would be misinterpreted as
+/// part of the upload URL).
+/// 
+/// # Example
+/// ```rust
+/// use std::path::Path;
+/// use iars::item::sync::normalize_path;
+/// 
+/// assert_eq!(normalize_path(Path::new(r"C:\scans\page 1.png"))?, "scans/page 1.png");
+/// assert_eq!(normalize_path(Path::new(r"\\server\share\./notes.txt"))?, "notes.txt");
+/// assert!(normalize_path(Path::new("../outside.txt")).is_err());
+/// # Ok::<(), iars::ItemError>(())
+/// ```
+pub fn normalize_path(path: &Path) -> Result<String, ItemError> {
+    let invalid = |reason| ItemError::InvalidPath { path: path.to_path_buf(), reason };
+    let path = path.to_str().ok_or_else(|| invalid("not valid Unicode"))?.replace('\\', "/");
+    
+    let (unc, mut rest) = match path.strip_prefix("//?/").or_else(|| path.strip_prefix("//./")) {
+        Some(rest) => match rest.strip_prefix("UNC/") {
+            Some(rest) => (true, rest),
+            None => (false, rest),
+        },
+        None => match path.strip_prefix("//") {
+            Some(rest) => (true, rest),
+            None => (false, path.as_str()),
+        },
+    };
+    if unc {
+        // skips the server and share names
+        rest = rest.splitn(3, '/').nth(2).unwrap_or("");
+    }
+    if let [drive, b':', ..] = rest.as_bytes() {
+        if drive.is_ascii_alphabetic() {
+            rest = &rest[2..];
+        }
+    }
+    
+    let mut parts = vec![];
+    for part in rest.split('/') {
+        match part {
+            "" | "." => (),
+            ".." => return Err(invalid("contains a `..` component")),
+            _ if part.chars().any(|c| c.is_control() || c == '?' || c == '#') => return Err(invalid("contains a reserved character")),
+            _ => parts.push(part),
+        }
+    }
+    if parts.is_empty() {
+        return Err(invalid("is empty"));
+    }
+    
+    Ok(parts.join("/"))
+}
+
+/// Lists every file within a local directory, as [normalized][`normalize_path`] paths relative to the directory.
+/// 
+/// Ignored files and directories are skipped before their paths are normalized, so they never cause
+/// an [`ItemError::InvalidPath`].
+fn local_files(root: &Path, symlinks: SymlinkPolicy, ignore: &Ignore) -> Result<Vec<String>, ItemError> {
+    /// `ancestors` holds the canonical paths of the directories being walked, to detect link cycles.
+    fn walk(root: &Path, dir: &Path, symlinks: SymlinkPolicy, ignore: &Ignore, ancestors: &mut Vec<PathBuf>, out: &mut Vec<String>) -> Result<(), ItemError> {
+        ancestors.push(dir.canonicalize()?);
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            let name = relative.to_string_lossy().replace('\\', "/");
+            
+            let mut file_type = entry.file_type()?;
+            if file_type.is_symlink() {
+                match symlinks {
+                    SymlinkPolicy::Follow => file_type = fs::metadata(&path)?.file_type(),
+                    SymlinkPolicy::Skip => continue,
+                    SymlinkPolicy::Error if ignore.is_ignored(&name, path.is_dir()) => continue,
+                    SymlinkPolicy::Error => return Err(ItemError::InvalidPath { path, reason: "is a symbolic link" }),
+                }
+            }
+            if ignore.is_ignored(&name, file_type.is_dir()) {
+                continue;
+            }
+            
+            if file_type.is_dir() {
+                if ancestors.contains(&path.canonicalize()?) {
+                    return Err(ItemError::InvalidPath { path, reason: "is a symbolic link to a directory containing it" });
+                }
+                walk(root, &path, symlinks, ignore, ancestors, out)?;
+            } else if path.extension().is_none_or(|ext| ext != "iars-part") {
+                out.push(normalize_path(relative)?);
+            }
+        }
+        ancestors.pop();
+        
+        Ok(())
+    }
+    
+    let mut files = vec![];
+    if root.is_dir() {
+        walk(root, root, symlinks, ignore, &mut vec![], &mut files)?;
+    }
+    
+    Ok(files)
+}
+
+/// Returns true if the local file exists and matches the size and MD5 checksum of the remote file.
+fn is_unchanged(path: &Path, remote: &FileMetadata) -> Result<bool, ItemError> {
+    let len = match fs::metadata(path) {
+        Ok(meta) => meta.len(),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(err.into()),
+    };
+    
+    if remote.size.is_some_and(|size| size != len) {
+        return Ok(false);
+    }
+    
+    match remote.md5.as_ref() {
+        Some(md5) => Ok(md5_file(path)?.eq_ignore_ascii_case(md5)),
+        None => Ok(true),
+    }
+}
+
+/// Returns true if the local file exists, but its size differs from the size of the remote file.
+fn is_partial(path: &Path, remote: &FileMetadata) -> bool {
+    fs::metadata(path).is_ok_and(|meta| remote.size.is_some_and(|size| size != meta.len()))
+}
+
+/// Calculates the MD5 checksum of a local file, as a hex string.
+pub(crate) fn md5_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Md5::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn normalizes_paths() {
+        for (path, normalized) in [
+            ("scans/page 1.png", "scans/page 1.png"),
+            ("/scans//./page.png/", "scans/page.png"),
+            (r"scans\sub\page.png", "scans/sub/page.png"),
+            (r"C:\scans\page.png", "scans/page.png"),
+            ("c:page.png", "page.png"),
+            (r"\\server\share\scans\page.png", "scans/page.png"),
+            (r"\\?\D:\scans\page.png", "scans/page.png"),
+            (r"\\?\UNC\server\share\page.png", "page.png"),
+            (r"\\.\D:\page.png", "page.png"),
+            ("notes..txt", "notes..txt"),
+            ("1:2.txt", "1:2.txt"),
+        ] {
+            assert_eq!(normalize_path(Path::new(path)).unwrap(), normalized, "{path:?}");
+        }
+    }
+    
+    #[test]
+    fn rejects_invalid_paths() {
+        for path in ["", "/", "./.", "C:", r"\\server\share", "../outside.txt", "scans/../page.png", "page?.png", "page#1.png", "page\t.png"] {
+            assert!(matches!(normalize_path(Path::new(path)), Err(ItemError::InvalidPath { .. })), "{path:?} was accepted");
+        }
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn rejects_non_unicode_paths() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+        
+        let path = Path::new(OsStr::from_bytes(b"page\xff.png"));
+        assert!(matches!(normalize_path(path), Err(ItemError::InvalidPath { reason: "not valid Unicode", .. })));
+    }
+}
\ No newline at end of file
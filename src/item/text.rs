@@ -0,0 +1,109 @@
+//! Retrieval of the text (OCR) derivatives of texts items.
+//! 
+//! When books and other documents are derived, the Internet Archive produces several text files from
+//! the OCR of each page. [`Item::download_text`] locates the best of these available in an item and
+//! downloads it, decompressing it if necessary.
+//! 
+//! # Example
+//! ```rust,no_run
+//! use std::fs::File;
+//! use iars::Item;
+//! use iars::item::text::TextFormat;
+//! 
+//! let item = Item::new("test_book")?;
+//! 
+//! let text = item.download_text(&[TextFormat::Plain, TextFormat::Hocr], File::create("test_book.txt")?)?;
+//! println!("downloaded {} ({:?}, {} bytes)", text.name, text.format, text.size);
+//! # Ok::<(), iars::ItemError>(())
+//! ```
+
+use std::io::Write;
+use crate::item::{FileMetadata, Item, ItemError, MetadataResponse};
+
+/// Format of a text derivative.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextFormat {
+    /// Plain text of every page (`_djvu.txt`).
+    Plain,
+    
+    /// hOCR HTML, including the position of each word (`_hocr.html`).
+    Hocr,
+    
+    /// hOCR HTML, including the position of each character (`_chocr.html.gz`).
+    CharHocr,
+    
+    /// DjVu XML, including the position of each word (`_djvu.xml`).
+    DjvuXml,
+}
+impl TextFormat {
+    /// Every format, in the order they are preferred by default.
+    pub const ALL: [TextFormat; 4] = [Self::Plain, Self::Hocr, Self::DjvuXml, Self::CharHocr];
+    
+    /// Returns true if the file is a text derivative of this format.
+    pub fn matches(&self, file: &FileMetadata) -> bool {
+        let (format, suffix) = match self {
+            Self::Plain => ("DjVuTXT", "_djvu.txt"),
+            Self::Hocr => ("hOCR", "_hocr.html"),
+            Self::CharHocr => ("Character hOCR GZ", "_chocr.html.gz"),
+            Self::DjvuXml => ("Djvu XML", "_djvu.xml"),
+        };
+        
+        file.format.as_deref() == Some(format) || file.name.ends_with(suffix)
+    }
+}
+
+/// Summary of a downloaded text derivative.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextDownload {
+    /// Path of the downloaded file within the item.
+    pub name: String,
+    
+    /// Format of the downloaded file.
+    pub format: TextFormat,
+    
+    /// Number of bytes written, after decompression.
+    pub size: u64,
+}
+
+impl MetadataResponse {
+    /// Returns the first text derivative found in the order of `preference`.
+    /// 
+    /// If `preference` is empty, the [default order][`TextFormat::ALL`] is used.
+    pub fn text_file(&self, preference: &[TextFormat]) -> Option<(TextFormat, &FileMetadata)> {
+        let preference = if preference.is_empty() { &TextFormat::ALL[..] } else { preference };
+        
+        preference.iter().find_map(|format| {
+            self.files.iter()
+                .find(|file| format.matches(file))
+                .map(|file| (*format, file))
+        })
+    }
+}
+
+impl Item {
+    /// Downloads the item's best available text derivative into the `writer`.
+    /// 
+    /// Formats are tried in the order of `preference`, or the [default order][`TextFormat::ALL`] if it
+    /// is empty. Gzipped derivatives are always decompressed.
+    /// 
+    /// # Errors
+    /// If the item has none of the preferred formats, an [`ItemError::NotFound`] is returned. See
+    /// [`Item::download_file`] for other errors.
+    pub fn download_text(&self, preference: &[TextFormat], writer: impl Write) -> Result<TextDownload, ItemError> {
+        let meta = self.metadata()?;
+        let Some((format, file)) = meta.text_file(preference) else {
+            let url = format!("https://archive.org/metadata/{}", self.inner.identifier);
+            return Err(ItemError::NotFound { context: self.context("download_text", &url) });
+        };
+        
+        let size = self.clone()
+            .with_gzip_decompression(true)
+            .download_file(&file.name, writer)?;
+        
+        Ok(TextDownload {
+            name: file.name.clone(),
+            format,
+            size,
+        })
+    }
+}
\ No newline at end of file
@@ -4,14 +4,23 @@
 //! |Supported|Name|Endpoint|
 //! |:-------:|:--:|--------|
 //! | Yes | IAS3 (S3-like) ([API docs](https://archive.org/developers/ias3.html)) |`https://s3.us.archive.org/{identifier}`|
-//! | Read-only | Metadata ([API docs](https://archive.org/developers/metadata.html)) |`https://archive.org/metadata/{identifier}`|
-//! | No | Views ([API docs](https://archive.org/developers/views_api.html)) |`https://be-api.us.archive.org/views/v1/short/{identifier}[,...]`|
-//! | No | Reviews ([API docs](https://archive.org/developers/reviews.html)) |`https://archive.org/services/reviews.php`|
-//! | No | Changes ([API docs](https://archive.org/developers/changes.html)) |`https://be-api.us.archive.org/changes/v1`|
-//! | Partial | Tasks ([API docs](https://archive.org/developers/tasks.html)) |`https://archive.org/services/tasks.php`|
+//! | Yes | Metadata ([API docs](https://archive.org/developers/metadata.html)) |`https://archive.org/metadata/{identifier}`|
+//! | Yes | Views ([API docs](https://archive.org/developers/views_api.html)) |`https://be-api.us.archive.org/views/v1/short/{identifier}[,...]`|
+//! | Read-only | Reviews ([API docs](https://archive.org/developers/reviews.html)) |`https://archive.org/services/reviews.php`|
+//! | Read-only | Related Items |`https://be-api.us.archive.org/mds/v1/get_related/all/{identifier}`|
+//! | Yes | Changes ([API docs](https://archive.org/developers/changes.html)) |`https://be-api.us.archive.org/changes/v1`|
+//! | Partial | Scrape (search) ([API docs](https://archive.org/services/swagger/?url=%2Fservices%2Fsearch%2Fv1%2Fswagger.yaml)) |`https://archive.org/services/search/v1/scrape`|
+//! | Yes | Tasks ([API docs](https://archive.org/developers/tasks.html)) |`https://archive.org/services/tasks.php`|
+//! | Yes | Wayback Availability ([API docs](https://archive.org/help/wayback_api.php)) |`https://archive.org/wayback/available`|
+//! | Yes | Wayback TimeMap ([Memento](https://mementoweb.org/guide/rfc/)) |`https://web.archive.org/web/timemap/link/{url}`|
+//! | Partial | Save Page Now |`https://web.archive.org/save`|
+//! | Yes | IIIF Image & Presentation ([API docs](https://iiif.archive.org/iiif/documentation)) |`https://iiif.archive.org/iiif/3/{identifier}/manifest.json`|
+//! | Read-only | Open Library ([API docs](https://openlibrary.org/developers/api)) |`https://openlibrary.org/books/{olid}.json`|
+//! | Read-only | Scholar search ([API docs](https://scholar.archive.org/help)) |`https://scholar.archive.org/search`|
 //! 
-//! The IAS3, Metadata, Views, and Reviews APIs are accessible through the [`Item`] data type. The
-//! remaining APIs are accessed via their respective module ([`changes`], and [`tasks`]).
+//! The IAS3, Metadata, Views, Reviews, and Related Items APIs are accessible through the [`Item`] data type. The
+//! remaining APIs are accessed via their respective module ([`changes`], [`iiif`], [`openlibrary`], [`scholar`], [`search`], [`tasks`], and [`wayback`]).
+//! The commonly used types of every module can be imported at once using `use iars::prelude::*`.
 //! 
 //! # Authentication
 //! Generally, any operations that modify or upload files to the Internet Archive will require authentication.
@@ -19,10 +28,15 @@
 //! 
 //! Cookies are technically accepted by the Internet Archive for authentication, however they are intended
 //! only for use in a browser environment. As this crate is intended for programmatic access of their APIs,
-//! only key authentication is available.
+//! key authentication is used for everything else. The one exception is downloading access-restricted files
+//! (e.g. stream-only or lending files), which some items only allow with a logged-in session. For these,
+//! [`SessionCookies`] can be opted into using `Item::with_cookies`.
 //! 
 //! To acquire your own S3-like keys, log into <https://archive.org/> and then proceed to the [API Key page](https://archive.org/account/s3.php).
 //! 
+//! Credentials can also be stored in the platform's keyring (macOS Keychain, Windows Credential Manager,
+//! or the Secret Service on Linux) by enabling the `keyring` feature. See `Credentials::save_to_keyring`.
+//! 
 //! # S3-like API
 //! Also refered to the `ias3`, this API is responsible for providing read and write access to the
 //! files that make up an item on the Internet Archive. It is refered to as S3-like because each item
@@ -33,6 +47,29 @@
 //! (authentication doesn't work). The API differs in [several ways](https://archive.org/developers/ias3.html#how-this-is-different-from-normal-s3),
 //! and includes numerous custom HTTP headers which affect the behavior of each request.
 //! 
+//! # Features
+//! Parsing of XML responses is enabled by the default `xml` feature, which is only required by
+//! `Item::list`, `Item::list_originals`, and the `item::multipart` module. Applications which only
+//! upload whole files can disable default features to avoid compiling an XML parser ([quick-xml](https://crates.io/crates/quick-xml)).
+//! 
+//! Parsing of JSON responses is enabled by the default `json` feature. Most of the APIs respond with JSON, so
+//! it's required by every module other than `client`, `headers`, `item`, `metrics`, `stats`, and `warc`, by
+//! the `Item` methods which read metadata, reviews, views, or tasks, and by the submodules of `item` other
+//! than `builder` and `multipart`. Without it, files can still be uploaded, copied, downloaded, and deleted
+//! (and listed, if `xml` is enabled) without compiling [serde_json](https://crates.io/crates/serde_json).
+//! The `vcr` and `testing` features enable `json`.
+//! 
+//! Enabling the `zeroize` feature overwrites the keys of `Credentials` and the values of `SessionCookies`
+//! with zeros when they're dropped, so key material doesn't linger in freed memory. As the types then
+//! implement [`Drop`], their fields can no longer be moved out of them.
+//! 
+//! # Testing
+//! Enabling the `vcr` feature adds the `vcr` module, which records real responses to fixture files and
+//! replays them later, so code built on `iars` can be tested without contacting the Internet Archive.
+//! 
+//! For end-to-end tests, the `testing` feature adds the `testing` module, which manages uniquely named
+//! items in `test_collection` and deletes them once a test has finished.
+//! 
 //! # Why not async?
 //! Using async often severely increases the number of dependencies required to use a crate, while
 //! simultaneously increasing the complexity of its development and usage.
@@ -42,24 +79,100 @@
 //! to benefit from async.
 //! 
 //! As such, all HTTP requests are performed using [ureq] which subscribes to [a similar mindset][ureq#blocking-io-for-simplicity].
+//! 
+//! This also rules out `wasm32-unknown-unknown`. Browsers only offer an asynchronous `fetch`, which can't
+//! be waited on from the blocking API, and ureq's `Request`, `Response`, and `Error` types are part of that
+//! API (e.g. [`Item::upload_file`] returns a `ureq::Response`).
 
+#![allow(clippy::result_large_err, clippy::large_enum_variant)]
+
+use std::fmt;
+use std::fmt::Formatter;
+use std::str::FromStr;
+use std::sync::RwLock;
 use crate::headers::Header;
 
+#[cfg(feature = "json")]
+pub mod bulk;
+#[cfg(feature = "json")]
 pub mod changes;
+pub mod client;
+#[cfg(feature = "json")]
+mod glob;
 pub mod headers;
+#[cfg(feature = "json")]
+pub mod iiif;
 pub mod item;
+pub mod metrics;
+#[cfg(feature = "json")]
+pub mod openlibrary;
+pub mod prelude;
+#[cfg(feature = "json")]
+pub mod related;
+#[cfg(feature = "json")]
+pub mod reviews;
+#[cfg(feature = "json")]
+pub mod scholar;
+#[cfg(feature = "json")]
+pub mod search;
+pub mod stats;
+#[cfg(feature = "json")]
 pub mod tasks;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "json")]
+pub mod views;
+#[cfg(feature = "vcr")]
+pub mod vcr;
+pub mod warc;
+#[cfg(feature = "json")]
+pub mod wayback;
 
+pub use client::Client;
 pub use item::{Item, ItemError};
 
-/// `User-Agent` string used by default for all API requests.
-pub const DEFAULT_USER_AGENT: &'static str = "iars <https://crates.io/crates/iars>";
+/// `User-Agent` string used by default for all API requests, unless [overridden][`set_default_user_agent`].
+pub const DEFAULT_USER_AGENT: &str = "iars <https://crates.io/crates/iars>";
+
+static USER_AGENT_OVERRIDE: RwLock<Option<String>> = RwLock::new(None);
+
+/// Sets the `User-Agent` string used by every [`Item`] and request builder created afterwards, in
+/// place of [`DEFAULT_USER_AGENT`].
+/// 
+/// This allows an application to identify itself once, instead of calling `with_useragent` at every
+/// construction site. Items and builders which already exist keep the `User-Agent` they were created
+/// with, and a `User-Agent` provided using `with_useragent` still takes precedence.
+/// 
+/// If `None` or if the string is empty, [`DEFAULT_USER_AGENT`] is restored.
+/// 
+/// # Example
+/// ```rust
+/// iars::set_default_user_agent(Some("my-archiver/1.0 (admin@example.com)".to_string()));
+/// 
+/// assert_eq!(iars::default_user_agent(), "my-archiver/1.0 (admin@example.com)");
+/// ```
+pub fn set_default_user_agent(useragent: Option<String>) {
+    *USER_AGENT_OVERRIDE.write().unwrap_or_else(|err| err.into_inner()) = useragent.filter(|useragent| !useragent.is_empty());
+}
+
+/// Returns the `User-Agent` string used when none is provided: either the one set by [`set_default_user_agent`],
+/// or [`DEFAULT_USER_AGENT`].
+pub fn default_user_agent() -> String {
+    USER_AGENT_OVERRIDE.read().unwrap_or_else(|err| err.into_inner())
+        .clone()
+        .unwrap_or_else(|| DEFAULT_USER_AGENT.to_string())
+}
 
 
 /// Container for authentication keys required by portions of the Internet Archive API.
 /// 
 /// Users can get these API keys from <https://archive.org/account/s3.php>.
-#[derive(Debug, Clone, PartialEq)]
+/// 
+/// The secret key is redacted from this type's [`Debug`][`std::fmt::Debug`] output. If the `zeroize`
+/// feature is enabled, both keys are also overwritten with zeros when the credentials (or any clone of
+/// them) are dropped. Copies made while sending a request, such as the `authorization` header given
+/// to [ureq], are not wiped.
+#[derive(Clone, PartialEq)]
 pub struct Credentials {
     pub access: String,
     pub secret: String,
@@ -95,7 +208,189 @@ impl Credentials {
             secret,
         })
     }
+    
+    /// Attempts to load credentials from a named profile in the shared credentials file used by the AWS CLI.
+    /// 
+    /// The file is read from the path set by the `AWS_SHARED_CREDENTIALS_FILE` environment variable if it
+    /// is set, and otherwise from `.aws/credentials` within the home directory (`HOME`, or `USERPROFILE`
+    /// if `HOME` is not set). Each profile is a section of the file, named either `[name]` or
+    /// `[profile name]`, containing both keys:
+    /// 
+    /// ```ini
+    /// [default]
+    /// aws_access_key_id = abcdefghijklmnop
+    /// aws_secret_access_key = 1234567890123456
+    /// 
+    /// [work]
+    /// aws_access_key_id = qrstuvwxyzabcdef
+    /// aws_secret_access_key = 6543210987654321
+    /// ```
+    /// 
+    /// Blank lines, and lines starting with `#` or `;`, are skipped. Other keys (e.g. `aws_session_token`)
+    /// are ignored. If a key appears more than once within the profile, including in a repeated section,
+    /// the last value is used.
+    /// 
+    /// `None` will be returned if the file cannot be read, the profile does not exist, or either key
+    /// is missing or empty.
+    pub fn from_profile(profile: &str) -> Option<Self> {
+        let path = match std::env::var_os("AWS_SHARED_CREDENTIALS_FILE") {
+            Some(path) => std::path::PathBuf::from(path),
+            None => std::path::PathBuf::from(std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?)
+                .join(".aws")
+                .join("credentials"),
+        };
+        
+        let contents = std::fs::read_to_string(path).ok()?;
+        
+        let mut section = "";
+        let mut access = None;
+        let mut secret = None;
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            
+            if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+                section = name.trim();
+                section = section.strip_prefix("profile ").unwrap_or(section).trim();
+            } else if section == profile {
+                match line.split_once('=').map(|(key, val)| (key.trim(), val.trim())) {
+                    Some(("aws_access_key_id", val)) => access = Some(val.to_string()),
+                    Some(("aws_secret_access_key", val)) => secret = Some(val.to_string()),
+                    _ => (),
+                }
+            }
+        }
+        
+        let (access, secret) = (access?, secret?);
+        if access.is_empty() || secret.is_empty() {
+            return None;
+        }
+        
+        Some(Self {
+            access,
+            secret,
+        })
+    }
+    
+    /// Attempts to load credentials previously [saved][`Credentials::save_to_keyring`] to the platform's keyring.
+    /// 
+    /// `Ok(None)` will be returned if no credentials have been saved.
+    /// 
+    /// # Errors
+    /// Any [`keyring::Error`] encountered while accessing the keyring is returned, such as when the
+    /// keyring is locked or unavailable.
+    #[cfg(feature = "keyring")]
+    pub fn from_keyring() -> Result<Option<Self>, keyring::Error> {
+        let password = match keyring_entry()?.get_password() {
+            Ok(password) => password,
+            Err(keyring::Error::NoEntry) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        
+        match password.split_once(':') {
+            Some((access, secret)) => Ok(Some(Self::new(access, secret))),
+            None => Err(keyring::Error::BadEncoding(password.into_bytes())),
+        }
+    }
+    
+    /// Saves these credentials to the platform's keyring, replacing any credentials saved previously.
+    /// 
+    /// The keyring is provided by the operating system (e.g. macOS Keychain, Windows Credential Manager,
+    /// or the Secret Service on Linux), allowing applications to avoid storing keys in plaintext.
+    /// 
+    /// # Errors
+    /// Any [`keyring::Error`] encountered while accessing the keyring is returned, such as when the
+    /// keyring is locked or unavailable.
+    #[cfg(feature = "keyring")]
+    pub fn save_to_keyring(&self) -> Result<(), keyring::Error> {
+        keyring_entry()?.set_password(&format!("{}:{}", self.access, self.secret))
+    }
+    
+    /// Removes any credentials saved to the platform's keyring.
+    /// 
+    /// # Errors
+    /// Any [`keyring::Error`] encountered while accessing the keyring is returned, except for when
+    /// no credentials were saved.
+    #[cfg(feature = "keyring")]
+    pub fn delete_from_keyring() -> Result<(), keyring::Error> {
+        match keyring_entry()?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // the secret key grants write access to the account, so it is never printed
+        f.debug_struct("Credentials")
+            .field("access", &self.access)
+            .field("secret", &"<redacted>")
+            .finish()
+    }
+}
+#[cfg(feature = "zeroize")]
+impl Drop for Credentials {
+    fn drop(&mut self) {
+        use zeroize::Zeroize;
+        
+        self.access.zeroize();
+        self.secret.zeroize();
+    }
+}
+
+/// Keyring entry that [`Credentials`] are saved under.
+#[cfg(feature = "keyring")]
+fn keyring_entry() -> Result<keyring::Entry, keyring::Error> {
+    keyring::Entry::new("iars", "default")
+}
+
+/// Cookies of a logged-in archive.org session, used to download access-restricted files.
+/// 
+/// The values can be copied from the `logged-in-user` and `logged-in-sig` cookies of a browser which is
+/// logged into <https://archive.org/>. They are only sent when [downloading files][`Item::download_file`],
+/// and only to Internet Archive hosts.
+/// 
+/// Like [`Credentials`], both values are overwritten with zeros when dropped if the `zeroize` feature is
+/// enabled, but the `cookie` header built from them for each download is not.
+#[derive(Clone, PartialEq)]
+pub struct SessionCookies {
+    pub logged_in_user: String,
+    pub logged_in_sig: String,
+}
+impl SessionCookies {
+    /// Creates a new [`SessionCookies`] instance using the values of the `logged-in-user` and `logged-in-sig` cookies.
+    pub fn new(logged_in_user: &str, logged_in_sig: &str) -> Self {
+        Self {
+            logged_in_user: logged_in_user.into(),
+            logged_in_sig: logged_in_sig.into(),
+        }
+    }
+}
+impl std::fmt::Debug for SessionCookies {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // the signature grants full access to the account, so it is never printed
+        f.debug_struct("SessionCookies")
+            .field("logged_in_user", &self.logged_in_user)
+            .finish_non_exhaustive()
+    }
+}
+#[cfg(feature = "zeroize")]
+impl Drop for SessionCookies {
+    fn drop(&mut self) {
+        use zeroize::Zeroize;
+        
+        self.logged_in_user.zeroize();
+        self.logged_in_sig.zeroize();
+    }
+}
+impl From<&SessionCookies> for Header {
+    fn from(value: &SessionCookies) -> Self {
+        Header::Cookie(format!("logged-in-user={}; logged-in-sig={}", value.logged_in_user, value.logged_in_sig))
+    }
 }
+
 impl From<&Credentials> for Header {
     fn from(value: &Credentials) -> Self {
         Header::Authorization {
@@ -129,4 +424,68 @@ pub fn validate_identifier(ident: &str) -> bool {
     }
     
     true
+}
+
+/// An item identifier which has been [validated][`validate_identifier`].
+/// 
+/// Identifiers can be parsed from strings (e.g. command line arguments or config files) using [`str::parse`]
+/// or [`TryFrom`], and converted into an [`Item`] without validating them again.
+/// 
+/// # Example
+/// ```rust
+/// use iars::{Identifier, Item};
+/// 
+/// let identifier: Identifier = "test_item".parse()?;
+/// let item = Item::from(identifier);
+/// assert_eq!(item.identifier(), "test_item");
+/// 
+/// assert!(Identifier::try_from("-not valid").is_err());
+/// # Ok::<(), iars::ItemError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Identifier(String);
+impl Identifier {
+    /// Returns the identifier as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+    
+    /// Returns the identifier as an owned string.
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+impl TryFrom<String> for Identifier {
+    type Error = ItemError;
+    
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match validate_identifier(&value) {
+            true => Ok(Self(value)),
+            false => Err(ItemError::InvalidIdentifier(value)),
+        }
+    }
+}
+impl TryFrom<&str> for Identifier {
+    type Error = ItemError;
+    
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::try_from(value.to_string())
+    }
+}
+impl FromStr for Identifier {
+    type Err = ItemError;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s)
+    }
+}
+impl AsRef<str> for Identifier {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
 }
\ No newline at end of file
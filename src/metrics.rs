@@ -0,0 +1,195 @@
+//! Counters of requests, retries, transferred bytes, and errors.
+//! 
+//! A [`Metrics`] handle can be attached to a [`Client`][`crate::Client`] using [`Client::with_metrics`][`crate::Client::with_metrics`].
+//! Every request performed through that client (including each retry and redirect) is then counted,
+//! by HTTP method and host, along with the bytes of file uploads and downloads and any errors, by
+//! [category][`ErrorCategory`].
+//! 
+//! Counters only ever increase, and can be read at any time (e.g. from another thread) as a [`Counters`]
+//! snapshot, or rendered in the [Prometheus text format](https://prometheus.io/docs/instrumenting/exposition_formats/)
+//! to be served from an application's own metrics endpoint.
+//! 
+//! # Example
+//! ```rust,no_run
+//! use iars::{Client, Item};
+//! use iars::metrics::Metrics;
+//! 
+//! let metrics = Metrics::new();
+//! let item = Item::new("test_item")?
+//!     .with_client(Client::new().with_metrics(metrics.clone()));
+//! 
+//! item.download_file("large_file.bin", std::io::sink())?;
+//! 
+//! println!("{}", metrics.to_prometheus());
+//! # Ok::<(), iars::ItemError>(())
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::{Formatter, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use crate::stats::Direction;
+
+/// Category of a failed request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ErrorCategory {
+    /// The request failed before a response was received (e.g. a connection or timeout error).
+    Connection,
+    
+    /// The server responded with `429 Too Many Requests`.
+    Throttled,
+    
+    /// The server responded with any other `4xx` status code.
+    Client,
+    
+    /// The server responded with a `5xx` status code.
+    Server,
+    
+    /// The request was not sent, because the host's [circuit breaker][`crate::Client::with_circuit_breaker`] was open.
+    CircuitOpen,
+}
+impl ErrorCategory {
+    /// Categorizes an error returned by a request.
+    pub(crate) fn of(err: &ureq::Error) -> Self {
+        match err {
+            ureq::Error::Status(429, _) => Self::Throttled,
+            ureq::Error::Status(500.., _) => Self::Server,
+            ureq::Error::Status(_, _) => Self::Client,
+            ureq::Error::Transport(_) => Self::Connection,
+        }
+    }
+}
+impl fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", match self {
+            Self::Connection => "connection",
+            Self::Throttled => "throttled",
+            Self::Client => "client",
+            Self::Server => "server",
+            Self::CircuitOpen => "circuit_open",
+        })
+    }
+}
+
+/// Counters of the requests sent to a single host using a single HTTP method.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OperationCounters {
+    /// Number of requests sent, including retries and redirects.
+    pub requests: u64,
+    
+    /// Number of requests which failed.
+    pub errors: u64,
+    
+    /// Total time spent waiting for responses. File transfers only include the time until the response
+    /// headers were received.
+    pub duration: Duration,
+}
+
+/// Snapshot of the counters recorded by a [`Metrics`] handle.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Counters {
+    /// Total number of requests sent, including retries and redirects.
+    pub requests: u64,
+    
+    /// Number of [automatic retries][`crate::client#retries`].
+    pub retries: u64,
+    
+    /// Total bytes of file uploads sent.
+    pub bytes_up: u64,
+    
+    /// Total bytes of file downloads received.
+    pub bytes_down: u64,
+    
+    /// Number of failed requests, by category.
+    pub errors: BTreeMap<ErrorCategory, u64>,
+    
+    /// Counters of each HTTP method and host, keyed by `(method, host)`.
+    pub operations: BTreeMap<(String, String), OperationCounters>,
+}
+
+/// Handle for recording request metrics.
+/// 
+/// Clones of a handle share the same counters.
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    counters: Arc<Mutex<Counters>>,
+}
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Returns a snapshot of every counter.
+    pub fn counters(&self) -> Counters {
+        self.lock().clone()
+    }
+    
+    /// Renders every counter in the Prometheus text exposition format, with names prefixed by `iars_`.
+    pub fn to_prometheus(&self) -> String {
+        let counters = self.counters();
+        let mut out = String::new();
+        
+        let _ = writeln!(out, "# TYPE iars_requests_total counter");
+        for ((method, host), op) in &counters.operations {
+            let _ = writeln!(out, "iars_requests_total{{method=\"{method}\",host=\"{host}\"}} {}", op.requests);
+        }
+        
+        let _ = writeln!(out, "# TYPE iars_request_failures_total counter");
+        for ((method, host), op) in &counters.operations {
+            let _ = writeln!(out, "iars_request_failures_total{{method=\"{method}\",host=\"{host}\"}} {}", op.errors);
+        }
+        
+        let _ = writeln!(out, "# TYPE iars_request_duration_seconds_total counter");
+        for ((method, host), op) in &counters.operations {
+            let _ = writeln!(out, "iars_request_duration_seconds_total{{method=\"{method}\",host=\"{host}\"}} {}", op.duration.as_secs_f64());
+        }
+        
+        let _ = writeln!(out, "# TYPE iars_errors_total counter");
+        for (category, count) in &counters.errors {
+            let _ = writeln!(out, "iars_errors_total{{category=\"{category}\"}} {count}");
+        }
+        
+        let _ = writeln!(out, "# TYPE iars_retries_total counter");
+        let _ = writeln!(out, "iars_retries_total {}", counters.retries);
+        
+        let _ = writeln!(out, "# TYPE iars_transferred_bytes_total counter");
+        let _ = writeln!(out, "iars_transferred_bytes_total{{direction=\"up\"}} {}", counters.bytes_up);
+        let _ = writeln!(out, "iars_transferred_bytes_total{{direction=\"down\"}} {}", counters.bytes_down);
+        
+        out
+    }
+    
+    /// Records a request which received a response (or failed) after `elapsed`.
+    pub(crate) fn add_request(&self, method: &str, host: &str, error: Option<ErrorCategory>, elapsed: Duration) {
+        let mut counters = self.lock();
+        counters.requests += 1;
+        
+        let op = counters.operations.entry((method.to_string(), host.to_string())).or_default();
+        op.requests += 1;
+        op.duration += elapsed;
+        if let Some(category) = error {
+            op.errors += 1;
+            *counters.errors.entry(category).or_default() += 1;
+        }
+    }
+    
+    pub(crate) fn add_error(&self, category: ErrorCategory) {
+        *self.lock().errors.entry(category).or_default() += 1;
+    }
+    
+    pub(crate) fn add_retry(&self) {
+        self.lock().retries += 1;
+    }
+    
+    pub(crate) fn add_bytes(&self, direction: Direction, len: usize) {
+        match direction {
+            Direction::Upload => self.lock().bytes_up += len as u64,
+            Direction::Download => self.lock().bytes_down += len as u64,
+        }
+    }
+    
+    fn lock(&self) -> std::sync::MutexGuard<'_, Counters> {
+        self.counters.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
\ No newline at end of file
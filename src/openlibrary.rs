@@ -0,0 +1,467 @@
+//! Lookup of books through the [Open Library API](https://openlibrary.org/developers/api).
+//! 
+//! Open Library is operated by the Internet Archive, and catalogs books as [works][`Work`] (the
+//! abstract book) and [editions][`Edition`] (a specific publication of it). Many texts items on the
+//! Internet Archive are scans of an edition, which is linked back to the item by [`Edition::ocaid`].
+//! 
+//! Records can be retrieved by ISBN or by Open Library ID (OLID, e.g. `OL7353617M` for an edition, or
+//! `OL45804W` for a work), and [covers][`cover_url`] can be downloaded at several sizes. An edition's
+//! [metadata][`Edition::metadata`] can be used as the initial metadata of an item when uploading its scan.
+//! 
+//! # Example
+//! ```rust,no_run
+//! use iars::openlibrary::{self, CoverKey, CoverSize};
+//! 
+//! let lookup = openlibrary::lookup();
+//! if let Some(edition) = lookup.isbn("9780140328721")? {
+//!     println!("{:?} published by {:?}", edition.title, edition.publishers);
+//! 
+//!     let work = lookup.work(&edition.work_olids()[0])?;
+//!     println!("{:?}", work.and_then(|work| work.description().map(str::to_string)));
+//! 
+//!     if let Some(isbn) = edition.isbn() {
+//!         lookup.cover(&CoverKey::Isbn(isbn.to_string()), CoverSize::Large, std::fs::File::create("cover.jpg")?)?;
+//!     }
+//! }
+//! # Ok::<(), iars::openlibrary::OpenLibraryError>(())
+//! ```
+
+use std::fmt;
+use std::fmt::Formatter;
+use std::io::Write;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use crate::default_user_agent;
+use crate::client::{self, Client, RequestContext};
+
+const API: &str = "https://openlibrary.org";
+const COVERS_API: &str = "https://covers.openlibrary.org/b";
+
+/// Creates a new [`Lookup`] using the shared client and default User-Agent.
+pub fn lookup() -> Lookup {
+    Lookup::new()
+}
+
+/// Errors which may occur while looking up records on Open Library.
+/// 
+/// Errors produced by a request include the [`RequestContext`] of that request, identifying the
+/// operation and URL involved.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum OpenLibraryError {
+    /// An error while performing [`std::io`] operations.
+    /// 
+    /// `context` is `None` if the error did not occur during a request.
+    Io {
+        context: Option<RequestContext>,
+        source: std::io::Error,
+    },
+    
+    /// An error while processing a [`ureq`] request.
+    Ureq {
+        context: RequestContext,
+        source: ureq::Error,
+    },
+    
+    /// A [`ureq`] request was successful, but returned a 429 Too Many Requests error code.
+    /// 
+    /// This is only returned after any [automatic retries][`crate::client`] have been exhausted. Use
+    /// [`ResponseError::retry_after`][`crate::client::ResponseError::retry_after`] to find out how long the server asked the client to wait.
+    TooManyRequests {
+        context: RequestContext,
+        response: ureq::Response,
+    },
+}
+impl OpenLibraryError {
+    /// Returns the context of the request this error occurred during, if any.
+    pub fn context(&self) -> Option<&RequestContext> {
+        match self {
+            Self::Io { context, .. } => context.as_ref(),
+            Self::Ureq { context, .. } | Self::TooManyRequests { context, .. } => Some(context),
+        }
+    }
+    
+    pub(crate) fn io(context: &RequestContext, source: std::io::Error) -> Self {
+        Self::Io { context: Some(context.clone()), source }
+    }
+    
+    pub(crate) fn ureq(context: &RequestContext, source: ureq::Error) -> Self {
+        let context = context.with_error(&source);
+        match source {
+            ureq::Error::Status(429, response) => Self::TooManyRequests { context, response },
+            _ => Self::Ureq { context, source },
+        }
+    }
+}
+impl fmt::Display for OpenLibraryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { context: Some(context), source } => write!(f, "{context}: {source}"),
+            Self::Io { context: None, source } => write!(f, "{source}"),
+            Self::Ureq { context, source } => write!(f, "{context}: {source}"),
+            Self::TooManyRequests { context, .. } => write!(f, "{context}: 429 Too Many Requests"),
+        }
+    }
+}
+impl std::error::Error for OpenLibraryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Ureq { source, .. } => Some(source),
+            Self::TooManyRequests { .. } => None,
+        }
+    }
+}
+impl client::sealed::FailedResponse for OpenLibraryError {
+    fn failed_response(&self) -> Option<&ureq::Response> {
+        match self {
+            Self::Ureq { source: ureq::Error::Status(_, resp), .. } | Self::TooManyRequests { response: resp, .. } => Some(resp),
+            _ => None,
+        }
+    }
+    
+    fn failed_context(&self) -> Option<&RequestContext> {
+        self.context()
+    }
+    
+    fn failed_transport(&self) -> Option<&ureq::Transport> {
+        match self {
+            Self::Ureq { source: ureq::Error::Transport(transport), .. } => Some(transport),
+            _ => None,
+        }
+    }
+}
+impl From<std::io::Error> for OpenLibraryError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io { context: None, source: value }
+    }
+}
+
+/// Identifies the book a cover image belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoverKey {
+    /// ID of the cover image itself, as listed in [`Edition::covers`] or [`Work::covers`].
+    Id(i64),
+    
+    /// ISBN-10 or ISBN-13 of an edition.
+    Isbn(String),
+    
+    /// OLID of an edition.
+    Olid(String),
+}
+
+/// Size of a cover image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoverSize {
+    Small,
+    Medium,
+    Large,
+}
+
+/// Returns the URL of a cover image.
+/// 
+/// If no such cover exists, the URL serves a blank image. See [`Lookup::cover`] to detect missing covers instead.
+pub fn cover_url(key: &CoverKey, size: CoverSize) -> String {
+    let (kind, value) = match key {
+        CoverKey::Id(id) => ("id", id.to_string()),
+        CoverKey::Isbn(isbn) => ("isbn", isbn.replace('-', "")),
+        CoverKey::Olid(olid) => ("olid", olid_of(olid).to_string()),
+    };
+    let size = match size {
+        CoverSize::Small => "S",
+        CoverSize::Medium => "M",
+        CoverSize::Large => "L",
+    };
+    
+    format!("{COVERS_API}/{kind}/{value}-{size}.jpg")
+}
+
+/// Builder for looking up records in Open Library.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lookup {
+    useragent: String,
+    client: Client,
+}
+impl Lookup {
+    /// Creates a lookup using the shared client and default User-Agent.
+    pub fn new() -> Self {
+        Self {
+            useragent: default_user_agent(),
+            client: Client::shared(),
+        }
+    }
+    
+    /// Configures the User-Agent string provided in each request.
+    /// 
+    /// If `None` or if the string is empty, a [default][`default_user_agent`] will be used. Open Library
+    /// asks that applications making many requests identify themselves with a contact address.
+    pub fn with_useragent(mut self, useragent: Option<String>) -> Self {
+        self.useragent = match useragent {
+            Some(useragent) if !useragent.is_empty() => useragent,
+            _ => default_user_agent(),
+        };
+        
+        self
+    }
+    
+    /// Configures the [`Client`] used to perform each request.
+    /// 
+    /// By default, the [shared client][`Client::shared`] is used.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        
+        self
+    }
+    
+    /// Retrieves the edition with the given ISBN-10 or ISBN-13. Hyphens are ignored.
+    /// 
+    /// If no edition has the ISBN, `None` is returned.
+    /// 
+    /// # Errors
+    /// This may return [`OpenLibraryError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    /// 
+    /// If any [I/O errors][`std::io::Error`] occur or the response fails to be deserialized, an [`OpenLibraryError::Io`] is returned.
+    pub fn isbn(&self, isbn: &str) -> Result<Option<Edition>, OpenLibraryError> {
+        self.get("isbn", &format!("{API}/isbn/{}.json", isbn.replace('-', "")))
+    }
+    
+    /// Retrieves the edition with the given OLID (e.g. `OL7353617M` or `/books/OL7353617M`).
+    /// 
+    /// If the edition doesn't exist, `None` is returned.
+    /// 
+    /// # Errors
+    /// See [`Lookup::isbn`].
+    pub fn edition(&self, olid: &str) -> Result<Option<Edition>, OpenLibraryError> {
+        self.get("edition", &format!("{API}/books/{}.json", olid_of(olid)))
+    }
+    
+    /// Retrieves the work with the given OLID (e.g. `OL45804W` or `/works/OL45804W`).
+    /// 
+    /// If the work doesn't exist, `None` is returned.
+    /// 
+    /// # Errors
+    /// See [`Lookup::isbn`].
+    pub fn work(&self, olid: &str) -> Result<Option<Work>, OpenLibraryError> {
+        self.get("work", &format!("{API}/works/{}.json", olid_of(olid)))
+    }
+    
+    /// Retrieves every edition of the work with the given OLID, requesting further pages as needed.
+    /// 
+    /// If the work doesn't exist, an empty list is returned.
+    /// 
+    /// # Errors
+    /// See [`Lookup::isbn`].
+    pub fn editions(&self, work_olid: &str) -> Result<Vec<Edition>, OpenLibraryError> {
+        #[derive(Deserialize)]
+        struct EditionsResponse {
+            #[serde(default)]
+            entries: Vec<Edition>,
+            #[serde(default)]
+            links: Links,
+        }
+        
+        #[derive(Default, Deserialize)]
+        struct Links {
+            next: Option<String>,
+        }
+        
+        let mut editions = vec![];
+        let mut url = format!("{API}/works/{}/editions.json", olid_of(work_olid));
+        while let Some(page) = self.get::<EditionsResponse>("editions", &url)? {
+            let fetched = page.entries.len();
+            editions.extend(page.entries);
+            
+            match page.links.next {
+                Some(next) if fetched > 0 => url = format!("{API}{next}"),
+                _ => break,
+            }
+        }
+        
+        Ok(editions)
+    }
+    
+    /// Downloads a cover image into the `writer`.
+    /// 
+    /// On success, the number of bytes written is returned. If no such cover exists, `None` is returned
+    /// and nothing is written.
+    /// 
+    /// # Errors
+    /// This may return [`OpenLibraryError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    /// 
+    /// If any [I/O errors][`std::io::Error`] occur while transfering data into the `writer`, an [`OpenLibraryError::Io`] is returned.
+    pub fn cover(&self, key: &CoverKey, size: CoverSize, mut writer: impl Write) -> Result<Option<u64>, OpenLibraryError> {
+        let url = cover_url(key, size);
+        let context = RequestContext::new("cover", &url, None);
+        let req = self.client.request("GET", &url)
+            .query("default", "false")
+            .set("user-agent", &self.useragent);
+        
+        let download = match self.client.call_download(req) {
+            Ok(download) => download,
+            Err(ureq::Error::Status(404, _)) => return Ok(None),
+            Err(err) => return Err(OpenLibraryError::ureq(&context, err)),
+        };
+        
+        self.client.copy(download.into_reader(), &mut writer)
+            .map(Some)
+            .map_err(|err| OpenLibraryError::io(&context, err))
+    }
+    
+    /// Requests a JSON record, treating `404 Not Found` as `None`.
+    fn get<T: DeserializeOwned>(&self, operation: &'static str, url: &str) -> Result<Option<T>, OpenLibraryError> {
+        let context = RequestContext::new(operation, url, None);
+        let req = self.client.request("GET", url)
+            .set("user-agent", &self.useragent);
+        
+        match self.client.call(req) {
+            Ok(resp) => client::read_json(resp.into_reader()).map(Some).map_err(|err| OpenLibraryError::io(&context, err)),
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(err) => Err(OpenLibraryError::ureq(&context, err)),
+        }
+    }
+}
+impl Default for Lookup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reference to another record, such as an author or language.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Key {
+    /// Path of the referenced record (e.g. `/authors/OL34184A`).
+    pub key: String,
+}
+
+/// A specific publication of a book.
+/// 
+/// Fields which aren't modeled here are kept in [`extra`][`Edition::extra`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Edition {
+    /// Path of the edition (e.g. `/books/OL7353617M`).
+    pub key: String,
+    
+    pub title: Option<String>,
+    pub subtitle: Option<String>,
+    
+    #[serde(default)]
+    pub authors: Vec<Key>,
+    
+    /// The works this is an edition of. Usually exactly one.
+    #[serde(default)]
+    pub works: Vec<Key>,
+    
+    #[serde(default)]
+    pub publishers: Vec<String>,
+    
+    /// Publication date, as written in the book (e.g. `October 1, 1988`).
+    pub publish_date: Option<String>,
+    
+    #[serde(default)]
+    pub isbn_10: Vec<String>,
+    
+    #[serde(default)]
+    pub isbn_13: Vec<String>,
+    
+    pub number_of_pages: Option<u32>,
+    
+    /// Languages of the edition (e.g. `/languages/eng`).
+    #[serde(default)]
+    pub languages: Vec<Key>,
+    
+    /// IDs of the edition's cover images. See [`CoverKey::Id`].
+    #[serde(default)]
+    pub covers: Vec<i64>,
+    
+    /// Identifier of the Internet Archive item containing a scan of this edition, if any.
+    pub ocaid: Option<String>,
+    
+    /// All other fields of the edition.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+impl Edition {
+    /// Returns the OLID of the edition (e.g. `OL7353617M`).
+    pub fn olid(&self) -> &str {
+        olid_of(&self.key)
+    }
+    
+    /// Returns the OLIDs of the works this is an edition of.
+    pub fn work_olids(&self) -> Vec<String> {
+        self.works.iter().map(|work| olid_of(&work.key).to_string()).collect()
+    }
+    
+    /// Returns the edition's first ISBN-13, or its first ISBN-10 if it has none.
+    pub fn isbn(&self) -> Option<&str> {
+        self.isbn_13.first().or(self.isbn_10.first()).map(String::as_str)
+    }
+    
+    /// Converts the edition into item metadata, suitable for the initial metadata of an upload
+    /// (see [`Item::upload_file`][`crate::Item::upload_file`]).
+    /// 
+    /// The fields `title`, `publisher`, `date`, `isbn`, `language`, `openlibrary_edition`, and
+    /// `openlibrary_work` are included when known. Only the first value of each is used.
+    pub fn metadata(&self) -> Vec<(String, String)> {
+        let fields = [
+            ("title", self.title.clone()),
+            ("publisher", self.publishers.first().cloned()),
+            ("date", self.publish_date.clone()),
+            ("isbn", self.isbn().map(str::to_string)),
+            ("language", self.languages.first().map(|language| olid_of(&language.key).to_string())),
+            ("openlibrary_edition", Some(self.olid().to_string())),
+            ("openlibrary_work", self.work_olids().into_iter().next()),
+        ];
+        
+        fields.into_iter()
+            .filter_map(|(key, value)| Some((key.to_string(), value?)))
+            .collect()
+    }
+}
+
+/// The abstract book which [editions][`Edition`] are publications of.
+/// 
+/// Fields which aren't modeled here are kept in [`extra`][`Work::extra`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Work {
+    /// Path of the work (e.g. `/works/OL45804W`).
+    pub key: String,
+    
+    pub title: Option<String>,
+    pub subtitle: Option<String>,
+    
+    #[serde(default)]
+    pub subjects: Vec<String>,
+    
+    /// IDs of the work's cover images. See [`CoverKey::Id`].
+    #[serde(default)]
+    pub covers: Vec<i64>,
+    
+    pub first_publish_date: Option<String>,
+    
+    #[serde(default)]
+    description: Option<Value>,
+    
+    /// All other fields of the work.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+impl Work {
+    /// Returns the OLID of the work (e.g. `OL45804W`).
+    pub fn olid(&self) -> &str {
+        olid_of(&self.key)
+    }
+    
+    /// Returns the work's description, if it has one.
+    pub fn description(&self) -> Option<&str> {
+        match self.description.as_ref()? {
+            Value::Object(text) => text.get("value")?.as_str(),
+            value => value.as_str(),
+        }
+    }
+}
+
+/// Returns the last segment of a record path (e.g. `OL45804W` from `/works/OL45804W`).
+fn olid_of(key: &str) -> &str {
+    key.rsplit('/').next().unwrap_or(key)
+}
\ No newline at end of file
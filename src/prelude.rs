@@ -0,0 +1,46 @@
+//! Re-exports of the most commonly used types, for glob importing.
+//! 
+//! Types are only included if their names are unambiguous outside of their module, so request
+//! builders (which are usually named `Request`) are left out. They are created using the functions
+//! of their module instead, such as [`search::scrape`][`crate::search::scrape()`].
+//! 
+//! # Example
+//! ```rust,no_run
+//! use iars::prelude::*;
+//! 
+//! let item = Item::new("test_item")?
+//!     .with_credentials(Credentials::try_from_env());
+//! 
+//! # #[cfg(feature = "json")] {
+//! for file in item.metadata()?.files() {
+//!     println!("{} ({:?} bytes)", file.name, file.size);
+//! }
+//! 
+//! let resp = iars::search::scrape("collection:nasa").call(None)?;
+//! println!("{:?} items in the collection", resp.total);
+//! # }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+pub use crate::{Client, Credentials, Identifier, Item, ItemError, SessionCookies, validate_identifier};
+pub use crate::client::{QuotaInfo, RawResponse, RequestContext, ResponseError};
+#[cfg(feature = "json")]
+pub use crate::changes::ChangesError;
+#[cfg(feature = "json")]
+pub use crate::iiif::IiifError;
+#[cfg(feature = "json")]
+pub use crate::item::{FileMetadata, MetadataResponse};
+#[cfg(feature = "json")]
+pub use crate::item::metadata::MetaValue;
+#[cfg(feature = "json")]
+pub use crate::openlibrary::OpenLibraryError;
+#[cfg(feature = "json")]
+pub use crate::scholar::ScholarError;
+#[cfg(feature = "json")]
+pub use crate::search::{SearchDoc, SearchError};
+#[cfg(feature = "json")]
+pub use crate::tasks::{Command, Priority, Status, TaskError};
+#[cfg(feature = "json")]
+pub use crate::views::ViewsError;
+#[cfg(feature = "json")]
+pub use crate::wayback::WaybackError;
\ No newline at end of file
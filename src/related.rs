@@ -0,0 +1,54 @@
+//! Recommendations of items related to an item.
+//! 
+//! The same service which powers the "Related Items" section of an item's details page can be queried
+//! using [`Item::related`][`crate::Item::related`]. Recommendations are ranked by a relevance score.
+
+use serde::Deserialize;
+use serde_json::Value;
+
+/// A single item recommended as related to another item.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RelatedItem {
+    /// Identifier of the recommended item.
+    #[serde(rename = "_id")]
+    pub identifier: String,
+    
+    /// Relevance score of the recommendation. Higher scores are more relevant.
+    #[serde(rename = "_score", default)]
+    pub score: f64,
+    
+    /// Partial metadata of the recommended item, as provided by the service.
+    #[serde(rename = "_source", default)]
+    pub source: Value,
+}
+impl RelatedItem {
+    /// Title of the recommended item, if provided.
+    pub fn title(&self) -> Option<&str> {
+        first_str(self.source.get("title")?)
+    }
+    
+    /// Mediatype of the recommended item (e.g. "texts", "movies"), if provided.
+    pub fn mediatype(&self) -> Option<&str> {
+        first_str(self.source.get("mediatype")?)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct RelatedResponse {
+    #[serde(default)]
+    pub(crate) hits: RelatedHits,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct RelatedHits {
+    #[serde(default)]
+    pub(crate) hits: Vec<RelatedItem>,
+}
+
+/// Fields are provided either as a single string or as a list of strings.
+fn first_str(value: &Value) -> Option<&str> {
+    match value {
+        Value::Array(values) => values.first()?.as_str(),
+        value => value.as_str(),
+    }
+}
\ No newline at end of file
@@ -0,0 +1,43 @@
+//! User reviews of archive items.
+//! 
+//! Items on the Internet Archive can be reviewed by any logged-in user. Each review consists of a
+//! title, a body, and an optional star rating.
+//! 
+//! All reviews of an item are included in its [metadata record][`crate::item::MetadataResponse::reviews`],
+//! or can be retrieved on their own using [`Item::reviews`][`crate::Item::reviews`].
+
+use serde::Deserialize;
+use crate::item::deserialize_number;
+
+/// A single review of an item.
+/// 
+/// See also: [API Docs](https://archive.org/developers/md-record.html#reviews-field)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Review {
+    /// Title of the review.
+    #[serde(rename = "reviewtitle", default)]
+    pub title: String,
+    
+    /// Body text of the review.
+    #[serde(rename = "reviewbody", default)]
+    pub body: String,
+    
+    /// Screen name of the user who wrote the review.
+    #[serde(default)]
+    pub reviewer: String,
+    
+    /// Identifier of the reviewer's user item (e.g. `@username`), if known.
+    pub reviewer_itemname: Option<String>,
+    
+    /// Rating given by the reviewer, from 1 to 5. `None` if no rating was given.
+    #[serde(default, deserialize_with = "deserialize_number")]
+    pub stars: Option<u8>,
+    
+    /// Date and time the review was last modified (e.g. `2023-12-30 12:34:56`).
+    #[serde(rename = "reviewdate")]
+    pub review_date: Option<String>,
+    
+    /// Date and time the review was originally created.
+    #[serde(rename = "createdate")]
+    pub create_date: Option<String>,
+}
\ No newline at end of file
@@ -0,0 +1,516 @@
+//! Searching scholarly works through [Internet Archive Scholar](https://scholar.archive.org/).
+//! 
+//! Scholar indexes the fulltext and bibliographic metadata of research papers, reports, and datasets
+//! preserved by the Internet Archive, using the [fatcat](https://fatcat.wiki/) catalog. Searches use
+//! the same [query syntax](https://scholar.archive.org/help) as the search bar on the site, and results
+//! can be narrowed down by [type][`Request::with_type`], [time][`Request::with_time`], and [availability][`Request::with_availability`].
+//! 
+//! Results are paginated using an offset. A single page can be retrieved using [`Request::call`], or
+//! every result can be iterated over using [`Request::results`]. Scholar refuses to page deeper than
+//! a fixed number of results (see [`Response::deep_page_limit`]), at which point iteration ends.
+//! 
+//! # Example
+//! ```rust,no_run
+//! use iars::scholar::{self, Availability, Sort};
+//! 
+//! let resp = scholar::search("\"internet archive\" preservation")
+//!     .with_availability(Availability::Fulltext)
+//!     .with_sort(Sort::Recent)
+//!     .call()?;
+//! 
+//! println!("{} works found", resp.count_found);
+//! for doc in &resp.results {
+//!     println!("{:?} ({:?})", doc.biblio.title, doc.biblio.release_year);
+//! }
+//! # Ok::<(), iars::scholar::ScholarError>(())
+//! ```
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::fmt::Formatter;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use crate::default_user_agent;
+use crate::client::{self, Client, RequestContext};
+
+const SEARCH_API: &str = "https://scholar.archive.org/search";
+
+/// Creates a new [search request][`Request`] for works matching `query`.
+pub fn search(query: &str) -> Request {
+    Request::new(query)
+}
+
+/// Errors which may occur while searching Scholar.
+/// 
+/// Errors produced by a request include the [`RequestContext`] of that request, identifying the
+/// operation and URL involved.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ScholarError {
+    /// An error while performing [`std::io`] operations.
+    /// 
+    /// `context` is `None` if the error did not occur during a request.
+    Io {
+        context: Option<RequestContext>,
+        source: std::io::Error,
+    },
+    
+    /// An error while processing a [`ureq`] request.
+    Ureq {
+        context: RequestContext,
+        source: ureq::Error,
+    },
+    
+    /// A [`ureq`] request was successful, but returned a 429 Too Many Requests error code.
+    /// 
+    /// This is only returned after any [automatic retries][`crate::client`] have been exhausted. Use
+    /// [`ResponseError::retry_after`][`crate::client::ResponseError::retry_after`] to find out how long the server asked the client to wait.
+    TooManyRequests {
+        context: RequestContext,
+        response: ureq::Response,
+    },
+}
+impl ScholarError {
+    /// Returns the context of the request this error occurred during, if any.
+    pub fn context(&self) -> Option<&RequestContext> {
+        match self {
+            Self::Io { context, .. } => context.as_ref(),
+            Self::Ureq { context, .. } | Self::TooManyRequests { context, .. } => Some(context),
+        }
+    }
+    
+    pub(crate) fn io(context: &RequestContext, source: std::io::Error) -> Self {
+        Self::Io { context: Some(context.clone()), source }
+    }
+    
+    pub(crate) fn ureq(context: &RequestContext, source: ureq::Error) -> Self {
+        let context = context.with_error(&source);
+        match source {
+            ureq::Error::Status(429, response) => Self::TooManyRequests { context, response },
+            _ => Self::Ureq { context, source },
+        }
+    }
+}
+impl fmt::Display for ScholarError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { context: Some(context), source } => write!(f, "{context}: {source}"),
+            Self::Io { context: None, source } => write!(f, "{source}"),
+            Self::Ureq { context, source } => write!(f, "{context}: {source}"),
+            Self::TooManyRequests { context, .. } => write!(f, "{context}: 429 Too Many Requests"),
+        }
+    }
+}
+impl std::error::Error for ScholarError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Ureq { source, .. } => Some(source),
+            Self::TooManyRequests { .. } => None,
+        }
+    }
+}
+impl client::sealed::FailedResponse for ScholarError {
+    fn failed_response(&self) -> Option<&ureq::Response> {
+        match self {
+            Self::Ureq { source: ureq::Error::Status(_, resp), .. } | Self::TooManyRequests { response: resp, .. } => Some(resp),
+            _ => None,
+        }
+    }
+    
+    fn failed_context(&self) -> Option<&RequestContext> {
+        self.context()
+    }
+    
+    fn failed_transport(&self) -> Option<&ureq::Transport> {
+        match self {
+            Self::Ureq { source: ureq::Error::Transport(transport), .. } => Some(transport),
+            _ => None,
+        }
+    }
+}
+impl From<std::io::Error> for ScholarError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io { context: None, source: value }
+    }
+}
+
+/// Kind of works to include in the results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkType {
+    /// Journal articles, conference proceedings, preprints, and similar works. This is the default.
+    Papers,
+    
+    /// Reports, such as technical or government reports.
+    Reports,
+    
+    /// Datasets.
+    Datasets,
+    
+    /// Every kind of work.
+    Everything,
+}
+impl fmt::Display for WorkType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", match self {
+            Self::Papers => "papers",
+            Self::Reports => "reports",
+            Self::Datasets => "datasets",
+            Self::Everything => "everything",
+        })
+    }
+}
+
+/// Publication time of works to include in the results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeRange {
+    /// Any publication time. This is the default.
+    AllTime,
+    PastWeek,
+    PastYear,
+    Since2000,
+    Before1925,
+}
+impl fmt::Display for TimeRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", match self {
+            Self::AllTime => "all_time",
+            Self::PastWeek => "past_week",
+            Self::PastYear => "past_year",
+            Self::Since2000 => "since_2000",
+            Self::Before1925 => "before_1925",
+        })
+    }
+}
+
+/// Availability of works to include in the results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Availability {
+    /// Every work, including those only known from bibliographic metadata.
+    Everything,
+    
+    /// Works with a preserved fulltext copy. This is the default.
+    Fulltext,
+    
+    /// Works with a preserved fulltext copy which is open access.
+    OpenAccess,
+}
+impl fmt::Display for Availability {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", match self {
+            Self::Everything => "everything",
+            Self::Fulltext => "fulltext",
+            Self::OpenAccess => "oa",
+        })
+    }
+}
+
+/// Order of the results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sort {
+    /// Most relevant first. This is the default.
+    Relevance,
+    
+    /// Most recently published first.
+    Recent,
+    
+    /// Oldest first.
+    Oldest,
+}
+impl fmt::Display for Sort {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", match self {
+            Self::Relevance => "relevancy",
+            Self::Recent => "time_desc",
+            Self::Oldest => "time_asc",
+        })
+    }
+}
+
+/// Request builder for searching Internet Archive Scholar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    useragent: String,
+    client: Client,
+    query: String,
+    work_type: Option<WorkType>,
+    time: Option<TimeRange>,
+    availability: Option<Availability>,
+    sort: Option<Sort>,
+    limit: usize,
+    offset: usize,
+}
+impl Request {
+    /// Default number of results per page.
+    pub const DEFAULT_LIMIT: usize = 15;
+    
+    /// Maximum number of results per page accepted by Scholar.
+    pub const MAX_LIMIT: usize = 100;
+    
+    /// Creates a request for works matching `query`.
+    pub fn new(query: &str) -> Self {
+        Self {
+            useragent: default_user_agent(),
+            client: Client::shared(),
+            query: query.to_string(),
+            work_type: None,
+            time: None,
+            availability: None,
+            sort: None,
+            limit: Self::DEFAULT_LIMIT,
+            offset: 0,
+        }
+    }
+    
+    /// Configures the User-Agent string provided in this request.
+    /// 
+    /// If `None` or if the string is empty, a [default][`default_user_agent`] will be used.
+    pub fn with_useragent(mut self, useragent: Option<String>) -> Self {
+        self.useragent = match useragent {
+            Some(useragent) if !useragent.is_empty() => useragent,
+            _ => default_user_agent(),
+        };
+        
+        self
+    }
+    
+    /// Configures the [`Client`] used to perform this request.
+    /// 
+    /// By default, the [shared client][`Client::shared`] is used.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        
+        self
+    }
+    
+    /// Kind of works to include. If not provided, only [papers][`WorkType::Papers`] are included.
+    pub fn with_type(mut self, work_type: WorkType) -> Self {
+        self.work_type = Some(work_type);
+        
+        self
+    }
+    
+    /// Publication time of works to include. If not provided, works from any time are included.
+    pub fn with_time(mut self, time: TimeRange) -> Self {
+        self.time = Some(time);
+        
+        self
+    }
+    
+    /// Availability of works to include. If not provided, only works with [fulltext][`Availability::Fulltext`]
+    /// are included.
+    pub fn with_availability(mut self, availability: Availability) -> Self {
+        self.availability = Some(availability);
+        
+        self
+    }
+    
+    /// Order of the results. If not provided, the most relevant works are returned first.
+    pub fn with_sort(mut self, sort: Sort) -> Self {
+        self.sort = Some(sort);
+        
+        self
+    }
+    
+    /// Number of results per page, clamped to [`Request::MAX_LIMIT`]. Defaults to [`Request::DEFAULT_LIMIT`].
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit.clamp(1, Self::MAX_LIMIT);
+        
+        self
+    }
+    
+    /// Number of results to skip. Defaults to 0.
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        
+        self
+    }
+    
+    /// Performs the request, returning a single page of results.
+    /// 
+    /// # Errors
+    /// This may return [`ScholarError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    /// 
+    /// If any [I/O errors][`std::io::Error`] occur or the response fails to be deserialized, a [`ScholarError::Io`] is returned.
+    pub fn call(&self) -> Result<Response, ScholarError> {
+        let context = RequestContext::new("search", SEARCH_API, None);
+        let mut req = self.client.request("GET", SEARCH_API)
+            .query("q", &self.query)
+            .query("limit", &self.limit.to_string())
+            .query("offset", &self.offset.to_string())
+            .set("accept", "application/json")
+            .set("user-agent", &self.useragent);
+        
+        if let Some(work_type) = self.work_type {
+            req = req.query("filter_type", &work_type.to_string());
+        }
+        if let Some(time) = self.time {
+            req = req.query("filter_time", &time.to_string());
+        }
+        if let Some(availability) = self.availability {
+            req = req.query("filter_availability", &availability.to_string());
+        }
+        if let Some(sort) = self.sort {
+            req = req.query("sort_order", &sort.to_string());
+        }
+        
+        let resp = self.client.call(req).map_err(|err| ScholarError::ureq(&context, err))?;
+        
+        client::read_json(resp.into_reader()).map_err(|err| ScholarError::io(&context, err))
+    }
+    
+    /// Creates an iterator over every result, starting at the configured offset and requesting pages
+    /// as needed.
+    /// 
+    /// Iteration ends once every result has been returned, or Scholar's [deep paging limit][`Response::deep_page_limit`]
+    /// is reached. If a page fails, the error is returned once and iteration ends.
+    pub fn results(&self) -> Results {
+        Results {
+            request: self.clone(),
+            buffer: VecDeque::new(),
+            done: false,
+        }
+    }
+}
+
+/// A single page of search results.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Response {
+    /// Number of results in this page.
+    #[serde(default)]
+    pub count_returned: usize,
+    
+    /// Total number of works matching the query. Large counts may be approximate.
+    #[serde(default)]
+    pub count_found: usize,
+    
+    /// Offset of the first result in this page.
+    #[serde(default)]
+    pub offset: usize,
+    
+    /// Maximum number of results per page.
+    #[serde(default)]
+    pub limit: usize,
+    
+    /// Offset past which Scholar refuses to return results.
+    #[serde(default)]
+    pub deep_page_limit: usize,
+    
+    /// Time spent by the search index on the query, in milliseconds.
+    #[serde(default)]
+    pub query_time_ms: u64,
+    
+    /// The matching works.
+    #[serde(default)]
+    pub results: Vec<Document>,
+}
+
+/// A single work found by a search.
+/// 
+/// Fields which aren't modeled here are kept in [`extra`][`Document::extra`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Document {
+    /// Unique key of the document within the search index.
+    pub key: String,
+    
+    /// Kind of entity the document was built from (e.g. "work", "sim_page").
+    #[serde(default)]
+    pub doc_type: Option<String>,
+    
+    /// Identifier of the work in the fatcat catalog.
+    #[serde(default)]
+    pub work_ident: Option<String>,
+    
+    /// Bibliographic metadata of the work.
+    #[serde(default)]
+    pub biblio: Biblio,
+    
+    /// Preserved fulltext copy of the work, if any.
+    #[serde(default)]
+    pub fulltext: Option<Fulltext>,
+    
+    /// All other fields of the document.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Bibliographic metadata of a [`Document`].
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Biblio {
+    /// Identifier of the specific release (version) of the work in the fatcat catalog.
+    pub release_ident: Option<String>,
+    pub title: Option<String>,
+    pub subtitle: Option<String>,
+    
+    /// Names of the authors and other contributors, in order.
+    pub contrib_names: Vec<String>,
+    
+    /// Publication date, formatted as `YYYY-MM-DD`.
+    pub release_date: Option<String>,
+    pub release_year: Option<u32>,
+    
+    /// Kind of release (e.g. "article-journal", "report").
+    pub release_type: Option<String>,
+    
+    /// Publication stage (e.g. "published", "submitted").
+    pub release_stage: Option<String>,
+    
+    /// Name of the journal, conference, or other container the work was published in.
+    pub container_name: Option<String>,
+    pub publisher: Option<String>,
+    pub volume: Option<String>,
+    pub issue: Option<String>,
+    pub pages: Option<String>,
+    pub doi: Option<String>,
+    pub arxiv_id: Option<String>,
+    pub pmid: Option<String>,
+}
+
+/// Preserved fulltext copy of a [`Document`].
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Fulltext {
+    /// URL where the fulltext can be accessed (e.g. within an item, or the Wayback Machine).
+    pub access_url: Option<String>,
+    
+    /// Where the fulltext is stored (e.g. "ia_file", "wayback").
+    pub access_type: Option<String>,
+    
+    /// URL of a thumbnail image of the first page.
+    pub thumbnail_url: Option<String>,
+    
+    /// Language of the fulltext, as an ISO 639-1 code.
+    pub lang_code: Option<String>,
+}
+
+/// Iterator over every result of a [search][`Request::results`].
+#[derive(Debug)]
+pub struct Results {
+    request: Request,
+    buffer: VecDeque<Document>,
+    done: bool,
+}
+impl Iterator for Results {
+    type Item = Result<Document, ScholarError>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buffer.is_empty() && !self.done {
+            let resp = match self.request.call() {
+                Ok(resp) => resp,
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                },
+            };
+            
+            self.request.offset += resp.results.len();
+            self.done = resp.results.is_empty()
+                || self.request.offset >= resp.count_found
+                || (resp.deep_page_limit > 0 && self.request.offset >= resp.deep_page_limit);
+            self.buffer.extend(resp.results);
+        }
+        
+        self.buffer.pop_front().map(Ok)
+    }
+}
\ No newline at end of file
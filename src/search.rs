@@ -0,0 +1,197 @@
+//! Searching for items.
+//! 
+//! The Scrape API returns every item matching a query, using the same [query syntax](https://archive.org/advancedsearch.php)
+//! as the search bar on <https://archive.org/>. Results are paginated using a cursor, and can be
+//! retrieved in pages of up to 10,000 items.
+//! 
+//! Results are provided as typed [`SearchDoc`]s. Two ways of retrieving them are available:
+//! * [Scraping][`scrape()`] items matching a query, either a page at a time or using a [resilient iterator][`scrape::Items`].
+//! * [Walking a collection][`walk()`] and all of its sub-collections.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Formatter;
+use serde::{Deserialize, Deserializer};
+use crate::client::{self, RequestContext};
+use crate::item::{deserialize_metadata, deserialize_number, value_to_string};
+use crate::item::metadata::MetaValue;
+
+pub mod scrape;
+pub mod walk;
+
+/// Creates a new [scrape request][`scrape::Request`] for items matching `query`.
+pub fn scrape(query: &str) -> scrape::Request {
+    scrape::Request::new(query)
+}
+
+/// Creates a new [walk][`walk::Walk`] of `collection` and all of its sub-collections.
+pub fn walk(collection: &str) -> walk::Walk {
+    walk::Walk::new(collection)
+}
+
+/// Errors which may occur while searching for items.
+/// 
+/// Errors produced by a request include the [`RequestContext`] of that request, identifying the
+/// operation and URL involved.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SearchError {
+    /// An error while performing [`std::io`] operations.
+    /// 
+    /// `context` is `None` if the error did not occur during a request.
+    Io {
+        context: Option<RequestContext>,
+        source: std::io::Error,
+    },
+    
+    /// An error while processing a [`ureq`] request.
+    Ureq {
+        context: RequestContext,
+        source: ureq::Error,
+    },
+    
+    /// A [`ureq`] request was successful, but returned a 403 Forbidden error code.
+    Forbidden {
+        context: RequestContext,
+        response: ureq::Response,
+    },
+    
+    /// A [`ureq`] request was successful, but returned a 429 Too Many Requests error code.
+    /// 
+    /// This is only returned after any [automatic retries][`crate::client`] have been exhausted. Use
+    /// [`ResponseError::retry_after`][`crate::client::ResponseError::retry_after`] to find out how long the server asked the client to wait.
+    TooManyRequests {
+        context: RequestContext,
+        response: ureq::Response,
+    },
+    
+    /// The request was received, but refused by the Internet Archive with an error message (e.g. the
+    /// query is malformed).
+    Rejected {
+        context: RequestContext,
+        message: String,
+    },
+}
+impl SearchError {
+    /// Returns the context of the request this error occurred during, if any.
+    pub fn context(&self) -> Option<&RequestContext> {
+        match self {
+            Self::Io { context, .. } => context.as_ref(),
+            Self::Ureq { context, .. } | Self::Forbidden { context, .. } | Self::TooManyRequests { context, .. } | Self::Rejected { context, .. } => Some(context),
+        }
+    }
+    
+    pub(crate) fn io(context: &RequestContext, source: std::io::Error) -> Self {
+        Self::Io { context: Some(context.clone()), source }
+    }
+    
+    pub(crate) fn ureq(context: &RequestContext, source: ureq::Error) -> Self {
+        let context = context.with_error(&source);
+        match source {
+            ureq::Error::Status(403, response) => Self::Forbidden { context, response },
+            ureq::Error::Status(429, response) => Self::TooManyRequests { context, response },
+            _ => Self::Ureq { context, source },
+        }
+    }
+}
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { context: Some(context), source } => write!(f, "{context}: {source}"),
+            Self::Io { context: None, source } => write!(f, "{source}"),
+            Self::Ureq { context, source } => write!(f, "{context}: {source}"),
+            Self::Forbidden { context, .. } => write!(f, "{context}: 403 Forbidden"),
+            Self::TooManyRequests { context, .. } => write!(f, "{context}: 429 Too Many Requests"),
+            Self::Rejected { context, message } => write!(f, "{context}: rejected: {message}"),
+        }
+    }
+}
+impl std::error::Error for SearchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Ureq { source, .. } => Some(source),
+            Self::Forbidden { .. } | Self::TooManyRequests { .. } | Self::Rejected { .. } => None,
+        }
+    }
+}
+impl client::sealed::FailedResponse for SearchError {
+    fn failed_response(&self) -> Option<&ureq::Response> {
+        match self {
+            Self::Ureq { source: ureq::Error::Status(_, resp), .. } | Self::Forbidden { response: resp, .. } | Self::TooManyRequests { response: resp, .. } => Some(resp),
+            _ => None,
+        }
+    }
+    
+    fn failed_context(&self) -> Option<&RequestContext> {
+        self.context()
+    }
+    
+    fn failed_transport(&self) -> Option<&ureq::Transport> {
+        match self {
+            Self::Ureq { source: ureq::Error::Transport(transport), .. } => Some(transport),
+            _ => None,
+        }
+    }
+}
+impl From<std::io::Error> for SearchError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io { context: None, source: value }
+    }
+}
+
+/// A single search result.
+/// 
+/// Only the fields which were requested are populated (see [`SearchDoc::FIELDS`]). Requested fields
+/// which aren't modeled here are kept in [`extra`][`SearchDoc::extra`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SearchDoc {
+    /// Identifier of the item.
+    pub identifier: String,
+    
+    #[serde(default, deserialize_with = "deserialize_first")]
+    pub title: Option<String>,
+    
+    /// Mediatype of the item (e.g. "texts", "movies", "collection").
+    #[serde(default, deserialize_with = "deserialize_first")]
+    pub mediatype: Option<String>,
+    
+    /// Publication date of the item's content, as provided by its uploader.
+    #[serde(default, deserialize_with = "deserialize_first")]
+    pub date: Option<String>,
+    
+    /// Total number of downloads of the item.
+    #[serde(default, deserialize_with = "deserialize_number")]
+    pub downloads: Option<u64>,
+    
+    /// Total size of the item's files in bytes.
+    #[serde(default, deserialize_with = "deserialize_number")]
+    pub item_size: Option<u64>,
+    
+    /// Identifiers of the collections the item belongs to.
+    #[serde(rename = "collection", default, deserialize_with = "deserialize_list")]
+    pub collections: Vec<String>,
+    
+    /// All other requested fields, normalized the same way as an [item's metadata][`crate::item::MetadataResponse::metadata`].
+    #[serde(flatten, deserialize_with = "deserialize_metadata")]
+    pub extra: HashMap<String, MetaValue>,
+}
+impl SearchDoc {
+    /// Names of the fields modeled by [`SearchDoc`], for use with [`scrape::Request::with_fields`].
+    pub const FIELDS: &'static [&'static str] = &["identifier", "title", "mediatype", "date", "downloads", "item_size", "collection"];
+}
+
+/// Parses a field which may be either a single value or a list, keeping only the first value.
+fn deserialize_first<'de, D: Deserializer<'de>>(de: D) -> Result<Option<String>, D::Error> {
+    Ok(deserialize_list(de)?.into_iter().next())
+}
+
+/// Parses a field which may be either a single value or a list. Scalars which aren't strings are
+/// converted to strings.
+fn deserialize_list<'de, D: Deserializer<'de>>(de: D) -> Result<Vec<String>, D::Error> {
+    Ok(match Option::<serde_json::Value>::deserialize(de)? {
+        Some(serde_json::Value::Array(values)) => values.into_iter().filter_map(value_to_string).collect(),
+        Some(value) => value_to_string(value).into_iter().collect(),
+        None => vec![],
+    })
+}
\ No newline at end of file
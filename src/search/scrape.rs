@@ -0,0 +1,538 @@
+//! Retrieval of items matching a query, using the Scrape API.
+//! 
+//! Small queries can be paged through manually using [`Request::call`]. Enumerations of large queries
+//! can take hours, during which requests occasionally fail, and the cursor may expire. The [`Items`]
+//! iterator handles these cases on its own:
+//! * The cursor is carried between pages automatically.
+//! * Transient failures (connection errors, `5xx` responses, and throttling) are retried with a backoff.
+//! * If the cursor expires, the enumeration restarts from the beginning.
+//! * Each identifier is only yielded once, so restarts (or items which move between pages) don't
+//!   produce duplicates.
+//! 
+//! Only one page of results is held in memory at a time, aside from the identifiers kept for
+//! deduplication. For queries matching millions of items, [deduplication][`Request::with_deduplication`]
+//! can be disabled to keep memory usage bounded. Results can also be [streamed][`Request::stream`] to
+//! another thread through a channel, or passed to a [callback][`Request::for_each`].
+
+use std::collections::{HashSet, VecDeque};
+use std::hash::{BuildHasher, Hasher, RandomState};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+use serde::Deserialize;
+use crate::{Credentials, default_user_agent};
+use crate::client::{self, Client, RawResponse, RequestContext, ResponseError};
+use crate::headers::RequestHeaderExt;
+use crate::search::{SearchDoc, SearchError};
+
+/// Request builder for retrieving items from the Scrape API.
+/// 
+/// Refer to [`Request::call`] for an example.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    credentials: Option<Credentials>,
+    useragent: String,
+    client: Client,
+    pub(super) query: String,
+    fields: Vec<String>,
+    sorts: Vec<String>,
+    count: usize,
+    dedup: bool,
+    params: Vec<(String, String)>,
+}
+impl Request {
+    pub fn new(query: &str) -> Self {
+        Self {
+            credentials: None,
+            useragent: default_user_agent(),
+            client: Client::shared(),
+            query: query.to_string(),
+            fields: vec![],
+            sorts: vec![],
+            count: 1000,
+            dedup: true,
+            params: vec![],
+        }
+    }
+    
+    /// Provide authentication credentials to be used with this request.
+    /// 
+    /// Search results are public, so credentials are not normally required. Authenticated requests
+    /// may include items which are only visible to the account (e.g. its own hidden items).
+    pub fn with_credentials(mut self, credentials: Option<Credentials>) -> Self {
+        self.credentials = credentials;
+        
+        self
+    }
+    
+    /// Configures the User-Agent string provided in this request.
+    /// 
+    /// If `None` or if the string is empty, a [default][`default_user_agent`] will be used.
+    pub fn with_useragent(mut self, useragent: Option<String>) -> Self {
+        self.useragent = match useragent {
+            Some(useragent) if !useragent.is_empty() => useragent,
+            _ => default_user_agent(),
+        };
+        
+        self
+    }
+    
+    /// Configures the [`Client`] used to perform this request.
+    /// 
+    /// By default, the [shared client][`Client::shared`] is used.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        
+        self
+    }
+    
+    /// Adds a metadata field to include in each result (e.g. `title`, `mediatype`).
+    /// 
+    /// The `identifier` field is always included. Fields which aren't modeled by [`SearchDoc`] are
+    /// provided in its [`extra`][`SearchDoc::extra`] map.
+    pub fn with_field(mut self, field: &str) -> Self {
+        self.fields.push(field.to_string());
+        
+        self
+    }
+    
+    /// Adds several metadata fields to include in each result. See [`Request::with_field`].
+    /// 
+    /// [`SearchDoc::FIELDS`] can be used to request every field modeled by [`SearchDoc`].
+    pub fn with_fields(mut self, fields: &[&str]) -> Self {
+        self.fields.extend(fields.iter().map(|field| field.to_string()));
+        
+        self
+    }
+    
+    /// Adds a field to sort results by, optionally followed by `asc` or `desc` (e.g. `addeddate desc`).
+    /// 
+    /// Results are sorted by identifier if no sorts are provided.
+    pub fn with_sort(mut self, sort: &str) -> Self {
+        self.sorts.push(sort.to_string());
+        
+        self
+    }
+    
+    /// Sets the number of results returned by each request [call][`Request::call`].
+    /// 
+    /// The Scrape API only accepts counts from 100 to 10,000, so other values are clamped into that
+    /// range. This is 1,000 by default.
+    pub fn with_count(mut self, count: usize) -> Self {
+        self.count = count.clamp(100, 10000);
+        
+        self
+    }
+    
+    /// Configures whether or not [iterators][`Items`] created from this request skip identifiers which
+    /// have already been yielded.
+    /// 
+    /// Deduplication keeps every yielded identifier in memory. When disabled, memory usage stays bounded,
+    /// but an item may be yielded twice if the enumeration is restarted after its cursor expires.
+    /// 
+    /// This is true (enabled) by default.
+    pub fn with_deduplication(mut self, dedup: bool) -> Self {
+        self.dedup = dedup;
+        
+        self
+    }
+    
+    /// Adds an arbitrary query parameter to the request.
+    /// 
+    /// This allows parameters which aren't modeled by this crate yet to be used. Parameters are sent
+    /// as-is, after all other parameters, and do not replace parameters set using other methods.
+    pub fn with_param(mut self, key: &str, value: &str) -> Self {
+        self.params.push((key.to_string(), value.to_string()));
+        
+        self
+    }
+    
+    /// Performs the request query to the Internet Archive.
+    /// 
+    /// On success, returns the [`Response`] data. If there are more results, the response contains a
+    /// cursor which can be provided to subsequent calls of this method, in the same way as [task searches][`crate::tasks::search::Request::call`].
+    /// 
+    /// # Errors
+    /// This may return [`SearchError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    /// If the query is refused (e.g. due to a syntax error), a [`SearchError::Rejected`] is returned.
+    /// 
+    /// If any [I/O errors][`std::io::Error`] occur or the response fails to be deserialized, a [`SearchError::Io`] is returned.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// let request = iars::search::scrape("collection:nasa AND mediatype:movies")
+    ///     .with_field("title");
+    /// 
+    /// let mut cursor = None;
+    /// loop {
+    ///     let resp = request.call(cursor)?;
+    ///     for doc in &resp.items {
+    ///         println!("{}: {:?}", doc.identifier, doc.title);
+    ///     }
+    ///     
+    ///     cursor = resp.cursor;
+    ///     if cursor.is_none() {
+    ///         break;
+    ///     }
+    /// }
+    /// # Ok::<(), iars::search::SearchError>(())
+    /// ```
+    pub fn call(&self, cursor: Option<String>) -> Result<Response, SearchError> {
+        let (req, context) = self.request(cursor);
+        
+        let resp = match self.client.call(req) {
+            Ok(resp) | Err(ureq::Error::Status(400, resp)) => resp,
+            Err(err) => return Err(SearchError::ureq(&context, err)),
+        };
+        let resp: InterimResponse = client::read_json(resp.into_reader()).map_err(|err| SearchError::io(&context, err))?;
+        
+        match resp.error {
+            Some(message) => Err(SearchError::Rejected { context, message }),
+            None => Ok(Response {
+                items: resp.items,
+                count: resp.count,
+                total: resp.total,
+                cursor: resp.cursor,
+            }),
+        }
+    }
+    
+    /// Performs the same request as [`Request::call`], but returns the [undecoded response][`RawResponse`].
+    /// 
+    /// Refused queries are returned as a response with a `400` status, rather than as an error.
+    /// 
+    /// # Errors
+    /// See [`Request::call`].
+    pub fn call_raw(&self, cursor: Option<String>) -> Result<RawResponse, SearchError> {
+        let (req, context) = self.request(cursor);
+        let resp = match self.client.call(req) {
+            Ok(resp) | Err(ureq::Error::Status(400, resp)) => resp,
+            Err(err) => return Err(SearchError::ureq(&context, err)),
+        };
+        
+        RawResponse::read(resp).map_err(|err| SearchError::io(&context, err))
+    }
+    
+    fn request(&self, cursor: Option<String>) -> (ureq::Request, RequestContext) {
+        let url = "https://archive.org/services/search/v1/scrape";
+        let context = RequestContext::new("scrape", url, None);
+        
+        let mut fields = vec!["identifier".to_string()];
+        fields.extend(self.fields.iter().filter(|field| *field != "identifier").cloned());
+        
+        let mut req = self.client.request("GET", url)
+            .set("user-agent", &self.useragent)
+            .query("q", &self.query)
+            .query("fields", &fields.join(","))
+            .query("count", &self.count.to_string());
+        
+        if !self.sorts.is_empty() {
+            req = req.query("sorts", &self.sorts.join(","));
+        }
+        
+        if let Some(cursor) = cursor {
+            req = req.query("cursor", &cursor);
+        }
+        
+        req = req.query_pairs(self.params.iter().map(|(key, val)| (key.as_str(), val.as_str())));
+        
+        if let Some(creds) = self.credentials.as_ref() {
+            req = req.set_header(creds.into());
+        }
+        
+        (req, context)
+    }
+    
+    /// Creates an [iterator][`Items`] over every matching item, which requests pages as needed.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use iars::search::SearchDoc;
+    /// 
+    /// let items = iars::search::scrape("collection:nasa")
+    ///     .with_fields(SearchDoc::FIELDS)
+    ///     .items();
+    /// 
+    /// for doc in items {
+    ///     let doc = doc?;
+    ///     println!("{}: {:?} ({:?} downloads)", doc.identifier, doc.title, doc.downloads);
+    /// }
+    /// # Ok::<(), iars::search::SearchError>(())
+    /// ```
+    pub fn items(&self) -> Items {
+        Items {
+            request: self.clone(),
+            cursor: None,
+            buffer: VecDeque::new(),
+            seen: self.dedup.then(HashSet::new),
+            max_retries: 5,
+            max_restarts: 3,
+            restarts: 0,
+            finished: false,
+        }
+    }
+    
+    /// Calls `f` with every matching item, as each page arrives, until it returns false.
+    /// 
+    /// On success, returns the number of items passed to `f`.
+    /// 
+    /// # Errors
+    /// Returns the first error the [iterator][`Items`] returns. Items received before the error have
+    /// already been passed to `f`.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// let mut total_size = 0;
+    /// iars::search::scrape("collection:nasa")
+    ///     .with_field("item_size")
+    ///     .for_each(|doc| {
+    ///         total_size += doc.item_size.unwrap_or(0);
+    ///         true
+    ///     })?;
+    /// # Ok::<(), iars::search::SearchError>(())
+    /// ```
+    pub fn for_each(&self, mut f: impl FnMut(SearchDoc) -> bool) -> Result<usize, SearchError> {
+        let mut count = 0;
+        for doc in self.items() {
+            count += 1;
+            if !f(doc?) {
+                break;
+            }
+        }
+        
+        Ok(count)
+    }
+    
+    /// Iterates over every matching item on a new thread, sending each result through a channel.
+    /// 
+    /// At most `bound` results are buffered in the channel. Once it is full, no more pages are requested
+    /// until the receiver catches up. The thread stops after an error is sent, or when the receiver is
+    /// dropped.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// let rx = iars::search::scrape("collection:nasa")
+    ///     .with_deduplication(false)
+    ///     .stream(10000);
+    /// 
+    /// for doc in rx {
+    ///     println!("{}", doc?.identifier);
+    /// }
+    /// # Ok::<(), iars::search::SearchError>(())
+    /// ```
+    pub fn stream(&self, bound: usize) -> Receiver<Result<SearchDoc, SearchError>> {
+        let (tx, rx) = mpsc::sync_channel(bound);
+        let items = self.items();
+        
+        thread::spawn(move || {
+            for result in items {
+                if tx.send(result).is_err() {
+                    break;
+                }
+            }
+        });
+        
+        rx
+    }
+    
+    /// Returns up to `n` items chosen uniformly at random from every matching item.
+    /// 
+    /// Items are sampled using reservoir sampling while [iterating][`Request::items`] over every
+    /// match, so the whole query is enumerated. For large queries, raising the [count][`Request::with_count`]
+    /// reduces the number of requests. To sample a collection, use a query such as `collection:nasa`.
+    /// 
+    /// If fewer than `n` items match, every match is returned. Items are returned in no particular order.
+    /// 
+    /// # Errors
+    /// Returns the first error the [iterator][`Items`] returns.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// let sample = iars::search::scrape("collection:nasa AND mediatype:movies")
+    ///     .with_count(10000)
+    ///     .sample(25)?;
+    /// 
+    /// for doc in sample {
+    ///     println!("{}", doc.identifier);
+    /// }
+    /// # Ok::<(), iars::search::SearchError>(())
+    /// ```
+    pub fn sample(&self, n: usize) -> Result<Vec<SearchDoc>, SearchError> {
+        let seed = RandomState::new().build_hasher().finish();
+        
+        self.sample_with_seed(n, seed)
+    }
+    
+    /// Like [`Request::sample`], but using a fixed `seed`, so the same sample is chosen each time the
+    /// same results are returned (e.g. for reproducible research).
+    /// 
+    /// # Errors
+    /// See [`Request::sample`].
+    pub fn sample_with_seed(&self, n: usize, seed: u64) -> Result<Vec<SearchDoc>, SearchError> {
+        let mut rng = SplitMix64(seed);
+        let mut reservoir = Vec::with_capacity(n.min(10000));
+        
+        for (i, doc) in self.items().enumerate() {
+            let doc = doc?;
+            if reservoir.len() < n {
+                reservoir.push(doc);
+            } else {
+                let j = (rng.next() % (i as u64 + 1)) as usize;
+                if j < n {
+                    reservoir[j] = doc;
+                }
+            }
+        }
+        
+        Ok(reservoir)
+    }
+}
+
+/// Minimal pseudorandom number generator, used for [sampling][`Request::sample`] and fault injection.
+#[derive(Debug)]
+pub(crate) struct SplitMix64(pub(crate) u64);
+impl SplitMix64 {
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        
+        z ^ (z >> 31)
+    }
+}
+
+/// Response data returned from a successful [scrape request][`Request`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    /// Requested fields of each matching item.
+    pub items: Vec<SearchDoc>,
+    
+    /// Number of items in this response.
+    pub count: usize,
+    
+    /// Total number of items matching the query, if provided.
+    pub total: Option<usize>,
+    
+    /// Pagination token string for use in subsequent request calls.
+    /// 
+    /// If `None`, there is no more data to retrieve.
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InterimResponse {
+    #[serde(default)]
+    items: Vec<SearchDoc>,
+    #[serde(default)]
+    count: usize,
+    total: Option<usize>,
+    cursor: Option<String>,
+    error: Option<String>,
+}
+
+/// Iterator over every item matching a [scrape request][`Request`]. See the [module docs][`mod@crate::search::scrape`].
+/// 
+/// Once an error is returned, the iterator ends.
+#[derive(Debug, Clone)]
+pub struct Items {
+    request: Request,
+    cursor: Option<String>,
+    buffer: VecDeque<SearchDoc>,
+    seen: Option<HashSet<String>>,
+    max_retries: usize,
+    max_restarts: usize,
+    restarts: usize,
+    finished: bool,
+}
+impl Items {
+    /// Sets the maximum number of times a failed page request will be retried, before its error is returned.
+    /// 
+    /// Retries are in addition to the [client's own retries][`crate::client`]. This is 5 by default.
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        
+        self
+    }
+    
+    /// Sets the maximum number of times the enumeration will be restarted after its cursor expires.
+    /// 
+    /// Restarting requests every page again, but items which have already been yielded are skipped.
+    /// This is 3 by default.
+    pub fn with_max_restarts(mut self, max_restarts: usize) -> Self {
+        self.max_restarts = max_restarts;
+        
+        self
+    }
+    
+    /// Returns the number of times the enumeration has been restarted after its cursor expired.
+    pub fn restarts(&self) -> usize {
+        self.restarts
+    }
+    
+    /// Requests the next page, retrying or restarting as necessary.
+    fn fetch(&mut self) -> Result<(), SearchError> {
+        let mut attempt = 0;
+        loop {
+            match self.request.call(self.cursor.clone()) {
+                Ok(resp) => {
+                    self.cursor = resp.cursor;
+                    self.finished = self.cursor.is_none();
+                    
+                    for doc in resp.items {
+                        if self.seen.as_mut().is_none_or(|seen| seen.insert(doc.identifier.clone())) {
+                            self.buffer.push_back(doc);
+                        }
+                    }
+                    
+                    return Ok(());
+                },
+                Err(err) if self.cursor.is_some() && is_cursor_expired(&err) && self.restarts < self.max_restarts => {
+                    self.cursor = None;
+                    self.restarts += 1;
+                },
+                Err(err) if is_transient(&err) && attempt < self.max_retries => {
+                    // without a Retry-After header, back off exponentially starting at 1 second
+                    thread::sleep(err.retry_after().unwrap_or(Duration::from_secs(1 << attempt.min(6))));
+                    attempt += 1;
+                },
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+impl Iterator for Items {
+    type Item = Result<SearchDoc, SearchError>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.buffer.pop_front() {
+                return Some(Ok(item));
+            }
+            if self.finished {
+                return None;
+            }
+            
+            if let Err(err) = self.fetch() {
+                self.finished = true;
+                
+                return Some(Err(err));
+            }
+        }
+    }
+}
+
+/// Returns true if a request may succeed if it is sent again.
+fn is_transient(err: &SearchError) -> bool {
+    match err {
+        SearchError::Io { .. } | SearchError::TooManyRequests { .. } => true,
+        SearchError::Ureq { source: ureq::Error::Transport(_), .. } => true,
+        SearchError::Ureq { source: ureq::Error::Status(status, _), .. } => *status >= 500,
+        _ => false,
+    }
+}
+
+/// Returns true if a request was refused because its cursor is no longer valid.
+fn is_cursor_expired(err: &SearchError) -> bool {
+    matches!(err, SearchError::Rejected { message, .. } if message.to_ascii_lowercase().contains("cursor"))
+}
\ No newline at end of file
@@ -0,0 +1,146 @@
+//! Traversal of collection hierarchies.
+//! 
+//! Collections are items themselves (with a `mediatype` of `collection`), and can be members of other
+//! collections. A [`Walk`] enumerates every item of a collection, then every item of each of its
+//! sub-collections, and so on, breadth-first.
+//! 
+//! Items can belong to several collections, so the same item (or sub-collection) may be reached more
+//! than once. Each is only yielded the first time it is found, which also prevents cycles from being
+//! walked forever.
+//! 
+//! # Example
+//! ```rust,no_run
+//! for entry in iars::search::walk("nasa").with_max_depth(Some(2)) {
+//!     let entry = entry?;
+//!     println!("{}{} (in {})", "  ".repeat(entry.depth), entry.identifier, entry.parent);
+//! }
+//! # Ok::<(), iars::search::SearchError>(())
+//! ```
+
+use std::collections::{HashSet, VecDeque};
+use crate::{Client, Credentials};
+use crate::search::SearchError;
+use crate::search::scrape::{Items, Request};
+
+/// An item found while [walking][`Walk`] a collection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// Identifier of the item.
+    pub identifier: String,
+    
+    /// Identifier of the collection the item was found in.
+    pub parent: String,
+    
+    /// Number of collections between the walked collection and this item. Items directly within the
+    /// walked collection have a depth of 0.
+    pub depth: usize,
+    
+    /// Whether or not this item is a collection (and so may contain items of its own).
+    pub is_collection: bool,
+}
+
+/// Iterator over every item within a collection and its sub-collections.
+/// 
+/// Each collection is enumerated using a [resilient scrape iterator][`Items`], so transient failures are
+/// retried. If an error is still encountered, it is returned and the walk ends.
+#[derive(Debug, Clone)]
+pub struct Walk {
+    request: Request,
+    max_depth: Option<usize>,
+    queue: VecDeque<(String, usize)>,
+    seen: HashSet<String>,
+    current: Option<(String, usize, Items)>,
+}
+impl Walk {
+    pub fn new(collection: &str) -> Self {
+        Self {
+            request: Request::new("").with_field("mediatype").with_count(10000),
+            max_depth: None,
+            queue: VecDeque::from([(collection.to_string(), 0)]),
+            seen: HashSet::from([collection.to_string()]),
+            current: None,
+        }
+    }
+    
+    /// Provide authentication credentials to be used with each request.
+    pub fn with_credentials(mut self, credentials: Option<Credentials>) -> Self {
+        self.request = self.request.with_credentials(credentials);
+        
+        self
+    }
+    
+    /// Configures the User-Agent string provided in each request.
+    /// 
+    /// If `None` or if the string is empty, a [default][`crate::default_user_agent`] will be used.
+    pub fn with_useragent(mut self, useragent: Option<String>) -> Self {
+        self.request = self.request.with_useragent(useragent);
+        
+        self
+    }
+    
+    /// Configures the [`Client`] used to perform each request.
+    /// 
+    /// By default, the [shared client][`Client::shared`] is used.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.request = self.request.with_client(client);
+        
+        self
+    }
+    
+    /// Limits how deep sub-collections are walked. Sub-collections found at `max_depth` are still
+    /// yielded, but their items are not.
+    /// 
+    /// A depth of `Some(0)` only yields the items directly within the collection. This is `None`
+    /// (unlimited) by default.
+    pub fn with_max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        
+        self
+    }
+}
+impl Iterator for Walk {
+    type Item = Result<Entry, SearchError>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some((collection, depth, items)) = self.current.as_mut() else {
+                let (collection, depth) = self.queue.pop_front()?;
+                
+                let mut request = self.request.clone();
+                request.query = format!("collection:\"{collection}\"");
+                self.current = Some((collection, depth, request.items()));
+                continue;
+            };
+            
+            let doc = match items.next() {
+                Some(Ok(doc)) => doc,
+                Some(Err(err)) => {
+                    self.queue.clear();
+                    self.current = None;
+                    
+                    return Some(Err(err));
+                },
+                None => {
+                    self.current = None;
+                    continue;
+                },
+            };
+            
+            if !self.seen.insert(doc.identifier.clone()) {
+                continue;
+            }
+            
+            let is_collection = doc.mediatype.as_deref() == Some("collection");
+            if is_collection && self.max_depth.is_none_or(|max| *depth < max) {
+                self.queue.push_back((doc.identifier.clone(), *depth + 1));
+            }
+            
+            return Some(Ok(Entry {
+                identifier: doc.identifier,
+                parent: collection.clone(),
+                depth: *depth,
+                is_collection,
+            }));
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,253 @@
+//! Statistics of file transfers.
+//! 
+//! A [`TransferStats`] handle can be attached to a [`Client`][`crate::Client`] using
+//! [`Client::with_transfer_stats`][`crate::Client::with_transfer_stats`]. Every file upload and download
+//! performed through that client will then be recorded, allowing long-running transfers to be monitored.
+//! 
+//! Statistics are available in two forms:
+//! * Aggregate [totals][`TransferStats::totals`] of every transfer, which can be polled at any time
+//!   (e.g. from another thread).
+//! * The progress of each individual [transfer][`Transfer`], which is delivered to an optional
+//!   [callback][`TransferStats::with_callback`] as data is transferred.
+//! 
+//! # Example
+//! ```rust,no_run
+//! use iars::{Client, Item};
+//! use iars::stats::TransferStats;
+//! 
+//! let stats = TransferStats::new().with_callback(|transfer| {
+//!     println!("{}: {} bytes ({:.0} B/s, ETA {:?})", transfer.url, transfer.transferred, transfer.bytes_per_sec(), transfer.eta());
+//! });
+//! 
+//! let item = Item::new("test_item")?
+//!     .with_client(Client::new().with_transfer_stats(stats.clone()));
+//! 
+//! item.download_file("large_file.bin", std::io::sink())?;
+//! 
+//! println!("{:?}", stats.totals());
+//! # Ok::<(), iars::ItemError>(())
+//! ```
+
+use std::fmt;
+use std::fmt::Formatter;
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::metrics::Metrics;
+
+/// Direction of a [`Transfer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Data is being sent to the Internet Archive.
+    Upload,
+    
+    /// Data is being received from the Internet Archive.
+    Download,
+}
+
+/// Progress of a single file transfer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transfer {
+    /// URL the file is being transferred to or from.
+    pub url: String,
+    
+    /// Whether the file is being uploaded or downloaded.
+    pub direction: Direction,
+    
+    /// Number of bytes transferred so far.
+    pub transferred: u64,
+    
+    /// Total size of the file in bytes, if known.
+    pub total: Option<u64>,
+    
+    /// Time since the transfer began.
+    pub elapsed: Duration,
+    
+    /// Number of times the request was retried before the transfer began.
+    pub retries: usize,
+    
+    /// True once the transfer has finished (successfully or not).
+    pub finished: bool,
+}
+impl Transfer {
+    /// Average transfer rate in bytes per second.
+    pub fn bytes_per_sec(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 { self.transferred as f64 / secs } else { 0.0 }
+    }
+    
+    /// Estimated time remaining, based on the average transfer rate.
+    /// 
+    /// `None` if the total size is unknown, or nothing has been transferred yet.
+    pub fn eta(&self) -> Option<Duration> {
+        let remaining = self.total?.saturating_sub(self.transferred);
+        let rate = self.bytes_per_sec();
+        if rate <= 0.0 {
+            return None;
+        }
+        
+        Some(Duration::from_secs_f64(remaining as f64 / rate))
+    }
+}
+
+/// Aggregate statistics of every transfer recorded by a [`TransferStats`] handle.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Totals {
+    /// Total bytes uploaded.
+    pub bytes_up: u64,
+    
+    /// Total bytes downloaded.
+    pub bytes_down: u64,
+    
+    /// Number of transfers which have begun.
+    pub started: usize,
+    
+    /// Number of transfers which have finished (successfully or not).
+    pub finished: usize,
+    
+    /// Total number of retried requests.
+    pub retries: usize,
+    
+    /// Time since the handle was created.
+    pub elapsed: Duration,
+}
+impl Totals {
+    /// Average combined (upload and download) transfer rate in bytes per second.
+    pub fn bytes_per_sec(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 { (self.bytes_up + self.bytes_down) as f64 / secs } else { 0.0 }
+    }
+}
+
+type Callback = dyn Fn(&Transfer) + Send + Sync;
+
+/// Handle for recording statistics of file transfers.
+/// 
+/// Clones of a handle share the same statistics.
+#[derive(Clone)]
+pub struct TransferStats {
+    created: Instant,
+    totals: Arc<Mutex<Totals>>,
+    callback: Option<Arc<Callback>>,
+}
+impl fmt::Debug for TransferStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransferStats")
+            .field("totals", &self.totals())
+            .finish_non_exhaustive()
+    }
+}
+impl Default for TransferStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl TransferStats {
+    pub fn new() -> Self {
+        Self {
+            created: Instant::now(),
+            totals: Default::default(),
+            callback: None,
+        }
+    }
+    
+    /// Configures a callback which receives the progress of each transfer.
+    /// 
+    /// The callback is called periodically while data is transferred (at most every 100ms per
+    /// transfer), and once more when each transfer finishes. It is called on the thread performing
+    /// the transfer, so it should return quickly.
+    pub fn with_callback(mut self, callback: impl Fn(&Transfer) + Send + Sync + 'static) -> Self {
+        self.callback = Some(Arc::new(callback));
+        
+        self
+    }
+    
+    /// Returns the aggregate statistics of every transfer so far.
+    pub fn totals(&self) -> Totals {
+        Totals {
+            elapsed: self.created.elapsed(),
+            ..self.totals.lock().unwrap().clone()
+        }
+    }
+    
+    pub(crate) fn add_retry(&self) {
+        self.totals.lock().unwrap().retries += 1;
+    }
+    
+    /// Begins recording a new transfer.
+    pub(crate) fn start(&self, url: &str, direction: Direction, total: Option<u64>, retries: usize) -> Tracker {
+        self.totals.lock().unwrap().started += 1;
+        
+        Tracker {
+            stats: self.clone(),
+            transfer: Transfer {
+                url: url.to_string(),
+                direction,
+                transferred: 0,
+                total,
+                elapsed: Duration::ZERO,
+                retries,
+                finished: false,
+            },
+            started: Instant::now(),
+            last_report: Instant::now(),
+        }
+    }
+}
+
+/// Records the progress of a single transfer, finishing it when dropped.
+pub(crate) struct Tracker {
+    stats: TransferStats,
+    transfer: Transfer,
+    started: Instant,
+    last_report: Instant,
+}
+impl Tracker {
+    fn add(&mut self, len: usize) {
+        self.transfer.transferred += len as u64;
+        self.transfer.elapsed = self.started.elapsed();
+        
+        match self.transfer.direction {
+            Direction::Upload => self.stats.totals.lock().unwrap().bytes_up += len as u64,
+            Direction::Download => self.stats.totals.lock().unwrap().bytes_down += len as u64,
+        }
+        
+        if self.last_report.elapsed() >= Duration::from_millis(100) {
+            self.last_report = Instant::now();
+            if let Some(callback) = self.stats.callback.as_ref() {
+                callback(&self.transfer);
+            }
+        }
+    }
+}
+impl Drop for Tracker {
+    fn drop(&mut self) {
+        self.transfer.elapsed = self.started.elapsed();
+        self.transfer.finished = true;
+        self.stats.totals.lock().unwrap().finished += 1;
+        
+        if let Some(callback) = self.stats.callback.as_ref() {
+            callback(&self.transfer);
+        }
+    }
+}
+
+/// Reader which records the bytes read from it.
+pub(crate) struct TrackedReader<R> {
+    pub(crate) inner: R,
+    pub(crate) tracker: Option<Tracker>,
+    pub(crate) metrics: Option<(Metrics, Direction)>,
+}
+impl<R: Read> Read for TrackedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        if let Some(tracker) = self.tracker.as_mut() {
+            tracker.add(len);
+        }
+        if let Some((metrics, direction)) = self.metrics.as_ref() {
+            metrics.add_bytes(*direction, len);
+        }
+        
+        Ok(len)
+    }
+}
\ No newline at end of file
@@ -13,8 +13,10 @@ use std::collections::HashMap;
 use std::fmt;
 use std::fmt::Formatter;
 use serde::Deserialize;
-use crate::{Credentials, DEFAULT_USER_AGENT};
+use crate::{Credentials, default_user_agent};
+use crate::client::{self, Client, QuotaInfo, RequestContext};
 use crate::headers::RequestHeaderExt;
+use crate::item::deserialize_number;
 
 pub mod search;
 pub mod submit;
@@ -33,14 +35,87 @@ pub fn search() -> search::Request {
 /// * the owner of the item the task is associated with, or
 /// * users with privileged access
 pub fn log(task_id: usize, creds: &Credentials, useragent: Option<String>) -> Result<ureq::Response, ureq::Error> {
-    ureq::get("https://catalogd.archive.org/services/tasks.php")
+    let client = Client::shared();
+    let req = client.request("GET", "https://catalogd.archive.org/services/tasks.php")
         .query("task_log", &task_id.to_string())
         .set("user-agent", &useragent
             .and_then(|s| if s.is_empty() { None } else { Some(s) })
-            .unwrap_or(DEFAULT_USER_AGENT.into())
+            .unwrap_or_else(default_user_agent)
         )
-        .set_header(creds.into())
-        .call()
+        .set_header(creds.into());
+    
+    client.call(req)
+}
+
+/// Retrieves how many tasks of a [command][`Command`] the user may queue, and how many are currently queued.
+/// 
+/// The Internet Archive limits how many tasks of each command a user may have queued at once. Once the
+/// limit is reached, submissions are rejected until some tasks finish. The returned [`QuotaInfo`] has its
+/// [`limit`][`QuotaInfo::limit`], [`in_flight`][`QuotaInfo::in_flight`], [`remaining`][`QuotaInfo::remaining`],
+/// and [`over_limit`][`QuotaInfo::over_limit`] fields populated.
+/// 
+/// # Example
+/// ```rust,no_run
+/// use iars::Credentials;
+/// use iars::tasks::Command;
+/// 
+/// let creds = Credentials::new("accesskey", "secretkey");
+/// let quota = iars::tasks::rate_limit(&Command::Derive { remove_derived: "".into() }, &creds, None)?;
+/// 
+/// println!("{:?} of {:?} derives queued", quota.in_flight, quota.limit);
+/// # Ok::<(), iars::tasks::TaskError>(())
+/// ```
+/// 
+/// # Errors
+/// This may return [`TaskError::Ureq`] if a [`ureq::Error`] is encountered while performing the request. If the
+/// response cannot be deserialized, a [`TaskError::Io`] is returned.
+pub fn rate_limit(command: &Command, creds: &Credentials, useragent: Option<String>) -> Result<QuotaInfo, TaskError> {
+    let url = "https://archive.org/services/tasks.php";
+    let context = RequestContext::new("rate_limit", url, None);
+    
+    let client = Client::shared();
+    let req = client.request("GET", url)
+        .query("rate_limits", "1")
+        .query("cmd", command.name())
+        .set("user-agent", &useragent
+            .and_then(|s| if s.is_empty() { None } else { Some(s) })
+            .unwrap_or_else(default_user_agent)
+        )
+        .set_header(creds.into());
+    
+    let resp: RateLimitResponse = client.call(req)
+        .map_err(|err| TaskError::ureq(&context, err))?
+        .into_json()
+        .map_err(|err| TaskError::io(&context, err))?;
+    
+    let value = match resp.value {
+        Some(value) if resp.success => value,
+        _ => return Err(TaskError::Rejected { context, message: resp.error.unwrap_or_default() }),
+    };
+    
+    Ok(QuotaInfo {
+        limit: value.task_limits,
+        remaining: value.task_limits.zip(value.tasks_inflight).map(|(limit, inflight)| limit.saturating_sub(inflight)),
+        in_flight: value.tasks_inflight,
+        over_limit: value.task_limits.zip(value.tasks_inflight).map(|(limit, inflight)| inflight >= limit),
+        ..Default::default()
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct RateLimitResponse {
+    #[serde(default)]
+    success: bool,
+    error: Option<String>,
+    value: Option<RateLimitValue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RateLimitValue {
+    #[serde(default, deserialize_with = "deserialize_number")]
+    task_limits: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_number")]
+    tasks_inflight: Option<u64>,
 }
 
 /// Creates a new task [submission request][`submit::Request`].
@@ -48,32 +123,122 @@ pub fn submit() -> submit::Request {
     submit::Request::new()
 }
 
+/// Errors which may occur while searching or submitting tasks.
+/// 
+/// Errors produced by a request include the [`RequestContext`] of that request, identifying the
+/// operation and URL (and item, if known) involved.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum TaskError {
     /// An error while performing [`std::io`] operations.
-    Io(std::io::Error),
+    /// 
+    /// `context` is `None` if the error did not occur during a request.
+    Io {
+        context: Option<RequestContext>,
+        source: std::io::Error,
+    },
     
     /// An error while processing a [`ureq`] request.
-    Ureq(ureq::Error),
+    Ureq {
+        context: RequestContext,
+        source: ureq::Error,
+    },
     
     /// A [`ureq`] request was successful, but returned a 403 Forbidden error code.
     /// 
     /// This is usually caused by not having valid [authentication][`crate::Credentials`].
-    Forbidden(ureq::Response),
+    Forbidden {
+        context: RequestContext,
+        response: ureq::Response,
+    },
+    
+    /// A [`ureq`] request was successful, but returned a 429 Too Many Requests error code.
+    /// 
+    /// This is only returned after any [automatic retries][`crate::client`] have been exhausted. Use
+    /// [`ResponseError::retry_after`][`crate::client::ResponseError::retry_after`] to find out how long the server asked the client to wait.
+    TooManyRequests {
+        context: RequestContext,
+        response: ureq::Response,
+    },
+    
+    /// The request was received, but refused by the Internet Archive with an error message.
+    Rejected {
+        context: RequestContext,
+        message: String,
+    },
+    
+    /// Task priority is outside of the range permitted by [`Priority`].
+    InvalidPriority(isize),
 }
-impl From<std::io::Error> for TaskError {
-    fn from(value: std::io::Error) -> Self {
-        Self::Io(value)
+impl TaskError {
+    /// Returns the context of the request this error occurred during, if any.
+    pub fn context(&self) -> Option<&RequestContext> {
+        match self {
+            Self::Io { context, .. } => context.as_ref(),
+            Self::Ureq { context, .. } | Self::Forbidden { context, .. } | Self::TooManyRequests { context, .. } | Self::Rejected { context, .. } => Some(context),
+            Self::InvalidPriority(_) => None,
+        }
+    }
+    
+    pub(crate) fn io(context: &RequestContext, source: std::io::Error) -> Self {
+        Self::Io { context: Some(context.clone()), source }
+    }
+    
+    pub(crate) fn ureq(context: &RequestContext, source: ureq::Error) -> Self {
+        let context = context.with_error(&source);
+        match source {
+            ureq::Error::Status(403, response) => Self::Forbidden { context, response },
+            ureq::Error::Status(429, response) => Self::TooManyRequests { context, response },
+            _ => Self::Ureq { context, source },
+        }
+    }
+}
+impl fmt::Display for TaskError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { context: Some(context), source } => write!(f, "{context}: {source}"),
+            Self::Io { context: None, source } => write!(f, "{source}"),
+            Self::Ureq { context, source } => write!(f, "{context}: {source}"),
+            Self::Forbidden { context, .. } => write!(f, "{context}: 403 Forbidden"),
+            Self::TooManyRequests { context, .. } => write!(f, "{context}: 429 Too Many Requests"),
+            Self::Rejected { context, message } => write!(f, "{context}: rejected: {message}"),
+            Self::InvalidPriority(priority) => write!(f, "invalid task priority: {priority} (must be from -10 to 10)"),
+        }
     }
 }
-impl From<ureq::Error> for TaskError {
-    fn from(value: ureq::Error) -> Self {
-        match value {
-            ureq::Error::Status(403, resp) => Self::Forbidden(resp),
-            _ => Self::Ureq(value)
+impl std::error::Error for TaskError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Ureq { source, .. } => Some(source),
+            Self::Forbidden { .. } | Self::TooManyRequests { .. } | Self::Rejected { .. } | Self::InvalidPriority(_) => None,
         }
     }
 }
+impl client::sealed::FailedResponse for TaskError {
+    fn failed_response(&self) -> Option<&ureq::Response> {
+        match self {
+            Self::Ureq { source: ureq::Error::Status(_, resp), .. } | Self::Forbidden { response: resp, .. } | Self::TooManyRequests { response: resp, .. } => Some(resp),
+            _ => None,
+        }
+    }
+    
+    fn failed_context(&self) -> Option<&RequestContext> {
+        self.context()
+    }
+    
+    fn failed_transport(&self) -> Option<&ureq::Transport> {
+        match self {
+            Self::Ureq { source: ureq::Error::Transport(transport), .. } => Some(transport),
+            _ => None,
+        }
+    }
+}
+impl From<std::io::Error> for TaskError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io { context: None, source: value }
+    }
+}
 
 /// Task commands available on the Internet Archive.
 #[derive(Debug, Clone, PartialEq)]
@@ -272,3 +437,67 @@ impl Status {
         }
     }
 }
+
+/// Priority of a task, from -10 to +10 (inclusive).
+/// 
+/// Tasks with a higher priority are run before tasks with a lower priority. Tasks are queued with a
+/// priority of 0 unless requested otherwise. Negative priorities can be used to keep bulk operations
+/// from delaying other tasks, while positive priorities may be refused for users without elevated
+/// privileges.
+/// 
+/// # Example
+/// ```rust
+/// use iars::tasks::Priority;
+/// 
+/// let priority = Priority::new(-5)?;
+/// assert_eq!(priority.get(), -5);
+/// 
+/// assert!(Priority::new(11).is_err());
+/// # Ok::<(), iars::tasks::TaskError>(())
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Priority(i8);
+impl Priority {
+    /// Lowest priority.
+    pub const MIN: Self = Self(-10);
+    
+    /// Priority used when none is provided.
+    pub const DEFAULT: Self = Self(0);
+    
+    /// Highest priority.
+    pub const MAX: Self = Self(10);
+    
+    /// Creates a priority.
+    /// 
+    /// # Errors
+    /// If `priority` is outside of -10 to +10, a [`TaskError::InvalidPriority`] is returned.
+    pub fn new(priority: isize) -> Result<Self, TaskError> {
+        if !(Self::MIN.get()..=Self::MAX.get()).contains(&priority) {
+            return Err(TaskError::InvalidPriority(priority));
+        }
+        
+        Ok(Self(priority as i8))
+    }
+    
+    /// Returns the priority as a number.
+    pub fn get(self) -> isize {
+        self.0 as isize
+    }
+}
+impl TryFrom<isize> for Priority {
+    type Error = TaskError;
+    
+    fn try_from(value: isize) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+impl From<Priority> for isize {
+    fn from(value: Priority) -> Self {
+        value.get()
+    }
+}
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
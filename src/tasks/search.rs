@@ -1,7 +1,8 @@
 use std::cmp::min;
 use std::collections::HashMap;
 use serde::{Deserialize, Deserializer};
-use crate::{Credentials, DEFAULT_USER_AGENT};
+use crate::{Credentials, default_user_agent};
+use crate::client::{self, Client, RawResponse, RequestContext};
 use crate::headers::RequestHeaderExt;
 use crate::tasks::{Command, Status, TaskError};
 
@@ -46,10 +47,8 @@ pub enum Filter {
     /// Wildcards (`*` or `%`) can be used in this filter.
     Submitter(String),
     
-    /// Priority of the task.
-    /// 
-    /// Typically a number from -10 to +10 (inclusive), with 0 as the default.
-    Priority(isize),
+    /// [Priority][`crate::tasks::Priority`] of the task.
+    Priority(crate::tasks::Priority),
     
     /// The current [state][Status] of the task.
     State(Status),
@@ -84,26 +83,33 @@ impl From<Status> for Filter {
 pub struct Request {
     credentials: Option<Credentials>,
     useragent: String,
+    client: Client,
     filters: HashMap<String, String>,
     summary: bool,
     catalog: bool,
     history: bool,
     limit: usize,
+    params: Vec<(String, String)>,
 }
 impl Default for Request {
     fn default() -> Self {
         Self {
             credentials: None,
-            useragent: DEFAULT_USER_AGENT.to_string(),
+            useragent: default_user_agent(),
+            client: Client::shared(),
             filters: Default::default(),
             summary: true,
             catalog: false,
             history: false,
             limit: 50,
+            params: vec![],
         }
     }
 }
 impl Request {
+    /// Maximum number of tasks the Internet Archive returns from a single request.
+    pub const MAX_LIMIT_PER_CALL: usize = 500;
+    
     pub fn new() -> Self {
         Self::default()
     }
@@ -122,13 +128,21 @@ impl Request {
     
     /// Configures the User-Agent string provided in this request.
     /// 
-    /// If `None` or if the string is empty, a [default][`DEFAULT_USER_AGENT`] will be used.
+    /// If `None` or if the string is empty, a [default][`default_user_agent`] will be used.
     pub fn with_useragent(mut self, useragent: Option<String>) -> Self {
-        if useragent.is_none() || useragent.as_ref().unwrap().is_empty() {
-            self.useragent = DEFAULT_USER_AGENT.to_string();
-        } else {
-            self.useragent = useragent.unwrap();
-        }
+        self.useragent = match useragent {
+            Some(useragent) if !useragent.is_empty() => useragent,
+            _ => default_user_agent(),
+        };
+        
+        self
+    }
+    
+    /// Configures the [`Client`] used to perform this request.
+    /// 
+    /// By default, the [shared client][`Client::shared`] is used.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
         
         self
     }
@@ -155,12 +169,15 @@ impl Request {
     /// 
     /// This number is the combined total between both the catalog and history categories.
     /// 
-    /// Any limit above 500 will be clamped to 500. Limits of 0 are permitted, but wasteful. If only the summary category
-    /// is needed, the caller should use [with_categories][`Request::with_categories`] instead.
+    /// The Internet Archive returns at most [`Request::MAX_LIMIT_PER_CALL`] tasks per request. For larger limits,
+    /// [`Request::call`] performs several requests, following the cursor between them, and merges their
+    /// results until `limit` tasks have been returned, or there are no more.
+    /// Limits of 0 are permitted, but wasteful.
+    /// If only the summary category is needed, the caller should use [with_categories][`Request::with_categories`] instead.
     /// 
     /// This limit does _not_ have any affect on the results of the summary category.
     pub fn with_limit(mut self, limit: usize) -> Self {
-        self.limit = min(limit, 500);
+        self.limit = limit;
         
         self
     }
@@ -191,6 +208,16 @@ impl Request {
         self
     }
     
+    /// Adds an arbitrary query parameter to the request.
+    /// 
+    /// This allows parameters which aren't modeled by this crate yet to be used. Parameters are sent
+    /// as-is, after all other parameters, and do not replace parameters set using other methods.
+    pub fn with_param(mut self, key: &str, value: &str) -> Self {
+        self.params.push((key.to_string(), value.to_string()));
+        
+        self
+    }
+    
     /// Performs the request query to the Internet Archive.
     /// 
     /// On success, returns the [`Response`] data.
@@ -201,6 +228,13 @@ impl Request {
     /// The cursor can be provided in subsequent calls of this method, however, the request's parameters _must_ be the
     /// same as the request used to produce the cursor.
     /// 
+    /// If the [limit][`Request::with_limit`] exceeds [`Request::MAX_LIMIT_PER_CALL`], several requests are performed,
+    /// and their tasks are merged into a single response. The summary is taken from the first request, and the cursor
+    /// from the last, so the search can still be continued afterwards. As a cursor is only valid for identical parameters,
+    /// every request asks for a full page of [`Request::MAX_LIMIT_PER_CALL`] tasks, and the tasks of the last page which
+    /// exceed the limit are discarded. The returned cursor continues after the whole page, so those tasks are skipped if the
+    /// search is continued; use a multiple of [`Request::MAX_LIMIT_PER_CALL`] as the limit to avoid this.
+    /// 
     /// # Errors
     /// This may return [`TaskError::Ureq`] if a [`ureq::Error`] is encountered while performing the request. If the error
     /// is a 403 Forbidden, then [`TaskError::Forbidden`] is returned instead.
@@ -236,23 +270,79 @@ impl Request {
     /// # Ok::<(), iars::tasks::TaskError>(())
     /// ```
     pub fn call(&self, cursor: Option<String>) -> Result<Response, TaskError> {
-        let mut req = ureq::get("https://archive.org/services/tasks.php")
+        self.call_pages(cursor, |cursor| self.call_page(cursor))
+    }
+    
+    /// Merges the pages returned by `call_page` until the limit is reached, as described by [`Request::call`].
+    fn call_pages(&self, cursor: Option<String>, mut call_page: impl FnMut(Option<String>) -> Result<Response, TaskError>) -> Result<Response, TaskError> {
+        let mut merged = call_page(cursor)?;
+        merged.truncate(self.limit);
+        
+        let mut remaining = self.limit - (merged.catalog.len() + merged.history.len());
+        while remaining > 0 && merged.cursor.is_some() {
+            let mut resp = call_page(merged.cursor.take())?;
+            
+            // an empty page would otherwise be requested forever
+            let empty = resp.catalog.is_empty() && resp.history.is_empty();
+            resp.truncate(remaining);
+            remaining -= resp.catalog.len() + resp.history.len();
+            
+            merged.success &= resp.success;
+            merged.catalog.extend(resp.catalog);
+            merged.history.extend(resp.history);
+            merged.cursor = resp.cursor;
+            if empty {
+                break;
+            }
+        }
+        
+        Ok(merged)
+    }
+    
+    /// Performs a single request, for up to [`Request::MAX_LIMIT_PER_CALL`] tasks.
+    fn call_page(&self, cursor: Option<String>) -> Result<Response, TaskError> {
+        let (req, context) = self.request(cursor);
+        
+        let resp = self.client.call(req).map_err(|err| TaskError::ureq(&context, err))?;
+        
+        client::read_json(resp.into_reader()).map_err(|err| TaskError::io(&context, err))
+    }
+    
+    /// Performs the same request as [`Request::call`], but returns the [undecoded response][`RawResponse`].
+    /// 
+    /// Only a single request is performed, so at most [`Request::MAX_LIMIT_PER_CALL`] tasks are returned.
+    /// 
+    /// # Errors
+    /// See [`Request::call`].
+    pub fn call_raw(&self, cursor: Option<String>) -> Result<RawResponse, TaskError> {
+        let (req, context) = self.request(cursor);
+        let resp = self.client.call(req).map_err(|err| TaskError::ureq(&context, err))?;
+        
+        RawResponse::read(resp).map_err(|err| TaskError::io(&context, err))
+    }
+    
+    fn request(&self, cursor: Option<String>) -> (ureq::Request, RequestContext) {
+        let url = "https://archive.org/services/tasks.php";
+        let context = RequestContext::new("search", url, self.filters.get("identifier").map(|id| id.as_str()));
+        let mut req = self.client.request("GET", url)
             .set("user-agent", &self.useragent)
             .query_pairs(self.filters.iter().map(|(key, val)| (key.as_str(), val.as_str())))
             .query("summary", &(self.summary as usize).to_string())
             .query("catalog", &(self.catalog as usize).to_string())
             .query("history", &(self.history as usize).to_string())
-            .query("limit", &self.limit.to_string());
+            .query("limit", &min(self.limit, Self::MAX_LIMIT_PER_CALL).to_string());
         
         if let Some(cursor) = cursor {
             req = req.query("cursor", &cursor);
         }
         
+        req = req.query_pairs(self.params.iter().map(|(key, val)| (key.as_str(), val.as_str())));
+        
         if let Some(creds) = self.credentials.as_ref() {
             req = req.set_header(creds.into());
         }
         
-        Ok(req.call()?.into_json()?)
+        (req, context)
     }
 }
 
@@ -278,6 +368,13 @@ pub struct Response {
     /// See also: [API Docs](https://archive.org/developers/tasks.html#limits-and-the-cursor)
     pub cursor: Option<String>,
 }
+impl Response {
+    /// Discards the tasks past the first `max`, counting catalog tasks before history tasks.
+    fn truncate(&mut self, max: usize) {
+        self.catalog.truncate(max);
+        self.history.truncate(max - self.catalog.len());
+    }
+}
 impl From<InterimResponse> for Response {
     fn from(resp: InterimResponse) -> Self {
         Self {
@@ -325,6 +422,10 @@ pub struct CatalogEntry {
     #[serde(rename = "submittime")]
     pub submit_time: String,
     pub task_id: usize,
+    
+    /// All other fields provided for this task, which are not modeled by this crate.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// Contains the data of a single completed task.
@@ -343,6 +444,10 @@ pub struct HistoryEntry {
     #[serde(rename = "submittime")]
     pub submit_time: String,
     pub task_id: usize,
+    
+    /// All other fields provided for this task, which are not modeled by this crate.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// Total counts of active tasks matched in a search request, organized by the current [status][`Status`] of each task.
@@ -352,4 +457,56 @@ pub struct Summary {
     pub running: usize,
     pub error: usize,
     pub paused: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+    
+    /// Returns a page of `catalog` and `history` tasks, continued by a cursor.
+    fn page(catalog: usize, history: usize) -> Response {
+        let task = json!({ "args": {}, "cmd": "derive.php", "identifier": "test_item", "priority": 0, "server": "ia800000.us.archive.org", "status": "queued", "submitter": "test@example.com", "submittime": "2024-01-02 03:04:05", "task_id": 1, "finished": 0 });
+        
+        Response {
+            success: true,
+            catalog: vec![serde_json::from_value(task.clone()).unwrap(); catalog],
+            history: vec![serde_json::from_value(task).unwrap(); history],
+            summary: None,
+            cursor: Some("next".to_string()),
+        }
+    }
+    
+    #[test]
+    fn caps_merged_pages_at_limit() {
+        let mut calls = 0;
+        let resp = crate::tasks::search().with_limit(1234).call_pages(None, |_| {
+            calls += 1;
+            Ok(page(300, 200))
+        }).unwrap();
+        
+        assert_eq!(calls, 3);
+        assert_eq!(resp.catalog.len(), 834);
+        assert_eq!(resp.history.len(), 400);
+        assert_eq!(resp.cursor.as_deref(), Some("next"));
+    }
+    
+    #[test]
+    fn stops_at_last_page() {
+        let mut calls = 0;
+        let resp = crate::tasks::search().with_limit(1234).call_pages(None, |_| {
+            calls += 1;
+            let mut page = page(300, 200);
+            if calls == 2 {
+                page.history.truncate(50);
+                page.cursor = None;
+            }
+            
+            Ok(page)
+        }).unwrap();
+        
+        assert_eq!(calls, 2);
+        assert_eq!(resp.catalog.len() + resp.history.len(), 850);
+        assert_eq!(resp.cursor, None);
+    }
 }
\ No newline at end of file
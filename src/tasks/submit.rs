@@ -1,9 +1,304 @@
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use serde::Deserialize;
+use serde_json::json;
+use crate::{Credentials, default_user_agent};
+use crate::client::{Client, RequestContext};
+use crate::headers::Header::{ContentLength, ContentType};
+use crate::headers::RequestHeaderExt;
+use crate::tasks::{Command, Priority, TaskError};
 
+/// Request builder for submitting a new task.
+/// 
+/// Submitting tasks always requires [authentication][`crate::Credentials`], and the user must have
+/// permission to modify the item.
+/// 
+/// See also: [API Docs](https://archive.org/developers/tasks.html#submitting-a-task)
+#[derive(Debug, Clone, PartialEq)]
 pub struct Request {
-    
+    credentials: Option<Credentials>,
+    useragent: String,
+    client: Client,
+    identifier: String,
+    command: Command,
+    priority: Option<Priority>,
+    pacer: Option<Pacer>,
+}
+impl Default for Request {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 impl Request {
     pub fn new() -> Self {
-        todo!()
+        Self {
+            credentials: None,
+            useragent: default_user_agent(),
+            client: Client::shared(),
+            identifier: String::new(),
+            command: Command::Derive { remove_derived: String::new() },
+            priority: None,
+            pacer: None,
+        }
+    }
+    
+    /// Provide authentication credentials to be used with this request.
+    pub fn with_credentials(mut self, credentials: Option<Credentials>) -> Self {
+        self.credentials = credentials;
+        
+        self
+    }
+    
+    /// Configures the User-Agent string provided in this request.
+    /// 
+    /// If `None` or if the string is empty, a [default][`default_user_agent`] will be used.
+    pub fn with_useragent(mut self, useragent: Option<String>) -> Self {
+        self.useragent = match useragent {
+            Some(useragent) if !useragent.is_empty() => useragent,
+            _ => default_user_agent(),
+        };
+        
+        self
+    }
+    
+    /// Configures the [`Client`] used to perform this request.
+    /// 
+    /// By default, the [shared client][`Client::shared`] is used.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        
+        self
+    }
+    
+    /// Identifier of the item the task will be performed on.
+    pub fn with_identifier(mut self, identifier: &str) -> Self {
+        self.identifier = identifier.to_string();
+        
+        self
+    }
+    
+    /// The [command][`Command`] the task will perform. By default, a derive is performed.
+    pub fn with_command(mut self, command: Command) -> Self {
+        self.command = command;
+        
+        self
+    }
+    
+    /// [Priority][`Priority`] of the task.
+    /// 
+    /// If not provided, the Internet Archive's default priority (usually 0) is used.
+    pub fn with_priority(mut self, priority: Option<Priority>) -> Self {
+        self.priority = priority;
+        
+        self
+    }
+    
+    /// Configures a [`Pacer`], which delays submissions to stay below a rate limit.
+    /// 
+    /// The pacer is waited on at the start of every [call][`Request::call`]. Clones of a pacer share
+    /// their state, so one pacer can be given to every request (and thread) submitting tasks for the
+    /// same user. No pacer is used by default.
+    pub fn with_pacer(mut self, pacer: Option<Pacer>) -> Self {
+        self.pacer = pacer;
+        
+        self
+    }
+    
+    /// Submits the task to the Internet Archive.
+    /// 
+    /// On success, returns the [`Response`] containing the new task's ID.
+    /// 
+    /// # Errors
+    /// This may return [`TaskError::Ureq`] if a [`ureq::Error`] is encountered while performing the request. If the error
+    /// is a 403 Forbidden, then [`TaskError::Forbidden`] is returned instead.
+    /// 
+    /// If the Tasks API refuses to queue the task, a [`TaskError::Rejected`] is returned.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use iars::Credentials;
+    /// use iars::tasks::Command;
+    /// 
+    /// let resp = iars::tasks::submit()
+    ///     .with_credentials(Some(Credentials::new("accesskey", "secretkey")))
+    ///     .with_identifier("test_item")
+    ///     .with_command(Command::Derive { remove_derived: "*.jpg".into() })
+    ///     .call()?;
+    /// 
+    /// println!("queued task {}", resp.task_id);
+    /// # Ok::<(), iars::tasks::TaskError>(())
+    /// ```
+    pub fn call(&self) -> Result<Response, TaskError> {
+        if let Some(pacer) = self.pacer.as_ref() {
+            pacer.wait();
+        }
+        
+        let url = "https://archive.org/services/tasks.php";
+        let context = RequestContext::new("submit", url, Some(&self.identifier));
+        
+        let mut body = json!({
+            "identifier": self.identifier,
+            "cmd": self.command.name(),
+            "args": self.command.args(),
+        });
+        if let Some(priority) = self.priority {
+            body["priority"] = priority.get().into();
+        }
+        let body = body.to_string();
+        
+        let mut req = self.client.request("POST", url)
+            .set("user-agent", &self.useragent)
+            .set_header(ContentType("application/json".into()))
+            .set_header(ContentLength(body.len()));
+        
+        if let Some(creds) = self.credentials.as_ref() {
+            req = req.set_header(creds.into());
+        }
+        
+        let resp = match self.client.send(req, body.as_bytes()) {
+            Ok(resp) | Err(ureq::Error::Status(400 | 409, resp)) => resp,
+            Err(err) => return Err(TaskError::ureq(&context, err)),
+        };
+        let resp: InterimResponse = resp.into_json().map_err(|err| TaskError::io(&context, err))?;
+        
+        match resp.value {
+            Some(value) if resp.success => Ok(Response {
+                task_id: value.task_id,
+                log: value.log,
+            }),
+            _ => Err(TaskError::Rejected { context, message: resp.error.unwrap_or_default() }),
+        }
+    }
+    
+    /// Submits this task for each of the provided identifiers, waiting for `interval` between submissions.
+    /// 
+    /// The [identifier][`Request::with_identifier`] configured in this request is ignored. A failed
+    /// submission does not stop the remaining identifiers from being submitted. The result of each
+    /// submission is returned in the same order as the identifiers.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use iars::Credentials;
+    /// use iars::tasks::Command;
+    /// 
+    /// let results = iars::tasks::submit()
+    ///     .with_credentials(Some(Credentials::new("accesskey", "secretkey")))
+    ///     .with_command(Command::Derive { remove_derived: "*".into() })
+    ///     .call_each(&["item_one", "item_two", "item_three"], Duration::from_secs(2));
+    /// 
+    /// for (identifier, result) in results {
+    ///     match result {
+    ///         Ok(resp) => println!("{identifier}: task {}", resp.task_id),
+    ///         Err(err) => eprintln!("{identifier}: {err}"),
+    ///     }
+    /// }
+    /// ```
+    pub fn call_each(&self, identifiers: &[&str], interval: Duration) -> Vec<(String, Result<Response, TaskError>)> {
+        let mut results = Vec::with_capacity(identifiers.len());
+        for (i, identifier) in identifiers.iter().enumerate() {
+            if i > 0 {
+                thread::sleep(interval);
+            }
+            
+            let result = self.clone().with_identifier(identifier).call();
+            results.push((identifier.to_string(), result));
+        }
+        
+        results
+    }
+}
+
+/// Token bucket which paces task submissions.
+/// 
+/// The Internet Archive limits how quickly each user may queue tasks (see [`crate::tasks::rate_limit`]).
+/// Bulk submissions which exceed the limit start failing partway through, so a pacer can be [attached][`Request::with_pacer`]
+/// to submission requests to keep them below the limit instead.
+/// 
+/// A pacer permits a burst of submissions at once, after which one submission is permitted per interval.
+/// 
+/// # Example
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use iars::Credentials;
+/// use iars::tasks::Command;
+/// use iars::tasks::submit::Pacer;
+/// 
+/// // at most one submission every 2 seconds, after an initial burst of 10
+/// let request = iars::tasks::submit()
+///     .with_credentials(Some(Credentials::new("accesskey", "secretkey")))
+///     .with_command(Command::Derive { remove_derived: String::new() })
+///     .with_pacer(Some(Pacer::new(Duration::from_secs(2), 10)));
+/// 
+/// for identifier in ["item_one", "item_two", "item_three"] {
+///     request.clone().with_identifier(identifier).call()?;
+/// }
+/// # Ok::<(), iars::tasks::TaskError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Pacer {
+    interval: Duration,
+    burst: usize,
+    // time of the next submission if every submission were spaced by `interval`
+    next_at: Arc<Mutex<Option<Instant>>>,
+}
+impl Pacer {
+    /// Creates a pacer which permits `burst` submissions at once, and one submission per `interval` after that.
+    /// 
+    /// A `burst` of 1 (or 0) enforces a minimum delay of `interval` between submissions.
+    pub fn new(interval: Duration, burst: usize) -> Self {
+        Self {
+            interval,
+            burst: burst.max(1),
+            next_at: Default::default(),
+        }
+    }
+    
+    /// Blocks until another submission is permitted, and consumes it.
+    pub fn wait(&self) {
+        let burst = u32::try_from(self.burst - 1).unwrap_or(u32::MAX);
+        let tolerance = self.interval.checked_mul(burst).unwrap_or(Duration::MAX);
+        
+        let now = Instant::now();
+        let ready = {
+            let mut next_at = self.next_at.lock().unwrap_or_else(|err| err.into_inner());
+            let start = next_at.map_or(now, |next_at| next_at.max(now));
+            *next_at = Some(start + self.interval);
+            
+            start.checked_sub(tolerance).map_or(now, |ready| ready.max(now))
+        };
+        
+        thread::sleep(ready - now);
     }
+}
+impl PartialEq for Pacer {
+    /// Pacers are only equal if they share the same state.
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.next_at, &other.next_at)
+    }
+}
+
+/// Response data returned from a successful task [submission][`Request::call`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    /// ID of the newly queued task.
+    pub task_id: usize,
+    
+    /// URL of the task's log.
+    pub log: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InterimResponse {
+    #[serde(default)]
+    success: bool,
+    error: Option<String>,
+    value: Option<InterimValue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InterimValue {
+    task_id: usize,
+    log: Option<String>,
 }
\ No newline at end of file
@@ -0,0 +1,119 @@
+//! Scaffolding for end-to-end tests against the Internet Archive.
+//! 
+//! *Requires the `testing` feature.*
+//! 
+//! A [`TestItem`] wraps an [`Item`] with a unique identifier, which is placed in `test_collection`
+//! when it is created by the first upload. When the `TestItem` is dropped (including while a test is
+//! panicking), a [delete task][`Command::Delete`] is submitted for the item, so tests don't leave
+//! items behind. Items in `test_collection` are also removed automatically after 30 days, in case the
+//! cleanup fails.
+//! 
+//! To exercise an application's handling of failed requests without a real network fault, the [`faults`]
+//! module can inject latency, timeouts, server errors, and truncated responses into a client.
+//! 
+//! # Example
+//! ```rust,no_run
+//! use iars::Credentials;
+//! use iars::testing::TestItem;
+//! 
+//! let test = TestItem::new("mycrate_upload", Credentials::try_from_env().unwrap())?;
+//! 
+//! test.upload_file(false, &[], "hello.txt", "Hello, world!".as_bytes(), 13)?;
+//! assert!(test.metadata()?.file("hello.txt").is_some());
+//! 
+//! // the item is deleted here
+//! # Ok::<(), iars::ItemError>(())
+//! ```
+
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::{Credentials, Item, ItemError};
+use crate::tasks::{self, Command, TaskError};
+
+pub mod faults;
+
+/// An [`Item`] with a unique identifier, which is deleted when dropped.
+/// 
+/// Dereferences to the wrapped [`Item`].
+#[derive(Debug)]
+pub struct TestItem {
+    item: Item,
+    credentials: Credentials,
+    cleanup: bool,
+}
+impl TestItem {
+    /// Creates a test item with a unique identifier starting with `prefix`.
+    /// 
+    /// The identifier is made unique by appending the current time, process ID, and a counter. The
+    /// item doesn't exist until a file is uploaded to it, at which point it is created in
+    /// `test_collection`.
+    /// 
+    /// # Errors
+    /// If the resulting identifier is invalid (e.g. `prefix` contains invalid characters or is too
+    /// long), an [`ItemError::InvalidIdentifier`] is returned.
+    pub fn new(prefix: &str, credentials: Credentials) -> Result<Self, ItemError> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let identifier = format!("{prefix}_{secs:x}_{:x}_{}", std::process::id(), COUNTER.fetch_add(1, Ordering::Relaxed));
+        
+        let item = Item::new(&identifier)?
+            .with_credentials(Some(credentials.clone()))
+            .with_auto_make(true)
+            .with_test_collection(true);
+        
+        Ok(Self {
+            item,
+            credentials,
+            cleanup: true,
+        })
+    }
+    
+    /// Returns the wrapped [`Item`].
+    pub fn item(&self) -> &Item {
+        &self.item
+    }
+    
+    /// Prevents the item from being deleted, and returns it (e.g. to inspect it after a failed test).
+    pub fn keep(mut self) -> Item {
+        self.cleanup = false;
+        
+        self.item.clone()
+    }
+    
+    /// Submits the delete task immediately, instead of waiting for the `TestItem` to be dropped.
+    /// 
+    /// # Errors
+    /// Returns any [`TaskError`] encountered while submitting the task. This includes when the item
+    /// was never created.
+    pub fn cleanup(mut self) -> Result<(), TaskError> {
+        self.cleanup = false;
+        
+        self.submit_delete()
+    }
+    
+    fn submit_delete(&self) -> Result<(), TaskError> {
+        tasks::submit()
+            .with_credentials(Some(self.credentials.clone()))
+            .with_identifier(self.item.identifier())
+            .with_command(Command::Delete)
+            .call()
+            .map(|_| ())
+    }
+}
+impl Deref for TestItem {
+    type Target = Item;
+    
+    fn deref(&self) -> &Self::Target {
+        &self.item
+    }
+}
+impl Drop for TestItem {
+    fn drop(&mut self) {
+        if self.cleanup {
+            // errors can't be reported from here, and the item may never have been created
+            let _ = self.submit_delete();
+        }
+    }
+}
\ No newline at end of file
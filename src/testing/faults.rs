@@ -0,0 +1,206 @@
+//! Injection of random faults into a client's requests, for testing resilience locally.
+//! 
+//! A [`FaultInjector`] is [middleware][`Client::with_middleware`] which disrupts a configured fraction
+//! of the requests passing through it, using one of the enabled [faults][`Fault`]:
+//! * [Latency][`Fault::Latency`]: the request is delayed before being sent.
+//! * [Timeouts][`Fault::Timeout`]: the request fails with a [transport error][`ureq::Error::Transport`], without being sent.
+//! * [Server errors][`Fault::ServerError`]: a `5xx` response is returned, without sending the request.
+//! * [Truncated bodies][`Fault::Truncation`]: the request is sent, but its response body is cut short.
+//! 
+//! Injected responses pass through the client's [retry and circuit breaker policies][`crate::client`]
+//! like any other response, so an application's handling of transient failures can be exercised
+//! without a flaky network. Faults are chosen using a [seedable][`FaultInjector::with_seed`] generator,
+//! so a failing sequence can be reproduced.
+//! 
+//! # Example
+//! ```rust,no_run
+//! use iars::Item;
+//! use iars::testing::faults::FaultInjector;
+//! 
+//! let faults = FaultInjector::new(0.25).with_seed(42);
+//! let item = Item::new("test_item")?.with_client(faults.client());
+//! 
+//! let meta = item.metadata();
+//! println!("{:?}", faults.counts());
+//! # Ok::<(), iars::ItemError>(())
+//! ```
+
+use std::hash::{BuildHasher, Hasher, RandomState};
+use std::io::{self, Read};
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use ureq::{Middleware, MiddlewareNext, Request, Response};
+use crate::Client;
+use crate::search::scrape::SplitMix64;
+
+/// Kind of fault which can be injected into a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Fault {
+    /// The request is delayed by a random duration within the [latency range][`FaultInjector::with_latency`],
+    /// and then sent normally.
+    Latency,
+    
+    /// The request fails immediately with a [`std::io::ErrorKind::TimedOut`] transport error.
+    Timeout,
+    
+    /// The request isn't sent, and a `500`, `502`, `503`, or `504` response is returned instead.
+    ServerError,
+    
+    /// The request is sent, but the response body ends after a random number of bytes. The `content-length`
+    /// header still reports the full length of the body.
+    Truncation,
+}
+
+/// Number of requests seen and faults injected by a [`FaultInjector`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FaultCounts {
+    /// Number of requests which passed through the injector, including those with faults.
+    pub requests: u64,
+    pub latencies: u64,
+    pub timeouts: u64,
+    pub server_errors: u64,
+    pub truncations: u64,
+}
+
+/// Middleware which injects random faults into a fraction of requests. See the [module docs][`crate::testing::faults`].
+/// 
+/// Clones of an injector share the same generator and counts.
+#[derive(Debug, Clone)]
+pub struct FaultInjector {
+    rate: f64,
+    faults: Vec<Fault>,
+    latency: Range<Duration>,
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug)]
+struct State {
+    rng: SplitMix64,
+    counts: FaultCounts,
+}
+
+impl FaultInjector {
+    /// Creates an injector which disrupts roughly `rate` of all requests (e.g. `0.1` for 10%), using
+    /// every kind of [`Fault`].
+    /// 
+    /// `rate` is clamped between 0 and 1. The generator is seeded randomly.
+    pub fn new(rate: f64) -> Self {
+        Self {
+            rate: rate.clamp(0.0, 1.0),
+            faults: vec![Fault::Latency, Fault::Timeout, Fault::ServerError, Fault::Truncation],
+            latency: Duration::from_millis(100)..Duration::from_secs(2),
+            state: Arc::new(Mutex::new(State {
+                rng: SplitMix64(RandomState::new().build_hasher().finish()),
+                counts: FaultCounts::default(),
+            })),
+        }
+    }
+    
+    /// Sets the kinds of faults which may be injected. Each disrupted request uses one of them, chosen
+    /// uniformly at random.
+    /// 
+    /// If no faults are provided, no requests are disrupted.
+    pub fn with_faults(mut self, faults: &[Fault]) -> Self {
+        self.faults = faults.to_vec();
+        
+        self
+    }
+    
+    /// Sets the range of delays added by [`Fault::Latency`]. This is 100 milliseconds to 2 seconds by default.
+    pub fn with_latency(mut self, latency: Range<Duration>) -> Self {
+        self.latency = latency;
+        
+        self
+    }
+    
+    /// Seeds the generator, so the same faults are injected into the same sequence of requests.
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.lock().rng = SplitMix64(seed);
+        
+        self
+    }
+    
+    /// Returns a new [`Client`] which passes every request through this injector.
+    pub fn client(&self) -> Client {
+        Client::new().with_middleware(self.clone())
+    }
+    
+    /// Returns the number of requests seen and faults injected so far.
+    pub fn counts(&self) -> FaultCounts {
+        self.lock().counts
+    }
+    
+    /// Decides whether to disrupt the next request, and how.
+    fn choose(&self) -> (Option<Fault>, u64) {
+        let mut state = self.lock();
+        state.counts.requests += 1;
+        
+        // uses the top 53 bits, which an f64 represents exactly
+        let roll = (state.rng.next() >> 11) as f64 / (1u64 << 53) as f64;
+        let fault = (!self.faults.is_empty() && roll < self.rate)
+            .then(|| self.faults[(state.rng.next() % self.faults.len() as u64) as usize]);
+        
+        match fault {
+            Some(Fault::Latency) => state.counts.latencies += 1,
+            Some(Fault::Timeout) => state.counts.timeouts += 1,
+            Some(Fault::ServerError) => state.counts.server_errors += 1,
+            Some(Fault::Truncation) => state.counts.truncations += 1,
+            None => (),
+        }
+        
+        (fault, state.rng.next())
+    }
+    
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+impl Middleware for FaultInjector {
+    fn handle(&self, request: Request, next: MiddlewareNext) -> Result<Response, ureq::Error> {
+        let (fault, random) = self.choose();
+        
+        match fault {
+            None => next.handle(request),
+            Some(Fault::Latency) => {
+                let span = self.latency.end.saturating_sub(self.latency.start).as_millis() as u64;
+                thread::sleep(self.latency.start + Duration::from_millis(random % (span + 1)));
+                
+                next.handle(request)
+            },
+            Some(Fault::Timeout) => Err(io::Error::new(io::ErrorKind::TimedOut, format!("injected timeout for {}", request.url())).into()),
+            Some(Fault::ServerError) => {
+                let (status, text) = [(500, "Internal Server Error"), (502, "Bad Gateway"), (503, "Service Unavailable"), (504, "Gateway Timeout")][random as usize % 4];
+                
+                Response::new(status, text, "injected server error")
+            },
+            Some(Fault::Truncation) => truncate(next.handle(request)?, random),
+        }
+    }
+}
+
+/// Rebuilds `resp` with only part of its body, chosen using `random`.
+fn truncate(resp: Response, random: u64) -> Result<Response, ureq::Error> {
+    let status = resp.status();
+    let headers: Vec<(String, String)> = resp.headers_names().into_iter()
+        // the body is read decoded, so its original framing no longer applies
+        .filter(|name| !["content-encoding", "content-length", "transfer-encoding"].contains(&name.to_ascii_lowercase().as_str()))
+        .flat_map(|name| resp.all(&name).into_iter().map(|value| (name.clone(), value.to_string())).collect::<Vec<_>>())
+        .collect();
+    
+    let mut body = vec![];
+    resp.into_reader().read_to_end(&mut body)?;
+    let len = body.len();
+    body.truncate((random % (len as u64).max(1)) as usize);
+    
+    let mut builder = http::Response::builder().status(status);
+    for (name, value) in &headers {
+        builder = builder.header(name, value);
+    }
+    builder = builder.header("content-length", len);
+    
+    let resp = builder.body(body).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    
+    Ok(resp.into())
+}
\ No newline at end of file
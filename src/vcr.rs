@@ -0,0 +1,213 @@
+//! Recording and replaying of HTTP interactions, for deterministic tests.
+//! 
+//! *Requires the `vcr` feature.*
+//! 
+//! A [`Cassette`] is [middleware][`Client::with_middleware`] which either records every response a
+//! client receives into a fixture file, or replays previously recorded responses without contacting
+//! the Internet Archive at all. Tests can be written against real responses once, and then run in CI
+//! without network access.
+//! 
+//! Interactions are matched by their method and URL, in the order they were recorded. Request bodies
+//! and headers are not recorded, so fixtures never contain [credentials][`crate::Credentials`].
+//! 
+//! # Example
+//! ```rust,no_run
+//! use iars::Item;
+//! use iars::vcr::Cassette;
+//! 
+//! // records when IARS_VCR=record is set, and replays otherwise
+//! let cassette = Cassette::from_env("tests/fixtures/metadata.json")?;
+//! 
+//! let item = Item::new("test_item")?.with_client(cassette.client());
+//! let meta = item.metadata()?;
+//! 
+//! assert!(!meta.files().is_empty());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde::{Deserialize, Serialize};
+use ureq::{Middleware, MiddlewareNext, Request, Response};
+use crate::Client;
+
+/// Environment variable which selects the mode of [`Cassette::from_env`].
+pub const MODE_VAR: &str = "IARS_VCR";
+
+/// Whether a [`Cassette`] records or replays interactions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Requests are sent normally, and their responses are saved to the fixture file.
+    Record,
+    
+    /// Requests are answered from the fixture file, and never sent.
+    Replay,
+}
+
+/// A single recorded request and its response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub method: String,
+    pub url: String,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    
+    /// Response body, encoded as base64.
+    pub body: String,
+}
+
+/// Middleware which records or replays interactions. See the [module docs][`crate::vcr`].
+/// 
+/// Cassettes are cheap to clone, and clones share the same interactions.
+#[derive(Debug, Clone)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: Mode,
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    interactions: Vec<Interaction>,
+    used: Vec<bool>,
+}
+
+impl Cassette {
+    /// Creates a cassette which records interactions to the fixture file at `path`.
+    /// 
+    /// Any existing fixture is replaced. The file is rewritten after each interaction, so it is kept
+    /// even if a test panics.
+    pub fn record(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            mode: Mode::Record,
+            state: Default::default(),
+        }
+    }
+    
+    /// Creates a cassette which replays interactions from the fixture file at `path`.
+    /// 
+    /// # Errors
+    /// If the fixture cannot be read or parsed, an [`io::Error`] is returned.
+    pub fn replay(path: impl AsRef<Path>) -> io::Result<Self> {
+        let interactions: Vec<Interaction> = serde_json::from_slice(&fs::read(path.as_ref())?)?;
+        
+        Ok(Self {
+            path: path.as_ref().to_path_buf(),
+            mode: Mode::Replay,
+            state: Arc::new(Mutex::new(State {
+                used: vec![false; interactions.len()],
+                interactions,
+            })),
+        })
+    }
+    
+    /// Creates a cassette which records if the [`MODE_VAR`] environment variable is set to `record`,
+    /// and replays otherwise.
+    /// 
+    /// # Errors
+    /// See [`Cassette::replay`].
+    pub fn from_env(path: impl AsRef<Path>) -> io::Result<Self> {
+        match std::env::var(MODE_VAR) {
+            Ok(mode) if mode.eq_ignore_ascii_case("record") => Ok(Self::record(path)),
+            _ => Self::replay(path),
+        }
+    }
+    
+    /// Returns whether this cassette records or replays interactions.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+    
+    /// Returns a new [`Client`] which passes every request through this cassette.
+    /// 
+    /// Automatic retries are disabled, so that each recorded response is replayed exactly as it was received.
+    pub fn client(&self) -> Client {
+        Client::new()
+            .with_retries(0)
+            .with_middleware(self.clone())
+    }
+    
+    /// Returns every interaction recorded so far, or loaded from the fixture.
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.state.lock().unwrap().interactions.clone()
+    }
+    
+    /// Returns true if every loaded interaction has been replayed.
+    /// 
+    /// Tests can assert this to make sure their requests haven't changed since the fixture was recorded.
+    pub fn is_finished(&self) -> bool {
+        self.state.lock().unwrap().used.iter().all(|used| *used)
+    }
+    
+    fn record_response(&self, method: &str, url: &str, resp: Response) -> Result<Response, ureq::Error> {
+        let status = resp.status();
+        let headers: Vec<(String, String)> = resp.headers_names().into_iter()
+            // the body is stored decoded, so its original framing no longer applies
+            .filter(|name| !["content-encoding", "content-length", "transfer-encoding"].contains(&name.to_ascii_lowercase().as_str()))
+            .flat_map(|name| resp.all(&name).into_iter().map(|value| (name.clone(), value.to_string())).collect::<Vec<_>>())
+            .collect();
+        
+        let mut body = vec![];
+        resp.into_reader().read_to_end(&mut body)?;
+        
+        let interaction = Interaction {
+            method: method.to_string(),
+            url: url.to_string(),
+            status,
+            headers,
+            body: BASE64.encode(&body),
+        };
+        
+        let mut state = self.state.lock().unwrap();
+        state.interactions.push(interaction.clone());
+        state.used.push(true);
+        if let Some(parent) = self.path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_vec_pretty(&state.interactions).map_err(io::Error::from)?)?;
+        
+        to_response(&interaction)
+    }
+    
+    fn replay_response(&self, method: &str, url: &str) -> Result<Response, ureq::Error> {
+        let mut state = self.state.lock().unwrap();
+        let State { interactions, used } = &mut *state;
+        
+        let index = interactions.iter().enumerate()
+            .position(|(i, interaction)| !used[i] && interaction.method == method && interaction.url == url)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no recorded interaction for {method} {url}")))?;
+        used[index] = true;
+        
+        to_response(&interactions[index])
+    }
+}
+impl Middleware for Cassette {
+    fn handle(&self, request: Request, next: MiddlewareNext) -> Result<Response, ureq::Error> {
+        let method = request.method().to_string();
+        let url = request.url().to_string();
+        match self.mode {
+            Mode::Record => self.record_response(&method, &url, next.handle(request)?),
+            Mode::Replay => self.replay_response(&method, request.url()),
+        }
+    }
+}
+
+fn to_response(interaction: &Interaction) -> Result<Response, ureq::Error> {
+    let body = BASE64.decode(&interaction.body)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    
+    let mut builder = http::Response::builder().status(interaction.status);
+    for (name, value) in &interaction.headers {
+        builder = builder.header(name, value);
+    }
+    builder = builder.header("content-length", body.len());
+    
+    let resp = builder.body(body).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    
+    Ok(resp.into())
+}
\ No newline at end of file
@@ -0,0 +1,532 @@
+//! Retrieval of item view statistics.
+//! 
+//! The [Views API](https://archive.org/developers/views_api.html) provides counts of how many times
+//! an item has been viewed. A "view" is counted when a user loads the item's details page, streams
+//! or downloads one of its files, or otherwise accesses the item's content.
+//! 
+//! Statistics for a single item are most easily retrieved using [`Item::views`][`crate::Item::views`].
+//! 
+//! The Views API limits how many identifiers can be requested at once. [`Request::call_batched`] splits
+//! any number of identifiers into acceptable batches, and merges the results. Likewise, [`Request::call_detail_range`]
+//! splits long date ranges into windows, and merges the per-day statistics into one continuous series.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Formatter;
+use std::ops::Range;
+use serde::Deserialize;
+use crate::{Credentials, default_user_agent};
+use crate::client::{self, civil_from_days, days_from_civil, Client, RawResponse};
+use crate::headers::RequestHeaderExt;
+
+/// Maximum number of identifiers requested in a single call by [`Request::call_batched`].
+pub const MAX_BATCH_IDENTIFIERS: usize = 100;
+
+/// Maximum combined length of the identifiers requested in a single call by [`Request::call_batched`],
+/// keeping the request URL within common server limits.
+const MAX_BATCH_LEN: usize = 4000;
+
+/// Maximum number of days requested in a single call by [`Request::call_detail_range`].
+pub const MAX_WINDOW_DAYS: usize = 365;
+
+/// Errors which may occur while retrieving view statistics.
+#[derive(Debug)]
+pub enum ViewsError {
+    /// An error while performing [`std::io`] operations.
+    Io(std::io::Error),
+    
+    /// An error while processing a [`ureq`] request.
+    Ureq(ureq::Error),
+    
+    /// A [`ureq`] request was successful, but returned a 403 Forbidden error code.
+    Forbidden(ureq::Response),
+    
+    /// A [`ureq`] request was successful, but returned a 429 Too Many Requests error code.
+    /// 
+    /// This is only returned after any [automatic retries][`crate::client`] have been exhausted.
+    TooManyRequests(ureq::Response),
+    
+    /// Date range passed to [`Request::call_detail_range`] is invalid, either because a date isn't formatted
+    /// as `YYYY-MM-DD`, or because the range ends before it starts.
+    InvalidDateRange(String, String),
+}
+impl fmt::Display for ViewsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Ureq(err) => write!(f, "{err}"),
+            Self::Forbidden(_) => write!(f, "403 Forbidden"),
+            Self::TooManyRequests(_) => write!(f, "429 Too Many Requests"),
+            Self::InvalidDateRange(start, end) => write!(f, "invalid date range: {start} to {end}"),
+        }
+    }
+}
+impl std::error::Error for ViewsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Ureq(err) => Some(err),
+            Self::Forbidden(_) | Self::TooManyRequests(_) | Self::InvalidDateRange(_, _) => None,
+        }
+    }
+}
+impl client::sealed::FailedResponse for ViewsError {
+    fn failed_response(&self) -> Option<&ureq::Response> {
+        match self {
+            Self::Ureq(ureq::Error::Status(_, resp)) | Self::Forbidden(resp) | Self::TooManyRequests(resp) => Some(resp),
+            _ => None,
+        }
+    }
+    
+    fn failed_transport(&self) -> Option<&ureq::Transport> {
+        match self {
+            Self::Ureq(ureq::Error::Transport(transport)) => Some(transport),
+            _ => None,
+        }
+    }
+}
+impl From<std::io::Error> for ViewsError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+impl From<ureq::Error> for ViewsError {
+    fn from(value: ureq::Error) -> Self {
+        match value {
+            ureq::Error::Status(403, resp) => Self::Forbidden(resp),
+            ureq::Error::Status(429, resp) => Self::TooManyRequests(resp),
+            _ => Self::Ureq(value)
+        }
+    }
+}
+
+/// Request builder for retrieving view statistics of one or more items.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    credentials: Option<Credentials>,
+    useragent: String,
+    client: Client,
+    identifiers: Vec<String>,
+    range: Option<(String, String)>,
+}
+impl Default for Request {
+    fn default() -> Self {
+        Self {
+            credentials: None,
+            useragent: default_user_agent(),
+            client: Client::shared(),
+            identifiers: vec![],
+            range: None,
+        }
+    }
+}
+impl Request {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Provide authentication credentials to be used with this request.
+    /// 
+    /// View statistics are public, so credentials are not normally required.
+    pub fn with_credentials(mut self, credentials: Option<Credentials>) -> Self {
+        self.credentials = credentials;
+        
+        self
+    }
+    
+    /// Configures the User-Agent string provided in this request.
+    /// 
+    /// If `None` or if the string is empty, a [default][`default_user_agent`] will be used.
+    pub fn with_useragent(mut self, useragent: Option<String>) -> Self {
+        self.useragent = match useragent {
+            Some(useragent) if !useragent.is_empty() => useragent,
+            _ => default_user_agent(),
+        };
+        
+        self
+    }
+    
+    /// Configures the [`Client`] used to perform this request.
+    /// 
+    /// By default, the [shared client][`Client::shared`] is used.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        
+        self
+    }
+    
+    /// Adds an item identifier to the request.
+    pub fn with_identifier(mut self, identifier: &str) -> Self {
+        self.identifiers.push(identifier.to_string());
+        
+        self
+    }
+    
+    /// Adds multiple item identifiers to the request.
+    pub fn with_identifiers<S: AsRef<str>>(mut self, identifiers: impl IntoIterator<Item = S>) -> Self {
+        self.identifiers.extend(identifiers.into_iter().map(|identifier| identifier.as_ref().to_string()));
+        
+        self
+    }
+    
+    /// Performs the request query to the Internet Archive.
+    /// 
+    /// On success, returns the [`Summary`] of each requested identifier. Identifiers the Views API
+    /// has no data for are still included, but will have [`Summary::have_data`] set to false.
+    /// 
+    /// # Errors
+    /// This may return [`ViewsError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    /// 
+    /// If any [I/O errors][`std::io::Error`] occur or the response fails to be deserialized, a [`ViewsError::Io`] is returned.
+    pub fn call(&self) -> Result<HashMap<String, Summary>, ViewsError> {
+        Ok(self.client.call(self.request("short"))?.into_json()?)
+    }
+    
+    /// Performs the same request as [`Request::call`], split into as many calls as needed to stay within
+    /// the endpoint's limits.
+    /// 
+    /// Identifiers are requested in batches of at most [`MAX_BATCH_IDENTIFIERS`], and the results of
+    /// every batch are merged. Duplicate identifiers are only requested once.
+    /// 
+    /// # Errors
+    /// See [`Request::call`]. Results of batches completed before an error are discarded.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use iars::views::Request;
+    /// 
+    /// let identifiers: Vec<String> = (1..=1000).map(|i| format!("example_{i}")).collect();
+    /// let views = Request::new()
+    ///     .with_identifiers(&identifiers)
+    ///     .call_batched()?;
+    /// 
+    /// for (identifier, summary) in views {
+    ///     println!("{identifier}: {}", summary.all_time);
+    /// }
+    /// # Ok::<(), iars::views::ViewsError>(())
+    /// ```
+    pub fn call_batched(&self) -> Result<HashMap<String, Summary>, ViewsError> {
+        let mut summaries = HashMap::with_capacity(self.identifiers.len());
+        for batch in self.batches() {
+            summaries.extend(batch.call()?);
+        }
+        
+        Ok(summaries)
+    }
+    
+    /// Splits the identifiers of this request into requests which stay within the endpoint's limits.
+    fn batches(&self) -> Vec<Self> {
+        let mut seen = std::collections::HashSet::new();
+        let mut batches: Vec<Self> = vec![];
+        let mut len = 0;
+        for identifier in self.identifiers.iter().filter(|identifier| seen.insert(identifier.as_str())) {
+            match batches.last_mut() {
+                Some(batch) if batch.identifiers.len() < MAX_BATCH_IDENTIFIERS && len + identifier.len() < MAX_BATCH_LEN => {
+                    batch.identifiers.push(identifier.clone());
+                    len += identifier.len() + 1;
+                },
+                _ => {
+                    batches.push(Self { identifiers: vec![identifier.clone()], ..self.clone() });
+                    len = identifier.len() + 1;
+                },
+            }
+        }
+        
+        batches
+    }
+    
+    /// Performs the same request as [`Request::call`], but returns the [undecoded response][`RawResponse`].
+    /// 
+    /// # Errors
+    /// See [`Request::call`].
+    pub fn call_raw(&self) -> Result<RawResponse, ViewsError> {
+        Ok(RawResponse::read(self.client.call(self.request("short"))?)?)
+    }
+    
+    /// Performs the request query for detailed, per-day statistics.
+    /// 
+    /// Unlike [`Request::call`], the counts are broken down by the kind of traffic which produced
+    /// each view (see [`Detail`]).
+    /// 
+    /// # Errors
+    /// This may return [`ViewsError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+    /// 
+    /// If any [I/O errors][`std::io::Error`] occur or the response fails to be deserialized, a [`ViewsError::Io`] is returned.
+    pub fn call_detail(&self) -> Result<DetailResponse, ViewsError> {
+        Ok(self.client.call(self.request("long"))?.into_json()?)
+    }
+    
+    /// Performs the same request as [`Request::call_detail`], but returns the [undecoded response][`RawResponse`].
+    /// 
+    /// # Errors
+    /// See [`Request::call_detail`].
+    pub fn call_detail_raw(&self) -> Result<RawResponse, ViewsError> {
+        Ok(RawResponse::read(self.client.call(self.request("long"))?)?)
+    }
+    
+    /// Performs the request query for detailed, per-day statistics between two dates (`YYYY-MM-DD`,
+    /// inclusive).
+    /// 
+    /// The range is split into windows of at most [`MAX_WINDOW_DAYS`], which are requested in order.
+    /// The results are merged into a single response, with one entry in [`DetailResponse::days`] for
+    /// every day of the range. Days missing from the API's responses are given a count of zero.
+    /// 
+    /// # Errors
+    /// If either date is invalid, or `end` is before `start`, a [`ViewsError::InvalidDateRange`] is
+    /// returned. Otherwise, see [`Request::call_detail`].
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use iars::views::Request;
+    /// 
+    /// let resp = Request::new()
+    ///     .with_identifier("example")
+    ///     .call_detail_range("2018-01-01", "2023-12-31")?;
+    /// 
+    /// let detail = &resp.items["example"];
+    /// for (day, views) in resp.days.iter().zip(&detail.non_robot.per_day) {
+    ///     println!("{day}: {views}");
+    /// }
+    /// # Ok::<(), iars::views::ViewsError>(())
+    /// ```
+    pub fn call_detail_range(&self, start: &str, end: &str) -> Result<DetailResponse, ViewsError> {
+        let invalid = || ViewsError::InvalidDateRange(start.to_string(), end.to_string());
+        let (first, last) = match (parse_date(start), parse_date(end)) {
+            (Some(first), Some(last)) if first <= last => (first, last),
+            _ => return Err(invalid()),
+        };
+        
+        let days: Vec<String> = (first..=last).map(format_date).collect();
+        let mut merged = DetailResponse {
+            days: days.clone(),
+            items: HashMap::new(),
+        };
+        
+        for (i, window) in days.chunks(MAX_WINDOW_DAYS).enumerate() {
+            let offset = i * MAX_WINDOW_DAYS;
+            let req = Self {
+                range: Some((window[0].clone(), window[window.len() - 1].clone())),
+                ..self.clone()
+            };
+            merged.merge_window(first, offset..offset + window.len(), req.call_detail()?);
+        }
+        
+        for detail in merged.items.values_mut() {
+            for series in [&mut detail.non_robot, &mut detail.robot, &mut detail.unrecognized] {
+                series.sum_per_day_data = series.per_day.iter().sum();
+            }
+        }
+        
+        Ok(merged)
+    }
+    
+    fn request(&self, kind: &str) -> ureq::Request {
+        let mut req = self.client.request("GET", &format!("https://be-api.us.archive.org/views/v1/{kind}/{}", self.identifiers.join(",")))
+            .set("user-agent", &self.useragent);
+        
+        if let Some((start, end)) = self.range.as_ref() {
+            req = req.query("start_date", start).query("end_date", end);
+        }
+        
+        if let Some(creds) = self.credentials.as_ref() {
+            req = req.set_header(creds.into());
+        }
+        
+        req
+    }
+}
+
+/// Total view counts of a single item.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Summary {
+    /// Number of views since the item was created.
+    pub all_time: usize,
+    
+    /// Number of views within the last 30 days.
+    pub last_30day: usize,
+    
+    /// Number of views within the last 7 days.
+    pub last_7day: usize,
+    
+    /// False if the Views API has no statistics for the item, in which case all counts will be zero.
+    pub have_data: bool,
+}
+
+/// Response data returned from a detailed views [request][`Request::call_detail`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(from = "InterimDetailResponse")]
+pub struct DetailResponse {
+    /// Dates (`YYYY-MM-DD`) of each entry found in the [per-day][`Series::per_day`] counts.
+    pub days: Vec<String>,
+    
+    /// Detailed statistics of each requested identifier.
+    pub items: HashMap<String, Detail>,
+}
+impl DetailResponse {
+    /// Copies the per-day counts of `resp` which fall within `window` (indices into `self.days`, where
+    /// index 0 is the day `first`) into this response.
+    fn merge_window(&mut self, first: i64, window: Range<usize>, resp: DetailResponse) {
+        let len = self.days.len();
+        for (identifier, detail) in resp.items {
+            let entry = self.items.entry(identifier).or_insert_with(|| Detail {
+                non_robot: Series::zeroed(len, detail.non_robot.previous_days_total),
+                robot: Series::zeroed(len, detail.robot.previous_days_total),
+                unrecognized: Series::zeroed(len, detail.unrecognized.previous_days_total),
+                pre_2017_total: detail.pre_2017_total,
+            });
+            
+            for (day, index) in resp.days.iter().enumerate().filter_map(|(j, day)| Some((j, parse_date(day)? - first))) {
+                let Some(index) = usize::try_from(index).ok().filter(|index| window.contains(index)) else { continue };
+                
+                entry.non_robot.per_day[index] = detail.non_robot.per_day.get(day).copied().unwrap_or(0);
+                entry.robot.per_day[index] = detail.robot.per_day.get(day).copied().unwrap_or(0);
+                entry.unrecognized.per_day[index] = detail.unrecognized.per_day.get(day).copied().unwrap_or(0);
+            }
+        }
+    }
+}
+impl From<InterimDetailResponse> for DetailResponse {
+    fn from(resp: InterimDetailResponse) -> Self {
+        Self {
+            days: resp.days,
+            items: resp.ids.into_iter().map(|(id, item)| (id, item.detail)).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct InterimDetailResponse {
+    #[serde(default)]
+    days: Vec<String>,
+    #[serde(default)]
+    ids: HashMap<String, InterimDetail>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InterimDetail {
+    #[serde(default)]
+    detail: Detail,
+}
+
+/// View counts of a single item, broken down by the kind of traffic which produced them.
+/// 
+/// See also: [API Docs](https://archive.org/developers/views_api.html)
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Detail {
+    /// Views by human visitors.
+    pub non_robot: Series,
+    
+    /// Views by known robots and crawlers.
+    pub robot: Series,
+    
+    /// Views which could not be classified as either human or robot traffic.
+    pub unrecognized: Series,
+    
+    /// Views counted before 2017-01-01, when the current method of collecting statistics began.
+    /// These views are not classified by traffic kind.
+    #[serde(rename = "pre_20170101_total")]
+    pub pre_2017_total: usize,
+}
+
+/// View counts of one kind of traffic over time.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Series {
+    /// Number of views on each day listed in [`DetailResponse::days`].
+    pub per_day: Vec<usize>,
+    
+    /// Number of views before the first day listed in [`DetailResponse::days`].
+    pub previous_days_total: usize,
+    
+    /// Sum of all [per-day][`Series::per_day`] counts.
+    pub sum_per_day_data: usize,
+}
+impl Series {
+    fn zeroed(days: usize, previous_days_total: usize) -> Self {
+        Self {
+            per_day: vec![0; days],
+            previous_days_total,
+            sum_per_day_data: 0,
+        }
+    }
+}
+
+/// Parses a `YYYY-MM-DD` date into the number of days since the UNIX epoch.
+fn parse_date(date: &str) -> Option<i64> {
+    let mut parts = date.split('-');
+    let mut field = |len: usize| parts.next().filter(|part| part.len() == len && part.bytes().all(|b| b.is_ascii_digit()))?.parse::<i64>().ok();
+    let (year, month, day) = (field(4)?, field(2)?, field(2)?);
+    
+    let days = days_from_civil(year, month, day);
+    (parts.next().is_none() && civil_from_days(days) == (year, month, day)).then_some(days)
+}
+
+/// Formats a number of days since the UNIX epoch as a `YYYY-MM-DD` date.
+fn format_date(days: i64) -> String {
+    let (year, month, day) = civil_from_days(days);
+    
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn detail(per_day: &[usize]) -> Detail {
+        Detail {
+            non_robot: Series { per_day: per_day.to_vec(), previous_days_total: 7, sum_per_day_data: 0 },
+            robot: Series { per_day: per_day.iter().map(|views| views * 10).collect(), ..Default::default() },
+            ..Default::default()
+        }
+    }
+    
+    #[test]
+    fn round_trips_dates() {
+        assert_eq!(parse_date("1970-01-01"), Some(0));
+        assert_eq!(parse_date("1969-12-31"), Some(-1));
+        assert_eq!(parse_date("2020-02-29"), Some(18321));
+        assert_eq!(parse_date("2000-02-29"), Some(11016));
+        assert_eq!(civil_from_days(18321), (2020, 2, 29));
+        assert_eq!(format_date(18322), "2020-03-01");
+        
+        for days in -800_000..800_000 {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+        for date in ["2020-02-28", "2020-02-29", "2020-03-01", "2024-12-31", "1600-02-29"] {
+            assert_eq!(format_date(parse_date(date).unwrap()), date);
+        }
+    }
+    
+    #[test]
+    fn rejects_invalid_dates() {
+        for date in ["2021-02-29", "1900-02-29", "2020-13-01", "2020-04-31", "2020-00-10", "2020-1-01", "20-01-01", "2020-01-01-01", "2020-01-0a", ""] {
+            assert_eq!(parse_date(date), None, "{date}");
+        }
+    }
+    
+    #[test]
+    fn merges_windows() {
+        let first = parse_date("2020-02-27").unwrap();
+        let days: Vec<String> = (first..first + 5).map(format_date).collect();
+        let mut merged = DetailResponse { days, items: HashMap::new() };
+        
+        // the first window ends on the leap day, and each response includes a day outside of its window
+        merged.merge_window(first, 0..3, DetailResponse {
+            days: ["2020-02-27", "2020-02-28", "2020-02-29", "2020-03-01"].map(String::from).to_vec(),
+            items: HashMap::from([("a".to_string(), detail(&[1, 2, 3, 99]))]),
+        });
+        merged.merge_window(first, 3..5, DetailResponse {
+            days: ["2020-02-29", "2020-03-01", "2020-03-02"].map(String::from).to_vec(),
+            items: HashMap::from([("a".to_string(), detail(&[99, 4])), ("b".to_string(), detail(&[99, 5, 6]))]),
+        });
+        
+        let a = &merged.items["a"];
+        assert_eq!(a.non_robot.per_day, [1, 2, 3, 4, 0]);
+        assert_eq!(a.robot.per_day, [10, 20, 30, 40, 0]);
+        assert_eq!(a.non_robot.previous_days_total, 7);
+        assert_eq!(merged.items["b"].non_robot.per_day, [0, 0, 0, 5, 6]);
+        assert_eq!(merged.items["b"].unrecognized.per_day, [0; 5]);
+    }
+}
\ No newline at end of file
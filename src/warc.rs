@@ -0,0 +1,315 @@
+//! Streaming of WARC (Web ARChive) records.
+//! 
+//! Web crawls on the Internet Archive are stored as [WARC](https://iipc.github.io/warc-specifications/)
+//! files, which are often several gigabytes in size. A [`WarcReader`] parses the records of a WARC file
+//! one at a time as it is read, so a whole file never needs to be buffered in memory or on disk.
+//! 
+//! Gzipped WARC files (`.warc.gz`) are detected and decompressed automatically.
+//! 
+//! # Example
+//! ```rust,no_run
+//! use std::io::Read;
+//! use iars::Item;
+//! 
+//! let item = Item::new("test_crawl")?;
+//! let mut records = item.read_warc("test_crawl-00000.warc.gz")?;
+//! 
+//! while let Some(mut record) = records.next_record()? {
+//!     if record.record_type() == Some("response") {
+//!         let mut body = vec![];
+//!         record.read_to_end(&mut body)?;
+//! 
+//!         println!("{:?}: {} bytes", record.target_uri(), body.len());
+//!     }
+//! }
+//! # Ok::<(), iars::ItemError>(())
+//! ```
+
+use std::io::{self, BufRead, BufReader, Read};
+use flate2::read::MultiGzDecoder;
+use crate::item::{Item, ItemError};
+
+/// Streaming reader of the records within a WARC file.
+/// 
+/// Records are read using [`WarcReader::next_record`]. Any part of a record's body which is not read
+/// is skipped when the next record is requested.
+pub struct WarcReader<R: Read> {
+    inner: BufReader<R>,
+    remaining: u64,
+}
+impl<R: Read> WarcReader<R> {
+    /// Creates a reader of uncompressed WARC data.
+    pub fn new(reader: R) -> Self {
+        Self {
+            inner: BufReader::new(reader),
+            remaining: 0,
+        }
+    }
+    
+    /// Reads the next record's headers, returning `None` once the end of the data is reached.
+    /// 
+    /// # Errors
+    /// If the data is not a valid WARC record, an [`io::Error`] of kind [`io::ErrorKind::InvalidData`]
+    /// is returned.
+    pub fn next_record(&mut self) -> io::Result<Option<Record<'_, R>>> {
+        io::copy(&mut (&mut self.inner).take(self.remaining), &mut io::sink())?;
+        self.remaining = 0;
+        
+        // records are separated by two CRLFs, which are skipped along with any other blank lines
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.inner.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            if !line.trim().is_empty() {
+                break;
+            }
+        }
+        
+        let version = line.trim_end().to_string();
+        if !version.starts_with("WARC/") {
+            return Err(invalid(format!("expected WARC version line, found {version:?}")));
+        }
+        
+        let mut headers: Vec<(String, String)> = vec![];
+        loop {
+            line.clear();
+            if self.inner.read_line(&mut line)? == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            
+            let trimmed = line.trim_end_matches(['\r', '\n']);
+            if trimmed.is_empty() {
+                break;
+            }
+            
+            if trimmed.starts_with([' ', '\t']) {
+                match headers.last_mut() {
+                    Some((_, value)) => {
+                        value.push(' ');
+                        value.push_str(trimmed.trim());
+                    },
+                    None => return Err(invalid(format!("unexpected continuation line {trimmed:?}"))),
+                }
+            } else {
+                let (name, value) = trimmed.split_once(':')
+                    .ok_or_else(|| invalid(format!("malformed header line {trimmed:?}")))?;
+                headers.push((name.trim().to_string(), value.trim().to_string()));
+            }
+        }
+        
+        let content_length = headers.iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("Content-Length"))
+            .and_then(|(_, value)| value.parse().ok())
+            .ok_or_else(|| invalid("record is missing a valid Content-Length".to_string()))?;
+        self.remaining = content_length;
+        
+        Ok(Some(Record {
+            version,
+            headers,
+            content_length,
+            reader: self,
+        }))
+    }
+}
+impl<R: Read> WarcReader<MaybeGz<R>> {
+    /// Creates a reader of WARC data which may or may not be gzipped.
+    /// 
+    /// Compression is detected from the first bytes of the data.
+    pub fn detect(reader: R) -> io::Result<Self> {
+        let mut reader = BufReader::new(reader);
+        let reader = if reader.fill_buf()?.starts_with(&[0x1f, 0x8b]) {
+            MaybeGz::Gzip(MultiGzDecoder::new(reader))
+        } else {
+            MaybeGz::Plain(reader)
+        };
+        
+        Ok(Self::new(reader))
+    }
+}
+
+/// Reader of data which may be gzipped. See [`WarcReader::detect`].
+pub enum MaybeGz<R: Read> {
+    Plain(BufReader<R>),
+    Gzip(MultiGzDecoder<BufReader<R>>),
+}
+impl<R: Read> Read for MaybeGz<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(reader) => reader.read(buf),
+            Self::Gzip(reader) => reader.read(buf),
+        }
+    }
+}
+
+/// A single WARC record.
+/// 
+/// The record's body (the block) is read through the [`Read`] implementation, and is limited to the
+/// record's [content length][`Record::content_length`].
+pub struct Record<'a, R: Read> {
+    version: String,
+    headers: Vec<(String, String)>,
+    content_length: u64,
+    reader: &'a mut WarcReader<R>,
+}
+impl<R: Read> Record<'_, R> {
+    /// WARC version of the record (e.g. `WARC/1.0`).
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+    
+    /// All of the record's headers, in the order they appear.
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+    
+    /// Returns the value of the first header matching `name`, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+    
+    /// Type of the record (e.g. `warcinfo`, `request`, `response`, `metadata`).
+    pub fn record_type(&self) -> Option<&str> {
+        self.header("WARC-Type")
+    }
+    
+    /// URI the record was captured from.
+    pub fn target_uri(&self) -> Option<&str> {
+        self.header("WARC-Target-URI")
+    }
+    
+    /// Unique ID of the record.
+    pub fn record_id(&self) -> Option<&str> {
+        self.header("WARC-Record-ID")
+    }
+    
+    /// Capture time of the record, as an ISO 8601 timestamp.
+    pub fn date(&self) -> Option<&str> {
+        self.header("WARC-Date")
+    }
+    
+    /// Size of the record's body in bytes.
+    pub fn content_length(&self) -> u64 {
+        self.content_length
+    }
+}
+impl<R: Read> Read for Record<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let max = buf.len().min(usize::try_from(self.reader.remaining).unwrap_or(usize::MAX));
+        if max == 0 {
+            return Ok(0);
+        }
+        
+        let read = self.reader.inner.read(&mut buf[..max])?;
+        if read == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        self.reader.remaining -= read as u64;
+        
+        Ok(read)
+    }
+}
+
+impl Item {
+    /// Starts downloading a WARC file from this item, returning a [`WarcReader`] of its records.
+    /// 
+    /// The file is streamed as records are read, and gzipped files are decompressed automatically.
+    /// See the [module docs][`crate::warc`] for an example.
+    /// 
+    /// # Errors
+    /// See [`Item::download_file`]. Errors while reading records are returned by [`WarcReader::next_record`].
+    pub fn read_warc(&self, filepath: &str) -> Result<WarcReader<MaybeGz<Box<dyn Read + Send>>>, ItemError> {
+        let (download, context) = self.open_download("read_warc", filepath)?;
+        let reader: Box<dyn Read + Send> = Box::new(download.into_reader());
+        
+        WarcReader::detect(reader).map_err(|err| ItemError::io(&context, err))
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use flate2::{write::GzEncoder, Compression};
+    use super::*;
+    
+    const DATA: &[u8] = b"WARC/1.0\r\nWARC-Type: warcinfo\r\nContent-Length: 5\r\n\r\nhello\r\n\r\n\
+        WARC/1.1\r\nWARC-Type: response\r\nWARC-Target-URI: http://example.com/\r\n  continued\r\ncontent-length: 4\r\n\r\nbody\r\n\r\n";
+    
+    #[test]
+    fn reads_records() {
+        let mut reader = WarcReader::new(DATA);
+        
+        let mut record = reader.next_record().unwrap().unwrap();
+        assert_eq!(record.version(), "WARC/1.0");
+        assert_eq!(record.record_type(), Some("warcinfo"));
+        assert_eq!(record.content_length(), 5);
+        let mut body = String::new();
+        record.read_to_string(&mut body).unwrap();
+        assert_eq!(body, "hello");
+        
+        let record = reader.next_record().unwrap().unwrap();
+        assert_eq!(record.version(), "WARC/1.1");
+        assert_eq!(record.target_uri(), Some("http://example.com/ continued"));
+        assert_eq!(record.header("Content-Length"), Some("4"));
+        
+        assert!(reader.next_record().unwrap().is_none());
+    }
+    
+    #[test]
+    fn skips_unread_bodies() {
+        let mut reader = WarcReader::new(DATA);
+        
+        let mut record = reader.next_record().unwrap().unwrap();
+        let mut buf = [0; 2];
+        record.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"he");
+        
+        let mut record = reader.next_record().unwrap().unwrap();
+        assert_eq!(record.record_type(), Some("response"));
+        let mut body = vec![];
+        record.read_to_end(&mut body).unwrap();
+        assert_eq!(body, b"body");
+    }
+    
+    #[test]
+    fn detects_gzip() {
+        // each record is its own gzip member, as in .warc.gz files
+        let (first, second) = DATA.split_at(DATA.windows(8).rposition(|w| w == b"WARC/1.1").unwrap());
+        let mut gz = vec![];
+        for member in [first, second] {
+            let mut encoder = GzEncoder::new(vec![], Compression::default());
+            encoder.write_all(member).unwrap();
+            gz.extend(encoder.finish().unwrap());
+        }
+        
+        let mut reader = WarcReader::detect(gz.as_slice()).unwrap();
+        assert_eq!(reader.next_record().unwrap().unwrap().record_type(), Some("warcinfo"));
+        assert_eq!(reader.next_record().unwrap().unwrap().record_type(), Some("response"));
+        assert!(reader.next_record().unwrap().is_none());
+        
+        let mut reader = WarcReader::detect(DATA).unwrap();
+        assert_eq!(reader.next_record().unwrap().unwrap().record_type(), Some("warcinfo"));
+    }
+    
+    #[test]
+    fn rejects_invalid_records() {
+        let kind = |data: &[u8]| WarcReader::new(data).next_record().err().unwrap().kind();
+        
+        assert_eq!(kind(b"HTTP/1.1 200 OK\r\n\r\n"), io::ErrorKind::InvalidData);
+        assert_eq!(kind(b"WARC/1.0\r\nWARC-Type: response\r\n\r\n"), io::ErrorKind::InvalidData);
+        assert_eq!(kind(b"WARC/1.0\r\n continued\r\n\r\n"), io::ErrorKind::InvalidData);
+        assert_eq!(kind(b"WARC/1.0\r\nno colon\r\n\r\n"), io::ErrorKind::InvalidData);
+        assert_eq!(kind(b"WARC/1.0\r\nContent-Length: 1\r\n"), io::ErrorKind::UnexpectedEof);
+        
+        let mut reader = WarcReader::new(&b"WARC/1.0\r\nContent-Length: 10\r\n\r\nshort"[..]);
+        let mut record = reader.next_record().unwrap().unwrap();
+        assert_eq!(record.read_to_end(&mut vec![]).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
\ No newline at end of file
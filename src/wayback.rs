@@ -0,0 +1,440 @@
+//! Lookup of archived web pages in the Wayback Machine.
+//! 
+//! The Wayback Machine stores snapshots ("mementos") of web pages captured over time. This module
+//! provides two ways of finding those snapshots:
+//! * The [Availability API](https://archive.org/help/wayback_api.php), which cheaply returns the
+//!   single snapshot closest to a given timestamp. See [`available()`].
+//! * [Memento](https://mementoweb.org/guide/rfc/) TimeMaps, which list every snapshot of a URL.
+//!   See [`timemap()`].
+//! 
+//! Captures of new snapshots can also be requested using [Save Page Now][`save()`], and then
+//! tracked until they finish using [`wait_for_capture()`].
+//! 
+//! Timestamps are formatted as `YYYYMMDDhhmmss` strings (UTC). Shorter prefixes (e.g. `2006` or
+//! `200601`) are accepted wherever a timestamp is provided, and the missing digits are treated as
+//! the start of that period.
+
+use std::fmt;
+use std::fmt::Formatter;
+use std::thread;
+use std::time::{Duration, Instant};
+use serde::Deserialize;
+use crate::{Credentials, default_user_agent};
+use crate::client::{self, days_from_civil, Client};
+use crate::headers::RequestHeaderExt;
+
+/// Errors which may occur while using the Wayback Machine APIs.
+#[derive(Debug)]
+pub enum WaybackError {
+    /// An error while performing [`std::io`] operations.
+    Io(std::io::Error),
+    
+    /// An error while processing a [`ureq`] request.
+    Ureq(ureq::Error),
+}
+impl fmt::Display for WaybackError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Ureq(err) => write!(f, "{err}"),
+        }
+    }
+}
+impl std::error::Error for WaybackError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Ureq(err) => Some(err),
+        }
+    }
+}
+impl client::sealed::FailedResponse for WaybackError {
+    fn failed_response(&self) -> Option<&ureq::Response> {
+        match self {
+            Self::Ureq(ureq::Error::Status(_, resp)) => Some(resp),
+            _ => None,
+        }
+    }
+    
+    fn failed_transport(&self) -> Option<&ureq::Transport> {
+        match self {
+            Self::Ureq(ureq::Error::Transport(transport)) => Some(transport),
+            _ => None,
+        }
+    }
+}
+impl From<std::io::Error> for WaybackError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+impl From<ureq::Error> for WaybackError {
+    fn from(value: ureq::Error) -> Self {
+        Self::Ureq(value)
+    }
+}
+
+/// A snapshot returned by the [Availability API][`available()`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Snapshot {
+    /// True if the snapshot can be viewed.
+    pub available: bool,
+    
+    /// URL of the snapshot within the Wayback Machine.
+    pub url: String,
+    
+    /// Capture time of the snapshot, formatted as `YYYYMMDDhhmmss`.
+    pub timestamp: String,
+    
+    /// HTTP status code the page responded with when it was captured.
+    pub status: String,
+}
+
+/// Finds the snapshot of `url` closest to `timestamp`, using the Availability API.
+/// 
+/// If `timestamp` is `None`, the most recent snapshot is returned. If the URL has never been archived
+/// (or is excluded from the Wayback Machine), `None` is returned.
+/// 
+/// # Errors
+/// This may return [`WaybackError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+/// 
+/// If any [I/O errors][`std::io::Error`] occur or the response fails to be deserialized, a [`WaybackError::Io`] is returned.
+pub fn available(url: &str, timestamp: Option<&str>, useragent: Option<String>) -> Result<Option<Snapshot>, WaybackError> {
+    #[derive(Deserialize)]
+    struct AvailableResponse {
+        #[serde(default)]
+        archived_snapshots: ArchivedSnapshots,
+    }
+    
+    #[derive(Default, Deserialize)]
+    struct ArchivedSnapshots {
+        closest: Option<Snapshot>,
+    }
+    
+    let client = Client::shared();
+    let mut req = client.request("GET", "https://archive.org/wayback/available")
+        .query("url", url)
+        .set("user-agent", &useragent
+            .and_then(|s| if s.is_empty() { None } else { Some(s) })
+            .unwrap_or_else(default_user_agent)
+        );
+    
+    if let Some(timestamp) = timestamp {
+        req = req.query("timestamp", timestamp);
+    }
+    
+    let resp: AvailableResponse = client.call(req)?.into_json()?;
+    
+    Ok(resp.archived_snapshots.closest)
+}
+
+/// Retrieves the Memento [`TimeMap`] of `url`, listing every snapshot of it.
+/// 
+/// URLs which have never been archived will produce an empty TimeMap.
+/// 
+/// Popular URLs may have hundreds of thousands of snapshots. If only a single snapshot is needed,
+/// [`available()`] is much cheaper.
+/// 
+/// # Errors
+/// This may return [`WaybackError::Ureq`] if a [`ureq::Error`] is encountered while performing the
+/// request. A `404 Not Found` response is treated as an empty TimeMap instead.
+/// 
+/// If any [I/O errors][`std::io::Error`] occur while reading the response, a [`WaybackError::Io`] is returned.
+pub fn timemap(url: &str, useragent: Option<String>) -> Result<TimeMap, WaybackError> {
+    let client = Client::shared();
+    // passed as a parameter, so any query string or fragment of the URL is kept
+    let req = client.request("GET", "https://web.archive.org/web/timemap/link")
+        .query("url", url)
+        .set("user-agent", &useragent
+            .and_then(|s| if s.is_empty() { None } else { Some(s) })
+            .unwrap_or_else(default_user_agent)
+        );
+    
+    let resp = client.call(req);
+    
+    let body = match resp {
+        Ok(resp) => resp.into_string()?,
+        Err(ureq::Error::Status(404, _)) => String::new(),
+        Err(err) => return Err(err.into()),
+    };
+    
+    Ok(TimeMap::parse(&body))
+}
+
+/// A single snapshot listed in a [`TimeMap`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Memento {
+    /// URL of the snapshot within the Wayback Machine.
+    pub url: String,
+    
+    /// Capture time of the snapshot, formatted as `YYYYMMDDhhmmss`.
+    pub timestamp: String,
+    
+    /// Capture time of the snapshot, formatted as an HTTP date (e.g. `Tue, 20 Jun 2000 18:02:59 GMT`).
+    pub datetime: String,
+}
+
+/// List of every snapshot of a URL, sorted from oldest to newest.
+/// 
+/// See also: [RFC 7089](https://mementoweb.org/guide/rfc/#Pattern6)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TimeMap {
+    /// The URL that was archived.
+    pub original: Option<String>,
+    
+    /// Each snapshot of the [original][`TimeMap::original`] URL.
+    pub mementos: Vec<Memento>,
+}
+impl TimeMap {
+    /// Parses a TimeMap from its `application/link-format` representation.
+    pub fn parse(body: &str) -> Self {
+        let mut timemap = Self::default();
+        
+        for link in body.lines().map(|line| line.trim().trim_end_matches(',')).filter(|link| link.starts_with('<')) {
+            let Some((target, params)) = link[1..].split_once('>') else { continue };
+            
+            let mut rel = "";
+            let mut datetime = "";
+            for param in params.split(';').map(str::trim) {
+                if let Some((key, val)) = param.split_once('=') {
+                    match key.trim() {
+                        "rel" => rel = val.trim_matches('"'),
+                        "datetime" => datetime = val.trim_matches('"'),
+                        _ => (),
+                    }
+                }
+            }
+            
+            let rels: Vec<&str> = rel.split_whitespace().collect();
+            if rels.contains(&"original") {
+                timemap.original = Some(target.to_string());
+            } else if rels.contains(&"memento") {
+                let timestamp = target
+                    .split_once("/web/")
+                    .and_then(|(_, path)| path.split('/').next())
+                    .unwrap_or("")
+                    .to_string();
+                
+                timemap.mementos.push(Memento {
+                    url: target.to_string(),
+                    timestamp,
+                    datetime: datetime.to_string(),
+                });
+            }
+        }
+        
+        timemap
+    }
+    
+    /// Finds the snapshot captured closest in time to `timestamp` (before or after).
+    /// 
+    /// Returns `None` if the TimeMap is empty, or if `timestamp` is not a valid timestamp.
+    pub fn nearest(&self, timestamp: &str) -> Option<&Memento> {
+        let target = timestamp_to_secs(timestamp)?;
+        
+        self.mementos
+            .iter()
+            .filter_map(|memento| Some((timestamp_to_secs(&memento.timestamp)?, memento)))
+            .min_by_key(|(secs, _)| secs.abs_diff(target))
+            .map(|(_, memento)| memento)
+    }
+}
+
+/// Requests that the Wayback Machine capture a new snapshot of `url`, using Save Page Now.
+/// 
+/// Captures are performed asynchronously by the Internet Archive. On success, the job ID of the
+/// capture is returned, which can be provided to [`capture_status()`] or [`wait_for_capture()`].
+/// 
+/// # Authentication
+/// Save Page Now requires [`Credentials`].
+/// 
+/// # Errors
+/// This may return [`WaybackError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+/// 
+/// If any [I/O errors][`std::io::Error`] occur or the response fails to be deserialized, a [`WaybackError::Io`] is returned.
+pub fn save(url: &str, creds: &Credentials, useragent: Option<String>) -> Result<String, WaybackError> {
+    #[derive(Deserialize)]
+    struct SaveResponse {
+        job_id: String,
+    }
+    
+    let client = Client::shared();
+    let req = client.request("POST", "https://web.archive.org/save")
+        .set("user-agent", &useragent
+            .and_then(|s| if s.is_empty() { None } else { Some(s) })
+            .unwrap_or_else(default_user_agent)
+        )
+        .set("accept", "application/json")
+        .set_header(creds.into());
+    
+    let resp: SaveResponse = client.send_form(req, &[("url", url)])?.into_json()?;
+    
+    Ok(resp.job_id)
+}
+
+/// The current state of a [Save Page Now][`save()`] capture.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CaptureStatus {
+    /// The capture is queued or still in progress.
+    Pending,
+    
+    /// The capture finished successfully.
+    Success {
+        /// URL of the new snapshot within the Wayback Machine.
+        snapshot_url: String,
+        
+        /// Capture time of the new snapshot, formatted as `YYYYMMDDhhmmss`.
+        timestamp: String,
+    },
+    
+    /// The capture failed.
+    Error {
+        /// Machine-readable reason for the failure (e.g. `error:too-many-redirects`).
+        reason: String,
+        
+        /// Human-readable description of the failure, if provided.
+        message: Option<String>,
+    },
+}
+
+/// Retrieves the current [status][`CaptureStatus`] of a Save Page Now capture.
+/// 
+/// # Errors
+/// This may return [`WaybackError::Ureq`] if a [`ureq::Error`] is encountered while performing the request.
+/// 
+/// If any [I/O errors][`std::io::Error`] occur or the response fails to be deserialized, a [`WaybackError::Io`] is returned.
+pub fn capture_status(job_id: &str, creds: Option<&Credentials>, useragent: Option<String>) -> Result<CaptureStatus, WaybackError> {
+    #[derive(Deserialize)]
+    struct StatusResponse {
+        status: String,
+        original_url: Option<String>,
+        timestamp: Option<String>,
+        status_ext: Option<String>,
+        message: Option<String>,
+    }
+    
+    let client = Client::shared();
+    let mut req = client.request("GET", &format!("https://web.archive.org/save/status/{job_id}"))
+        .set("user-agent", &useragent
+            .and_then(|s| if s.is_empty() { None } else { Some(s) })
+            .unwrap_or_else(default_user_agent)
+        )
+        .set("accept", "application/json");
+    
+    if let Some(creds) = creds {
+        req = req.set_header(creds.into());
+    }
+    
+    let resp: StatusResponse = client.call(req)?.into_json()?;
+    
+    Ok(match (resp.status.as_str(), resp.timestamp, resp.original_url) {
+        ("pending", _, _) => CaptureStatus::Pending,
+        ("success", Some(timestamp), Some(original_url)) => CaptureStatus::Success {
+            snapshot_url: format!("https://web.archive.org/web/{timestamp}/{original_url}"),
+            timestamp,
+        },
+        (status, _, _) => CaptureStatus::Error {
+            reason: resp.status_ext.unwrap_or_else(|| status.to_string()),
+            message: resp.message,
+        },
+    })
+}
+
+/// Repeatedly checks the [status][`capture_status()`] of a Save Page Now capture until it has finished.
+/// 
+/// The status is checked once every `interval`. If `timeout` elapses before the capture finishes,
+/// the last status ([`CaptureStatus::Pending`]) is returned.
+/// 
+/// # Example
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use iars::Credentials;
+/// use iars::wayback::{self, CaptureStatus};
+/// 
+/// let creds = Credentials::new("accesskey", "secretkey");
+/// 
+/// let job_id = wayback::save("https://example.com/", &creds, None)?;
+/// match wayback::wait_for_capture(&job_id, Some(&creds), None, Duration::from_secs(5), None)? {
+///     CaptureStatus::Success { snapshot_url, .. } => println!("Captured: {snapshot_url}"),
+///     CaptureStatus::Error { reason, .. } => println!("Capture failed: {reason}"),
+///     CaptureStatus::Pending => unreachable!(),
+/// }
+/// # Ok::<(), iars::wayback::WaybackError>(())
+/// ```
+/// 
+/// # Errors
+/// Any error encountered while checking the status is returned immediately. See [`capture_status()`].
+pub fn wait_for_capture(job_id: &str, creds: Option<&Credentials>, useragent: Option<String>, interval: Duration, timeout: Option<Duration>) -> Result<CaptureStatus, WaybackError> {
+    let start = Instant::now();
+    loop {
+        let status = capture_status(job_id, creds, useragent.clone())?;
+        if status != CaptureStatus::Pending || timeout.is_some_and(|timeout| start.elapsed() + interval > timeout) {
+            return Ok(status);
+        }
+        
+        thread::sleep(interval);
+    }
+}
+
+/// Converts a (possibly partial) `YYYYMMDDhhmmss` timestamp into seconds since the UNIX epoch.
+fn timestamp_to_secs(timestamp: &str) -> Option<i64> {
+    if timestamp.len() < 4 || timestamp.len() > 14 || !timestamp.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    
+    let padded = format!("{timestamp:0<14}");
+    let field = |range: std::ops::Range<usize>| padded[range].parse::<i64>().unwrap();
+    
+    let days = days_from_civil(field(0..4), field(4..6).clamp(1, 12), field(6..8).max(1));
+    
+    Some(days * 86400 + field(8..10) * 3600 + field(10..12) * 60 + field(12..14))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn parses_timemap() {
+        let body = r#"<http://example.com/page?a=1>; rel="original",
+<https://web.archive.org/web/timemap/link/http://example.com/page?a=1>; rel="self"; type="application/link-format"; from="Tue, 20 Jun 2000 18:02:59 GMT",
+<https://web.archive.org>; rel="timegate",
+<https://web.archive.org/web/20000620180259/http://example.com/page?a=1>; rel="first memento"; datetime="Tue, 20 Jun 2000 18:02:59 GMT",
+<https://web.archive.org/web/20010305101000/http://example.com/page?a=1>; rel="memento"; datetime="Mon, 05 Mar 2001 10:10:00 GMT",
+<https://web.archive.org/web/20240101000000/http://example.com/page?a=1>; rel="last memento"; datetime="Mon, 01 Jan 2024 00:00:00 GMT"
+"#;
+        let timemap = TimeMap::parse(body);
+        
+        assert_eq!(timemap.original.as_deref(), Some("http://example.com/page?a=1"));
+        assert_eq!(timemap.mementos.len(), 3);
+        assert_eq!(timemap.mementos[0].timestamp, "20000620180259");
+        assert_eq!(timemap.mementos[0].datetime, "Tue, 20 Jun 2000 18:02:59 GMT");
+        assert_eq!(timemap.mementos[2].url, "https://web.archive.org/web/20240101000000/http://example.com/page?a=1");
+    }
+    
+    #[test]
+    fn skips_malformed_links() {
+        let body = "not a link\n<unterminated; rel=\"memento\"\n<https://web.archive.org/web/2001/x>;rel=memento;datetime=\"Mon, 01 Jan 2001 00:00:00 GMT\"";
+        let timemap = TimeMap::parse(body);
+        
+        assert_eq!(timemap.original, None);
+        assert_eq!(timemap.mementos.len(), 1);
+        assert_eq!(timemap.mementos[0].timestamp, "2001");
+    }
+    
+    #[test]
+    fn parses_empty_timemap() {
+        assert_eq!(TimeMap::parse(""), TimeMap::default());
+    }
+    
+    #[test]
+    fn finds_nearest_memento() {
+        let timemap = TimeMap::parse(r#"<https://web.archive.org/web/20000101000000/x>; rel="memento"
+<https://web.archive.org/web/20100101000000/x>; rel="memento"
+<https://web.archive.org/web/20200101000000/x>; rel="memento""#);
+        
+        assert_eq!(timemap.nearest("2012").map(|m| m.timestamp.as_str()), Some("20100101000000"));
+        assert_eq!(timemap.nearest("2019").map(|m| m.timestamp.as_str()), Some("20200101000000"));
+        assert_eq!(timemap.nearest("1999x"), None);
+    }
+}
\ No newline at end of file